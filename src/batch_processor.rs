@@ -0,0 +1,222 @@
+use crate::config::SimulationConfig;
+use crate::simulation::Simulation;
+use crate::stats::SimulationStats;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::Path;
+
+/// Mean and (population) standard deviation of one metric across runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct MeanStd {
+    pub mean: f32,
+    pub std: f32,
+}
+
+impl MeanStd {
+    pub fn of(values: impl Iterator<Item = f32> + Clone) -> Self {
+        let n = values.clone().count();
+        if n == 0 {
+            return Self::default();
+        }
+        let mean = values.clone().sum::<f32>() / n as f32;
+        let variance = values.map(|v| (v - mean).powi(2)).sum::<f32>() / n as f32;
+        Self {
+            mean,
+            std: variance.sqrt(),
+        }
+    }
+}
+
+/// Final stats of several seeded runs of one config, for Monte Carlo studies
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregateStats {
+    pub runs: usize,
+    pub population: MeanStd,
+    pub average_energy: MeanStd,
+    pub average_speed: MeanStd,
+    pub average_size: MeanStd,
+    pub species: MeanStd,
+}
+
+impl AggregateStats {
+    pub fn from_runs(runs: &[SimulationStats]) -> Self {
+        let metric = |f: fn(&SimulationStats) -> f32| MeanStd::of(runs.iter().map(f));
+        Self {
+            runs: runs.len(),
+            population: metric(|s| s.total_entities as f32),
+            average_energy: metric(|s| s.average_metrics.average_energy),
+            average_speed: metric(|s| s.average_metrics.average_speed),
+            average_size: metric(|s| s.average_metrics.average_size),
+            species: metric(|s| s.species.len() as f32),
+        }
+    }
+}
+
+/// Run one simulation per seed for `steps` steps, in parallel, and aggregate their final stats
+pub fn run_batch(
+    world_size: f32,
+    config: &SimulationConfig,
+    seeds: &[u64],
+    steps: u32,
+) -> AggregateStats {
+    let finals: Vec<SimulationStats> = seeds
+        .par_iter()
+        .map(|&seed| final_stats(world_size, config, seed, steps))
+        .collect();
+    AggregateStats::from_runs(&finals)
+}
+
+/// Final stats of one named config in a comparison
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparisonRow {
+    pub name: String,
+    pub stats: SimulationStats,
+}
+
+/// Run every config for the same steps from the same seed, in parallel, so differences in
+/// the results come from the configs alone
+pub fn compare_configs(
+    world_size: f32,
+    configs: &[(String, SimulationConfig)],
+    seed: u64,
+    steps: u32,
+) -> Vec<ComparisonRow> {
+    configs
+        .par_iter()
+        .map(|(name, config)| ComparisonRow {
+            name: name.clone(),
+            stats: final_stats(world_size, config, seed, steps),
+        })
+        .collect()
+}
+
+/// `compare_configs` over config files, each named by its path
+pub fn compare_config_files<P: AsRef<Path>>(
+    paths: &[P],
+    world_size: f32,
+    seed: u64,
+    steps: u32,
+) -> Result<Vec<ComparisonRow>, Box<dyn std::error::Error>> {
+    let configs = paths
+        .iter()
+        .map(|path| {
+            let config = SimulationConfig::load_from_file(path)?;
+            Ok((path.as_ref().display().to_string(), config))
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    Ok(compare_configs(world_size, &configs, seed, steps))
+}
+
+/// Side-by-side table of final metrics, one row per config
+pub fn format_comparison(rows: &[ComparisonRow]) -> String {
+    let width = rows
+        .iter()
+        .map(|row| row.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    let mut table = format!(
+        "{:<width$} {:>10} {:>10} {:>8} {:>8} {:>8}\n",
+        "config", "population", "energy", "speed", "size", "species"
+    );
+    for row in rows {
+        let metrics = &row.stats.average_metrics;
+        table.push_str(&format!(
+            "{:<width$} {:>10} {:>10.2} {:>8.3} {:>8.2} {:>8}\n",
+            row.name,
+            row.stats.total_entities,
+            metrics.average_energy,
+            metrics.average_speed,
+            metrics.average_size,
+            row.stats.species.len(),
+        ));
+    }
+    table
+}
+
+fn final_stats(
+    world_size: f32,
+    config: &SimulationConfig,
+    seed: u64,
+    steps: u32,
+) -> SimulationStats {
+    let mut sim = Simulation::new_seeded(world_size, config.clone(), seed);
+    for _ in 0..steps {
+        sim.update();
+    }
+    sim.stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_mean_matches_individual_runs() {
+        let mut config = SimulationConfig::default();
+        config.population.initial_entities = 40;
+        config.population.entity_scale = 1.0;
+        let seeds = [1, 2, 3, 4];
+
+        let aggregate = run_batch(200.0, &config, &seeds, 20);
+
+        // Runs are reproducible from their seed, so re-run each one alone
+        let populations: Vec<f32> = seeds
+            .iter()
+            .map(|&seed| {
+                let mut sim = Simulation::new_seeded(200.0, config.clone(), seed);
+                for _ in 0..20 {
+                    sim.update();
+                }
+                sim.world().len() as f32
+            })
+            .collect();
+        let mean = populations.iter().sum::<f32>() / 4.0;
+
+        assert_eq!(aggregate.runs, 4);
+        assert!((aggregate.population.mean - mean).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_compare_configs_differing_in_mutation_rate() {
+        let mut base = SimulationConfig::default();
+        base.population.initial_entities = 60;
+        base.population.entity_scale = 1.0;
+        let mut mutating = base.clone();
+        mutating.genetics.mutation_rate_scale = 5.0;
+
+        let dir = tempfile::tempdir().unwrap();
+        let paths = [
+            dir.path().join("base.json"),
+            dir.path().join("mutating.json"),
+        ];
+        base.save_to_file(&paths[0]).unwrap();
+        mutating.save_to_file(&paths[1]).unwrap();
+
+        let rows = compare_config_files(&paths, 200.0, 9, 30).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].name.ends_with("base.json"));
+        assert!(rows[1].name.ends_with("mutating.json"));
+
+        // Same seed and steps, so each row is the run that config gives on its own
+        let alone = final_stats(200.0, &base, 9, 30);
+        assert_eq!(rows[0].stats.total_entities, alone.total_entities);
+
+        let table = format_comparison(&rows);
+        assert_eq!(table.lines().count(), 3);
+        assert!(table.lines().nth(2).unwrap().contains("mutating.json"));
+    }
+
+    #[test]
+    fn test_mean_std() {
+        let stats = MeanStd::of([2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0].into_iter());
+        assert_eq!(
+            stats,
+            MeanStd {
+                mean: 5.0,
+                std: 2.0
+            }
+        );
+        assert_eq!(MeanStd::of(std::iter::empty()), MeanStd::default());
+    }
+}
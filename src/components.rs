@@ -18,6 +18,38 @@ pub struct Size {
     pub radius: f32,
 }
 
+/// Steps left before an entity may reproduce again. Entities without one may reproduce.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReproCooldown {
+    pub remaining: u32,
+}
+
+/// Steps in a row an entity has spent at or below the death threshold, 0 once it has fed.
+/// Entities that have never starved don't carry the component.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Starving {
+    pub steps: u32,
+}
+
+/// Steps an entity has lived. Entities without one count as mature.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Age {
+    pub steps: u32,
+}
+
+/// Identifies an entity for the whole run. Unlike `hecs::Entity`, never reused after despawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Uid(pub u64);
+
+impl Uid {
+    /// Take the next uid from `counter`, advancing it
+    pub fn take(counter: &mut u64) -> Self {
+        let uid = Uid(*counter);
+        *counter += 1;
+        uid
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Velocity {
     pub x: f32,
@@ -32,8 +64,12 @@ pub struct Color {
 }
 
 impl Color {
+    /// RGB for a hue in turns (wrapped into [0, 1), so 1.0 is red again) and a saturation
+    /// and value clamped to [0, 1]
     pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
-        let h = h * 6.0;
+        // Hue sextant in [0, 6); outside [0, 1) the sextant and the ramp within it disagree
+        let h = h.rem_euclid(1.0) * 6.0;
+        let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
         let c = v * s;
         let x = c * (1.0 - ((h % 2.0) - 1.0).abs());
         let m = v - c;
@@ -63,6 +99,8 @@ pub struct MovementStyle {
     pub separation_distance: f32, // Preferred distance from other flock members
     pub alignment_strength: f32, // How much to align with flock direction
     pub cohesion_strength: f32, // How much to move toward flock center
+    pub migration_heading: f32, // Preferred direction of travel for migrants, in radians
+    pub flock_kin_threshold: f32, // Max gene distance to a neighbour to flock with it (higher = less selective)
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -72,6 +110,31 @@ pub enum MovementType {
     Solitary,  // Avoid other entities
     Predatory, // Hunt for prey
     Grazing,   // Move slowly and steadily
+    Migratory, // Drift toward an inherited heading
+}
+
+impl MovementType {
+    pub const ALL: [MovementType; 6] = [
+        MovementType::Random,
+        MovementType::Flocking,
+        MovementType::Solitary,
+        MovementType::Predatory,
+        MovementType::Grazing,
+        MovementType::Migratory,
+    ];
+
+    pub fn random(rng: &mut impl rand::Rng) -> Self {
+        Self::ALL[rng.gen_range(0..Self::ALL.len())].clone()
+    }
+
+    /// Pick a type with probability proportional to its weight, in `ALL` order.
+    /// Falls back to a uniform pick if the weights are unusable (negative or all zero).
+    pub fn weighted(rng: &mut impl rand::Rng, weights: &[f32; 6]) -> Self {
+        match rand::distributions::WeightedIndex::new(weights) {
+            Ok(index) => Self::ALL[rng.sample(index)].clone(),
+            Err(_) => Self::random(rng),
+        }
+    }
 }
 
 // Utility structs for better organization
@@ -184,6 +247,62 @@ mod tests {
         assert!((white.b - 1.0).abs() < 0.001);
     }
 
+    fn assert_rgb(color: &Color, rgb: (f32, f32, f32)) {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        assert!(
+            close(color.r, rgb.0) && close(color.g, rgb.1) && close(color.b, rgb.2),
+            "got ({}, {}, {}), expected {:?}",
+            color.r,
+            color.g,
+            color.b,
+            rgb
+        );
+    }
+
+    #[test]
+    fn test_color_from_hsv_primaries_and_secondaries() {
+        let expected = [
+            (0.0, (1.0, 0.0, 0.0)),       // Red
+            (1.0 / 6.0, (1.0, 1.0, 0.0)), // Yellow
+            (2.0 / 6.0, (0.0, 1.0, 0.0)), // Green
+            (3.0 / 6.0, (0.0, 1.0, 1.0)), // Cyan
+            (4.0 / 6.0, (0.0, 0.0, 1.0)), // Blue
+            (5.0 / 6.0, (1.0, 0.0, 1.0)), // Magenta
+            (1.0, (1.0, 0.0, 0.0)),       // Red again
+        ];
+        for (hue, rgb) in expected {
+            assert_rgb(&Color::from_hsv(hue, 1.0, 1.0), rgb);
+        }
+        // Midway through each sextant one channel is at half
+        assert_rgb(&Color::from_hsv(1.0 / 12.0, 1.0, 1.0), (1.0, 0.5, 0.0));
+        assert_rgb(&Color::from_hsv(11.0 / 12.0, 1.0, 1.0), (1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_color_from_hsv_wraps_hue_and_clamps_inputs() {
+        let inside = Color::from_hsv(0.2, 0.8, 0.6);
+        assert_rgb(
+            &Color::from_hsv(1.2, 0.8, 0.6),
+            (inside.r, inside.g, inside.b),
+        );
+        assert_rgb(
+            &Color::from_hsv(-0.8, 0.8, 0.6),
+            (inside.r, inside.g, inside.b),
+        );
+        assert_rgb(&Color::from_hsv(-1.0 / 12.0, 1.0, 1.0), (1.0, 0.0, 0.5));
+        assert_rgb(&Color::from_hsv(0.5, 2.0, 3.0), (0.0, 1.0, 1.0));
+        assert_rgb(&Color::from_hsv(0.5, -1.0, -1.0), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_color_from_hsv_grayscale() {
+        for v in [0.0, 0.25, 0.5, 1.0] {
+            for hue in [0.0, 0.3, 0.7] {
+                assert_rgb(&Color::from_hsv(hue, 0.0, v), (v, v, v));
+            }
+        }
+    }
+
     #[test]
     fn test_vec2_creation() {
         let vec = Vec2::new(3.0, 4.0);
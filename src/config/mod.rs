@@ -1,16 +1,44 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
+mod presets;
+mod schema;
+pub mod watch;
+
+pub use schema::ConfigError;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PopulationConfig {
     pub entity_scale: f32,
     pub max_population: u32,
     pub initial_entities: usize,
     pub spawn_radius_factor: f32,
+    pub spawn_pattern: SpawnPattern, // Initial spatial layout within the spawn radius
+    pub initial_movement_type_weights: [f32; 6], // Relative share of each movement type at spawn, in `MovementType::ALL` order
+    pub target_population: u32, // Population the homeostatic controller steers toward, scaled like `max_population`; 0 disables it
+    pub homeostasis_gain: f32, // How strongly the controller reacts to the relative error from the target
+    pub min_viable_population: u32, // Random entities are reseeded below this population, scaled like `max_population`; 0 disables it
+    pub reseed_count: u32,          // Most entities one reseed adds, never past the floor
+}
+
+/// Spatial layout of the initial population
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum SpawnPattern {
+    #[default]
+    Disc,
+    UniformSquare,
+    Grid,
+    Clusters {
+        n: usize,
+    },
+    Ring,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PhysicsConfig {
     pub max_velocity: f32,
     pub max_entity_radius: f32,
@@ -20,15 +48,64 @@ pub struct PhysicsConfig {
     pub interaction_radius_offset: f32,
     pub velocity_bounce_factor: f32,
     pub center_pressure_strength: f32,
+    pub max_interactions_per_step: usize, // Prey an entity may eat in a single step
+    pub substeps: u32, // Position integration increments per step; raise for stability at high speed
+    pub center_pressure_inner_radius: f32, // No center pressure within this distance of the center
+    pub edge_ramp_distance: f32, // Pressure ramps up within this distance of a wall
+    pub edge_multiplier_max: f32, // Extra pressure multiplier reached at the wall
+    pub soft_walls_only: bool, // Drop the constant inward pull and keep only the edge ramp
+    pub incremental_grid: bool, // Move only entities that changed cell instead of rebuilding the grid each step
+    pub grid_cell_capacity: usize, // Neighbour queries subsample cells fuller than this; 0 = no limit
+    pub max_neighbors: usize, // Closest neighbours each entity considers per step; 0 = no limit
+    pub velocity_damping: f32, // Fraction of velocity lost to drag each step
+    pub symmetric_interactions: bool, // Settle claimed meals one predator at a time, so an eaten predator eats nothing
+    pub diffusion_strength: f32,      // Largest random kick added to velocity each step; 0 disables
+    pub query_radius_margin: f32, // Neighbour queries reach this many steps at max_velocity beyond the sense radius
+    pub allow_cannibalism: bool,  // When off, entities never eat prey they recognise as kin
+    pub kin_recognition_threshold: f32, // Gene similarity below which prey counts as kin
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EnergyConfig {
     pub size_energy_cost_factor: f32,
     pub movement_energy_cost: f32,
+    pub max_energy_factor: f32, // Energy ceiling in units of 100 energy, shared by feeding and growth
+    pub sense_cost_factor: f32, // Per-step upkeep per unit of sense radius
+    pub turning_energy_cost: f32, // Energy per radian of heading change per unit of speed; 0 disables
+    pub initial_energy_min: f32,  // Starting energy of spawned entities is drawn from min..=max
+    pub initial_energy_max: f32,
+    pub max_energy_ratio: f32, // Max energy as a multiple of starting energy
+    pub size_growth: SizeGrowth, // How body radius follows energy
+    pub size_growth_rate: f32, // Saturating growth: how quickly radius approaches the asymptote per unit of energy
+    pub size_asymptote: f32,   // Saturating growth: radius approached as energy grows
+    pub predation_energy_overhead: f32, // Handling cost subtracted from the energy of every meal
+    pub death_energy_threshold: f32, // Entities at or below this energy die, once any starvation grace runs out
+    pub metabolic_floor: f32,        // Least upkeep per step, however efficient the entity
+    pub meal_gain_factor: f32, // Share of the prey's energy a meal yields, before the size and preference bonuses
+    pub prey_size_bonus: f32,  // Extra gain per unit of prey-to-predator radius ratio
+    pub max_size_bonus: f32, // Cap on the size multiplier, so ever larger prey give diminishing returns
+    pub starvation_grace: u32, // Steps an entity survives at or below the death threshold; 0 = dies at once
+}
+
+/// Mapping from an entity's energy to its body radius
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum SizeGrowth {
+    #[default]
+    Linear, // Radius tracks energy exactly
+    Saturating, // Radius rises quickly at first then levels off toward `size_asymptote`
+}
+
+/// Where a newborn's starting energy comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ChildEnergyMode {
+    #[default]
+    Reset, // A fixed share of the parent's max energy; the parent pays `reproduction_energy_cost`
+    Inherit, // A share of the parent's current energy, transferred out of the parent
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ReproductionConfig {
     pub reproduction_energy_threshold: f32,
     pub reproduction_energy_cost: f32,
@@ -37,52 +114,305 @@ pub struct ReproductionConfig {
     pub population_density_factor: f32,
     pub min_reproduction_chance: f32,
     pub death_chance_factor: f32,
+    pub reproduction_cooldown: u32, // Steps after reproducing before an entity may reproduce again
+    pub maturity_age: u32,          // Steps after birth before an entity may reproduce
+    pub child_energy_mode: ChildEnergyMode, // Whether `child_energy_factor` scales max or current energy
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneticsConfig {
+    pub diploid: bool,            // Carry two allele sets and reproduce sexually
+    pub dominance: f32, // 0.5 = additive, 1.0 = higher allele dominant, 0.0 = lower allele dominant
+    pub mutation_rate_scale: f32, // Global multiplier on every entity's heritable mutation rate
+    pub generational: bool, // Fixed-size generations resampled by fitness instead of continuous births
+    pub generation_steps: u32, // Steps per generation in generational mode
+    pub locked_genes: BTreeMap<String, f32>, // Traits fixed at a value by gene name: set at spawn, never mutated
+    pub trait_mutation_rates: BTreeMap<String, f32>, // Per-trait mutation rates by gene name, replacing the heritable rate; 0 = conserved
+}
+
+/// What generational mode selects for: each entity's fitness over a generation is the
+/// weighted sum of these components
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FitnessConfig {
+    pub energy_weight: f32, // Per unit of energy held, summed over every step lived
+    pub lifespan_weight: f32, // Per step lived
+    pub offspring_weight: f32, // Per step the entity was ready to reproduce, since births wait for the next generation
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimingConfig {
+    pub steps_per_second: f32, // Fixed simulation rate, independent of display refresh rate
+    pub max_steps_per_frame: u32, // Cap on catch-up steps per frame; excess backlog is dropped
+    pub step_budget_ms: f32,   // Soft limit on the time one step takes; 0 = no limit
+    pub skip_stats_over_budget: bool, // Skip the metrics log on steps over the budget
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SeasonConfig {
+    pub enabled: bool,
+    pub period: u32,                    // Steps per full seasonal cycle
+    pub movement_cost_amplitude: f32, // Seasonal swing of movement_energy_cost, as a fraction of it
+    pub center_pressure_amplitude: f32, // Seasonal swing of center_pressure_strength
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BehaviorConfig {
+    pub state_machine: bool, // Switch between foraging, fleeing, mate seeking and resting by context
+    pub rest_energy_fraction: f32, // Below this fraction of max energy an entity rests
+    pub mate_energy_fraction: f32, // At or above this fraction of max energy an entity seeks a mate
+    pub rest_speed_factor: f32, // Share of last step's velocity a resting entity keeps
+    pub flock_cohesion_coefficient: f32, // Scales the pull toward the flock centre
+    pub flock_alignment_coefficient: f32, // Scales the match to the flock's mean velocity
+    pub flock_separation_coefficient: f32, // Scales the push away from crowding flockmates
+    pub torpor_threshold: f32, // Below this fraction of max energy an entity is torpid; 0 disables
+    pub torpor_speed_factor: f32, // Share of its intended velocity a torpid entity moves at
+    pub predator_speed_multiplier: f32, // Chase speed of a predator closing on prey, relative to its speed gene
+    pub energy_share_amount: f32, // Energy a highly social entity gives a starving kin neighbour each step; 0 disables sharing
+    pub energy_share_kin_threshold: f32, // Gene similarity below which a neighbour counts as kin for sharing
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderingConfig {
+    pub low_energy_style: LowEnergyStyle,
+    pub low_energy_threshold: f32, // Entities below this fraction of their max energy are marked
+    pub color_mode: ColorMode,
+    pub population_series_length: usize, // Samples kept for the live population charts; 0 disables them
+    pub population_sample_interval: u32, // Steps between population chart samples, each detecting species
+    pub trails: bool,                    // Draw fading trails behind entities
+    pub trail_length: usize,             // Positions kept per trail, current one included
+}
+
+/// What an entity's rendered colour shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ColorMode {
+    #[default]
+    Appearance, // Hue and saturation genes
+    Genotype, // Projection of the whole genotype, so genetic clusters stand apart
+}
+
+/// How the renderer marks entities close to starving
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum LowEnergyStyle {
+    #[default]
+    None,
+    Outline, // Red ring around the entity
+    Bar,     // Bar under the entity showing its energy fraction
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SimulationConfig {
     pub population: PopulationConfig,
     pub physics: PhysicsConfig,
     pub energy: EnergyConfig,
     pub reproduction: ReproductionConfig,
+    pub genetics: GeneticsConfig,
+    pub fitness: FitnessConfig,
+    pub timing: TimingConfig,
+    pub seasons: SeasonConfig,
+    pub behavior: BehaviorConfig,
+    pub rendering: RenderingConfig,
+}
+
+impl Default for PopulationConfig {
+    fn default() -> Self {
+        Self {
+            entity_scale: 0.5,
+            max_population: 10000,
+            initial_entities: 2500,
+            spawn_radius_factor: 0.2,
+            spawn_pattern: SpawnPattern::Disc,
+            initial_movement_type_weights: [1.0; 6],
+            target_population: 0,
+            homeostasis_gain: 2.0,
+            min_viable_population: 0,
+            reseed_count: 5,
+        }
+    }
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            max_velocity: 2.0,
+            max_entity_radius: 20.0,
+            min_entity_radius: 1.0,
+            grid_cell_size: 25.0,
+            boundary_margin: 5.0,
+            interaction_radius_offset: 15.0,
+            velocity_bounce_factor: 0.8,
+            center_pressure_strength: 0.3,
+            max_interactions_per_step: 1,
+            substeps: 1,
+            center_pressure_inner_radius: 10.0,
+            edge_ramp_distance: 50.0,
+            edge_multiplier_max: 8.0,
+            soft_walls_only: false,
+            incremental_grid: true,
+            grid_cell_capacity: 0,
+            max_neighbors: 20,
+            velocity_damping: 0.0,
+            symmetric_interactions: false,
+            diffusion_strength: 0.0,
+            query_radius_margin: 0.0,
+            allow_cannibalism: true,
+            kin_recognition_threshold: 0.1,
+        }
+    }
+}
+
+impl Default for EnergyConfig {
+    fn default() -> Self {
+        Self {
+            size_energy_cost_factor: 0.15,
+            movement_energy_cost: 0.1,
+            max_energy_factor: 1.5,
+            sense_cost_factor: 0.002,
+            turning_energy_cost: 0.0,
+            initial_energy_min: 15.0,
+            initial_energy_max: 75.0,
+            max_energy_ratio: 1.3,
+            size_growth: SizeGrowth::Linear,
+            size_growth_rate: 0.005,
+            size_asymptote: 15.0,
+            predation_energy_overhead: 0.5,
+            death_energy_threshold: 0.0,
+            metabolic_floor: 0.05,
+            meal_gain_factor: 0.3,
+            prey_size_bonus: 0.3,
+            max_size_bonus: 1.5,
+            starvation_grace: 0,
+        }
+    }
+}
+
+impl Default for ReproductionConfig {
+    fn default() -> Self {
+        Self {
+            reproduction_energy_threshold: 0.8,
+            reproduction_energy_cost: 0.7,
+            child_energy_factor: 0.4,
+            child_spawn_radius: 15.0,
+            population_density_factor: 0.8,
+            min_reproduction_chance: 0.05,
+            death_chance_factor: 0.1,
+            reproduction_cooldown: 0,
+            maturity_age: 0,
+            child_energy_mode: ChildEnergyMode::Reset,
+        }
+    }
+}
+
+impl Default for GeneticsConfig {
+    fn default() -> Self {
+        Self {
+            diploid: false,
+            dominance: 0.5,
+            mutation_rate_scale: 1.0,
+            generational: false,
+            generation_steps: 500,
+            locked_genes: BTreeMap::new(),
+            trait_mutation_rates: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        Self {
+            steps_per_second: 60.0,
+            max_steps_per_frame: 4,
+            step_budget_ms: 0.0,
+            skip_stats_over_budget: false,
+        }
+    }
+}
+
+impl Default for FitnessConfig {
+    fn default() -> Self {
+        Self {
+            energy_weight: 1.0,
+            lifespan_weight: 0.0,
+            offspring_weight: 0.0,
+        }
+    }
+}
+
+impl Default for SeasonConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            period: 3000,
+            movement_cost_amplitude: 0.5,
+            center_pressure_amplitude: 0.0,
+        }
+    }
 }
 
-impl Default for SimulationConfig {
+impl Default for BehaviorConfig {
     fn default() -> Self {
         Self {
-            population: PopulationConfig {
-                entity_scale: 0.5,
-                max_population: 10000,
-                initial_entities: 2500,
-                spawn_radius_factor: 0.2,
-            },
-            physics: PhysicsConfig {
-                max_velocity: 2.0,
-                max_entity_radius: 20.0,
-                min_entity_radius: 1.0,
-                grid_cell_size: 25.0,
-                boundary_margin: 5.0,
-                interaction_radius_offset: 15.0,
-                velocity_bounce_factor: 0.8,
-                center_pressure_strength: 0.3,
-            },
-            energy: EnergyConfig {
-                size_energy_cost_factor: 0.15,
-                movement_energy_cost: 0.1,
-            },
-            reproduction: ReproductionConfig {
-                reproduction_energy_threshold: 0.8,
-                reproduction_energy_cost: 0.7,
-                child_energy_factor: 0.4,
-                child_spawn_radius: 15.0,
-                population_density_factor: 0.8,
-                min_reproduction_chance: 0.05,
-                death_chance_factor: 0.1,
-            },
+            state_machine: false,
+            rest_energy_fraction: 0.15,
+            mate_energy_fraction: 0.8,
+            rest_speed_factor: 0.2,
+            flock_cohesion_coefficient: 0.1,
+            flock_alignment_coefficient: 0.1,
+            flock_separation_coefficient: 0.2,
+            torpor_threshold: 0.0,
+            torpor_speed_factor: 0.1,
+            predator_speed_multiplier: 1.2,
+            energy_share_amount: 0.0,
+            energy_share_kin_threshold: 0.1,
         }
     }
 }
 
+impl Default for RenderingConfig {
+    fn default() -> Self {
+        Self {
+            low_energy_style: LowEnergyStyle::None,
+            low_energy_threshold: 0.2,
+            color_mode: ColorMode::Appearance,
+            population_series_length: 600,
+            population_sample_interval: 10,
+            trails: false,
+            trail_length: 12,
+        }
+    }
+}
+
+impl EnergyConfig {
+    /// Whether an entity holding `energy` survives, i.e. is above the death threshold
+    pub fn is_alive(&self, energy: f32) -> bool {
+        energy > self.death_energy_threshold
+    }
+
+    /// Whether an entity holding `energy` survives after `starving_steps` in a row at or
+    /// below the death threshold, the current step included
+    pub fn survives(&self, energy: f32, starving_steps: u32) -> bool {
+        self.is_alive(energy) || starving_steps <= self.starvation_grace
+    }
+
+    /// Most energy an entity can hold
+    pub fn energy_ceiling(&self) -> f32 {
+        self.max_energy_factor * 100.0
+    }
+
+    /// Random starting energy within the configured bounds
+    pub fn random_initial_energy(&self, rng: &mut impl rand::Rng) -> f32 {
+        rng.gen_range(
+            self.initial_energy_min..=self.initial_energy_max.max(self.initial_energy_min),
+        )
+    }
+}
+
 impl SimulationConfig {
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
         let content = fs::read_to_string(path)?;
@@ -0,0 +1,45 @@
+use super::{ColorMode, SimulationConfig};
+
+impl SimulationConfig {
+    /// Names accepted by `preset`
+    pub const PRESETS: [&'static str; 4] =
+        ["predator_prey", "flocking_demo", "high_mutation", "stable"];
+
+    /// A curated starting point, built from the defaults with a few fields changed, or
+    /// `None` for a name not in `PRESETS`
+    pub fn preset(name: &str) -> Option<Self> {
+        let mut config = Self::default();
+        match name {
+            // Grazers hunted by predators that chase hard and eat well
+            "predator_prey" => {
+                config.population.initial_movement_type_weights = [0.0, 0.0, 0.0, 1.0, 3.0, 0.0];
+                config.behavior.state_machine = true;
+                config.behavior.predator_speed_multiplier = 1.5;
+                config.energy.meal_gain_factor = 0.5;
+                config.physics.max_interactions_per_step = 2;
+            }
+            // Flockers only, with strong cohesion and alignment and trails to show the flocks
+            "flocking_demo" => {
+                config.population.initial_movement_type_weights = [0.0, 1.0, 0.0, 0.0, 0.0, 0.0];
+                config.behavior.flock_cohesion_coefficient = 0.4;
+                config.behavior.flock_alignment_coefficient = 0.5;
+                config.behavior.flock_separation_coefficient = 0.3;
+                config.rendering.trails = true;
+            }
+            // Fast drift, coloured by genotype so diverging lineages stand apart
+            "high_mutation" => {
+                config.genetics.mutation_rate_scale = 4.0;
+                config.rendering.color_mode = ColorMode::Genotype;
+            }
+            // Held near half the cap, with late maturity and a short starvation grace
+            // to damp booms and busts
+            "stable" => {
+                config.population.target_population = config.population.max_population / 2;
+                config.reproduction.maturity_age = 100;
+                config.energy.starvation_grace = 30;
+            }
+            _ => return None,
+        }
+        Some(config)
+    }
+}
@@ -0,0 +1,227 @@
+use super::SimulationConfig;
+use crate::components::MovementType;
+use crate::genes::{Genes, GENE_BOUNDS};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+/// Valid (min, max) for each numeric config field, keyed by "section.field"
+const FIELD_RANGES: &[(&str, f32, f32)] = &[
+    ("population.entity_scale", 0.01, 10.0),
+    ("population.max_population", 1.0, 100_000.0),
+    ("population.initial_entities", 0.0, 100_000.0),
+    ("population.spawn_radius_factor", 0.0, 1.0),
+    ("population.target_population", 0.0, 100_000.0),
+    ("population.homeostasis_gain", 0.0, 10.0),
+    ("population.min_viable_population", 0.0, 100_000.0),
+    ("population.reseed_count", 0.0, 100_000.0),
+    ("physics.max_velocity", 0.1, 10.0),
+    ("physics.max_entity_radius", 1.0, 100.0),
+    ("physics.min_entity_radius", 0.1, 50.0),
+    ("physics.grid_cell_size", 1.0, 500.0),
+    ("physics.boundary_margin", 0.0, 100.0),
+    ("physics.interaction_radius_offset", 0.0, 100.0),
+    ("physics.velocity_bounce_factor", 0.0, 1.0),
+    ("physics.center_pressure_strength", 0.0, 2.0),
+    ("physics.max_interactions_per_step", 1.0, 20.0),
+    ("physics.substeps", 1.0, 16.0),
+    ("physics.center_pressure_inner_radius", 0.0, 500.0),
+    ("physics.edge_ramp_distance", 0.0, 500.0),
+    ("physics.edge_multiplier_max", 0.0, 50.0),
+    ("physics.grid_cell_capacity", 0.0, 100_000.0),
+    ("physics.max_neighbors", 0.0, 1000.0),
+    ("physics.velocity_damping", 0.0, 1.0),
+    ("physics.diffusion_strength", 0.0, 5.0),
+    ("physics.query_radius_margin", 0.0, 10.0),
+    ("physics.kin_recognition_threshold", 0.0, 1.0),
+    ("energy.size_energy_cost_factor", 0.0, 1.0),
+    ("energy.movement_energy_cost", 0.0, 1.0),
+    ("energy.max_energy_factor", 0.1, 10.0),
+    ("energy.sense_cost_factor", 0.0, 0.05),
+    ("energy.turning_energy_cost", 0.0, 1.0),
+    ("energy.initial_energy_min", 0.1, 500.0),
+    ("energy.initial_energy_max", 0.1, 500.0),
+    ("energy.max_energy_ratio", 1.0, 5.0),
+    ("energy.size_growth_rate", 0.0001, 1.0),
+    ("energy.size_asymptote", 1.0, 100.0),
+    ("energy.predation_energy_overhead", 0.0, 50.0),
+    ("energy.death_energy_threshold", 0.0, 100.0),
+    ("energy.metabolic_floor", 0.0, 10.0),
+    ("energy.meal_gain_factor", 0.0, 5.0),
+    ("energy.prey_size_bonus", 0.0, 5.0),
+    ("energy.max_size_bonus", 1.0, 10.0),
+    ("energy.starvation_grace", 0.0, 10_000.0),
+    ("reproduction.reproduction_energy_threshold", 0.0, 1.0),
+    ("reproduction.reproduction_energy_cost", 0.0, 1.0),
+    ("reproduction.child_energy_factor", 0.0, 1.0),
+    ("reproduction.child_spawn_radius", 0.0, 100.0),
+    ("reproduction.population_density_factor", 0.0, 1.0),
+    ("reproduction.min_reproduction_chance", 0.0, 1.0),
+    ("reproduction.death_chance_factor", 0.0, 1.0),
+    ("reproduction.reproduction_cooldown", 0.0, 10_000.0),
+    ("reproduction.maturity_age", 0.0, 100_000.0),
+    ("genetics.dominance", 0.0, 1.0),
+    ("genetics.mutation_rate_scale", 0.0, 5.0),
+    ("genetics.generation_steps", 1.0, 10_000.0),
+    ("fitness.energy_weight", 0.0, 100.0),
+    ("fitness.lifespan_weight", 0.0, 100.0),
+    ("fitness.offspring_weight", 0.0, 100.0),
+    ("timing.steps_per_second", 1.0, 240.0),
+    ("timing.max_steps_per_frame", 1.0, 16.0),
+    ("timing.step_budget_ms", 0.0, 10_000.0),
+    ("seasons.period", 1.0, 100_000.0),
+    ("seasons.movement_cost_amplitude", 0.0, 1.0),
+    ("seasons.center_pressure_amplitude", 0.0, 1.0),
+    ("behavior.rest_energy_fraction", 0.0, 1.0),
+    ("behavior.mate_energy_fraction", 0.0, 1.0),
+    ("behavior.rest_speed_factor", 0.0, 1.0),
+    ("behavior.flock_cohesion_coefficient", 0.0, 2.0),
+    ("behavior.flock_alignment_coefficient", 0.0, 2.0),
+    ("behavior.flock_separation_coefficient", 0.0, 2.0),
+    ("behavior.torpor_threshold", 0.0, 1.0),
+    ("behavior.torpor_speed_factor", 0.0, 1.0),
+    ("behavior.predator_speed_multiplier", 0.0, 5.0),
+    ("behavior.energy_share_amount", 0.0, 50.0),
+    ("behavior.energy_share_kin_threshold", 0.0, 1.0),
+    ("rendering.low_energy_threshold", 0.0, 1.0),
+    ("rendering.population_series_length", 0.0, 100_000.0),
+    ("rendering.population_sample_interval", 1.0, 10_000.0),
+    ("rendering.trail_length", 1.0, 256.0),
+];
+
+/// A config field that failed validation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigError {
+    pub field: String, // "section.field"
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(field: &str, message: String) -> Self {
+        Self {
+            field: field.to_string(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl SimulationConfig {
+    /// Describe every config field (default, type and valid range), the gene trait ranges
+    /// and the `MovementType` variants, so tools can generate parameter UIs
+    pub fn schema_json() -> Value {
+        // Round-trip through text so f32 defaults keep their short decimal form
+        let defaults = serde_json::to_string(&SimulationConfig::default())
+            .and_then(|text| serde_json::from_str(&text))
+            .unwrap_or(Value::Null);
+        let mut config = Map::new();
+        for (section, fields) in defaults.as_object().into_iter().flatten() {
+            let fields = fields.as_object().into_iter().flatten();
+            let fields = fields
+                .map(|(field, default)| (field.clone(), field_schema(section, field, default)))
+                .collect();
+            config.insert(section.clone(), Value::Object(fields));
+        }
+
+        let genes: Map<String, Value> = GENE_BOUNDS
+            .iter()
+            .map(|b| {
+                (
+                    b.name.to_string(),
+                    json!({ "min": number(b.min), "max": number(b.max) }),
+                )
+            })
+            .collect();
+        let movement_types: Vec<Value> = MovementType::ALL
+            .iter()
+            .filter_map(|t| serde_json::to_value(t).ok())
+            .collect();
+
+        json!({ "config": config, "genes": genes, "movement_types": movement_types })
+    }
+
+    /// Check every numeric field against its valid range
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let values = serde_json::to_value(self).map_err(|e| ConfigError::new("", e.to_string()))?;
+        for (key, min, max) in FIELD_RANGES {
+            let (section, field) = key.split_once('.').unwrap_or_default();
+            if let Some(value) = values[section][field].as_f64() {
+                if !(*min as f64..=*max as f64).contains(&value) {
+                    return Err(ConfigError::new(
+                        key,
+                        format!("{} = {} is outside [{}, {}]", key, value, min, max),
+                    ));
+                }
+            }
+        }
+
+        if let Some(name) = self
+            .genetics
+            .locked_genes
+            .keys()
+            .find(|name| Genes::trait_index(name).is_none())
+        {
+            return Err(ConfigError::new(
+                "genetics.locked_genes",
+                format!("genetics.locked_genes: unknown gene {:?}", name),
+            ));
+        }
+
+        let rates = &self.genetics.trait_mutation_rates;
+        if let Some(name) = rates.keys().find(|name| Genes::trait_index(name).is_none()) {
+            return Err(ConfigError::new(
+                "genetics.trait_mutation_rates",
+                format!("genetics.trait_mutation_rates: unknown gene {:?}", name),
+            ));
+        }
+        if let Some((name, rate)) = rates.iter().find(|(_, rate)| !(0.0..=1.0).contains(*rate)) {
+            return Err(ConfigError::new(
+                "genetics.trait_mutation_rates",
+                format!(
+                    "genetics.trait_mutation_rates: {:?} = {} is outside [0, 1]",
+                    name, rate
+                ),
+            ));
+        }
+
+        let weights = &self.population.initial_movement_type_weights;
+        if weights.iter().any(|w| w.is_nan() || *w < 0.0) || weights.iter().sum::<f32>() <= 0.0 {
+            return Err(ConfigError::new(
+                "population.initial_movement_type_weights",
+                format!(
+                    "population.initial_movement_type_weights = {:?} must be non-negative with a positive sum",
+                    weights
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn field_schema(section: &str, field: &str, default: &Value) -> Value {
+    let key = format!("{}.{}", section, field);
+    let kind = match default {
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        _ => "string",
+    };
+
+    let mut schema = json!({ "type": kind, "default": default });
+    if let Some((_, min, max)) = FIELD_RANGES.iter().find(|(name, _, _)| *name == key) {
+        schema["min"] = number(*min);
+        schema["max"] = number(*max);
+    }
+    schema
+}
+
+fn number(value: f32) -> Value {
+    json!(value.to_string().parse::<f64>().unwrap_or_default())
+}
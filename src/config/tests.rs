@@ -2,6 +2,17 @@ use super::*;
 use std::fs;
 use tempfile::NamedTempFile;
 
+#[test]
+fn test_every_preset_is_valid_and_differs_from_the_defaults() {
+    let defaults = serde_json::to_value(SimulationConfig::default()).unwrap();
+    for name in SimulationConfig::PRESETS {
+        let preset = SimulationConfig::preset(name).unwrap();
+        assert_eq!(preset.validate(), Ok(()), "{}", name);
+        assert_ne!(serde_json::to_value(&preset).unwrap(), defaults, "{}", name);
+    }
+    assert!(SimulationConfig::preset("no_such_preset").is_none());
+}
+
 #[test]
 fn test_default_config() {
     let config = SimulationConfig::default();
@@ -11,6 +22,10 @@ fn test_default_config() {
     assert_eq!(config.population.max_population, 10000);
     assert_eq!(config.population.initial_entities, 2500);
     assert_eq!(config.population.spawn_radius_factor, 0.2);
+    assert_eq!(config.population.target_population, 0);
+    assert_eq!(config.population.homeostasis_gain, 2.0);
+    assert_eq!(config.population.min_viable_population, 0);
+    assert_eq!(config.population.reseed_count, 5);
     assert_eq!(config.physics.max_velocity, 2.0);
     assert_eq!(config.physics.max_entity_radius, 20.0);
     assert_eq!(config.physics.min_entity_radius, 1.0);
@@ -18,6 +33,25 @@ fn test_default_config() {
     assert_eq!(config.physics.boundary_margin, 5.0);
     assert_eq!(config.physics.interaction_radius_offset, 15.0);
     assert_eq!(config.physics.velocity_bounce_factor, 0.8);
+    assert_eq!(config.physics.max_interactions_per_step, 1);
+    assert_eq!(config.physics.substeps, 1);
+    assert_eq!(config.physics.center_pressure_inner_radius, 10.0);
+    assert_eq!(config.physics.edge_ramp_distance, 50.0);
+    assert_eq!(config.physics.edge_multiplier_max, 8.0);
+    assert!(!config.physics.soft_walls_only);
+    assert!(config.physics.incremental_grid);
+    assert_eq!(config.physics.grid_cell_capacity, 0);
+    assert_eq!(config.physics.max_neighbors, 20);
+    assert_eq!(config.physics.velocity_damping, 0.0);
+    assert!(!config.physics.symmetric_interactions);
+    assert_eq!(config.physics.diffusion_strength, 0.0);
+    assert_eq!(config.physics.query_radius_margin, 0.0);
+    assert!(config.physics.allow_cannibalism);
+    assert_eq!(config.physics.kin_recognition_threshold, 0.1);
+    assert!(config.genetics.locked_genes.is_empty());
+    assert!(config.genetics.trait_mutation_rates.is_empty());
+    assert_eq!(config.reproduction.reproduction_cooldown, 0);
+    assert_eq!(config.reproduction.maturity_age, 0);
     assert_eq!(config.energy.size_energy_cost_factor, 0.15);
     assert_eq!(config.energy.movement_energy_cost, 0.1);
     assert_eq!(config.reproduction.reproduction_energy_threshold, 0.8);
@@ -27,6 +61,62 @@ fn test_default_config() {
     assert_eq!(config.reproduction.population_density_factor, 0.8);
     assert_eq!(config.reproduction.min_reproduction_chance, 0.05);
     assert_eq!(config.reproduction.death_chance_factor, 0.1);
+    assert!(!config.genetics.diploid);
+    assert_eq!(config.genetics.dominance, 0.5);
+    assert_eq!(config.genetics.mutation_rate_scale, 1.0);
+    assert!(!config.genetics.generational);
+    assert_eq!(config.genetics.generation_steps, 500);
+    assert_eq!(config.population.spawn_pattern, SpawnPattern::Disc);
+    assert_eq!(config.energy.max_energy_factor, 1.5);
+    assert_eq!(config.energy.sense_cost_factor, 0.002);
+    assert_eq!(config.energy.turning_energy_cost, 0.0);
+    assert_eq!(config.energy.initial_energy_min, 15.0);
+    assert_eq!(config.energy.initial_energy_max, 75.0);
+    assert_eq!(config.energy.max_energy_ratio, 1.3);
+    assert_eq!(config.fitness.energy_weight, 1.0);
+    assert_eq!(config.fitness.lifespan_weight, 0.0);
+    assert_eq!(config.fitness.offspring_weight, 0.0);
+    assert_eq!(config.timing.steps_per_second, 60.0);
+    assert_eq!(config.timing.max_steps_per_frame, 4);
+    assert_eq!(config.timing.step_budget_ms, 0.0);
+    assert!(!config.timing.skip_stats_over_budget);
+    assert!(!config.seasons.enabled);
+    assert_eq!(config.seasons.period, 3000);
+    assert_eq!(config.seasons.movement_cost_amplitude, 0.5);
+    assert_eq!(config.seasons.center_pressure_amplitude, 0.0);
+    assert_eq!(config.energy.size_growth, SizeGrowth::Linear);
+    assert_eq!(config.energy.size_growth_rate, 0.005);
+    assert_eq!(config.energy.size_asymptote, 15.0);
+    assert_eq!(config.energy.predation_energy_overhead, 0.5);
+    assert_eq!(config.energy.death_energy_threshold, 0.0);
+    assert_eq!(config.energy.metabolic_floor, 0.05);
+    assert_eq!(config.energy.meal_gain_factor, 0.3);
+    assert_eq!(config.energy.prey_size_bonus, 0.3);
+    assert_eq!(config.energy.max_size_bonus, 1.5);
+    assert_eq!(config.energy.starvation_grace, 0);
+    assert_eq!(config.rendering.low_energy_style, LowEnergyStyle::None);
+    assert_eq!(config.rendering.low_energy_threshold, 0.2);
+    assert_eq!(config.rendering.color_mode, ColorMode::Appearance);
+    assert_eq!(config.rendering.population_series_length, 600);
+    assert_eq!(config.rendering.population_sample_interval, 10);
+    assert!(!config.rendering.trails);
+    assert_eq!(config.rendering.trail_length, 12);
+    assert_eq!(
+        config.reproduction.child_energy_mode,
+        ChildEnergyMode::Reset
+    );
+    assert!(!config.behavior.state_machine);
+    assert_eq!(config.behavior.rest_energy_fraction, 0.15);
+    assert_eq!(config.behavior.mate_energy_fraction, 0.8);
+    assert_eq!(config.behavior.rest_speed_factor, 0.2);
+    assert_eq!(config.behavior.flock_cohesion_coefficient, 0.1);
+    assert_eq!(config.behavior.flock_alignment_coefficient, 0.1);
+    assert_eq!(config.behavior.flock_separation_coefficient, 0.2);
+    assert_eq!(config.behavior.torpor_threshold, 0.0);
+    assert_eq!(config.behavior.torpor_speed_factor, 0.1);
+    assert_eq!(config.behavior.predator_speed_multiplier, 1.2);
+    assert_eq!(config.behavior.energy_share_amount, 0.0);
+    assert_eq!(config.behavior.energy_share_kin_threshold, 0.1);
 }
 
 #[test]
@@ -386,3 +476,122 @@ fn test_config_debug_format() {
     assert!(debug_str.contains("energy"));
     assert!(debug_str.contains("reproduction"));
 }
+
+#[test]
+fn test_config_without_genetics_section() {
+    let mut value = serde_json::to_value(SimulationConfig::default()).unwrap();
+    value.as_object_mut().unwrap().remove("genetics");
+
+    let config: SimulationConfig = serde_json::from_value(value).unwrap();
+    assert!(!config.genetics.diploid);
+}
+
+#[test]
+fn test_config_missing_fields_use_defaults() {
+    let config: SimulationConfig =
+        serde_json::from_str(r#"{ "physics": { "max_velocity": 3.0 } }"#).unwrap();
+
+    assert_eq!(config.physics.max_velocity, 3.0);
+    assert_eq!(config.physics.grid_cell_size, 25.0);
+    assert_eq!(config.population.max_population, 10000);
+}
+
+#[test]
+fn test_schema_covers_every_config_field() {
+    let schema = SimulationConfig::schema_json();
+    let defaults = serde_json::to_value(SimulationConfig::default()).unwrap();
+
+    for (section, fields) in defaults.as_object().unwrap() {
+        for (field, default) in fields.as_object().unwrap() {
+            let entry = &schema["config"][section][field];
+            assert!(
+                entry.is_object(),
+                "{}.{} missing from schema",
+                section,
+                field
+            );
+            if default.is_number() {
+                let (min, max) = (entry["min"].as_f64(), entry["max"].as_f64());
+                assert!(
+                    min.is_some() && max.is_some(),
+                    "{}.{} has no range",
+                    section,
+                    field
+                );
+                let default = entry["default"].as_f64().unwrap();
+                assert!((min.unwrap()..=max.unwrap()).contains(&default));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_schema_lists_genes_and_movement_types() {
+    let schema = SimulationConfig::schema_json();
+
+    assert_eq!(schema["genes"].as_object().unwrap().len(), 21);
+    assert_eq!(schema["genes"]["speed"]["max"], 3.0);
+    assert_eq!(schema["movement_types"].as_array().unwrap().len(), 6);
+    assert_eq!(schema["movement_types"][3], "Predatory");
+}
+
+#[test]
+fn test_validate_rejects_out_of_range_fields() {
+    let mut config = SimulationConfig::default();
+    assert!(config.validate().is_ok());
+
+    config.physics.max_velocity = 50.0;
+    let error = config.validate().unwrap_err();
+    assert_eq!(error.field, "physics.max_velocity");
+    assert!(error.message.contains("physics.max_velocity"));
+
+    config.physics.max_velocity = 2.0;
+    config.population.initial_movement_type_weights = [0.0; 6];
+    let error = config.validate().unwrap_err();
+    assert_eq!(error.field, "population.initial_movement_type_weights");
+
+    config.population.initial_movement_type_weights = [1.0; 6];
+    config
+        .genetics
+        .locked_genes
+        .insert("wingspan".to_string(), 1.0);
+    let error = config.validate().unwrap_err();
+    assert_eq!(error.field, "genetics.locked_genes");
+    assert!(error.message.contains("wingspan"));
+
+    config.genetics.locked_genes.clear();
+    config
+        .genetics
+        .trait_mutation_rates
+        .insert("speed".to_string(), 1.5);
+    let error = config.validate().unwrap_err();
+    assert_eq!(error.field, "genetics.trait_mutation_rates");
+}
+
+#[test]
+fn test_config_watcher_reloads_changed_file() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+    SimulationConfig::default().save_to_file(path).unwrap();
+    let mut watcher = watch::ConfigWatcher::new(path);
+    assert!(watcher.poll().is_none());
+
+    let bump_mtime = |seconds| {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        let time = std::time::SystemTime::now() + std::time::Duration::from_secs(seconds);
+        file.set_modified(time).unwrap();
+    };
+
+    let mut edited = SimulationConfig::default();
+    edited.physics.max_velocity = 0.5;
+    edited.save_to_file(path).unwrap();
+    bump_mtime(10);
+    let reloaded = watcher.poll().expect("changed file should reload");
+    assert_eq!(reloaded.physics.max_velocity, 0.5);
+    assert!(watcher.poll().is_none());
+
+    // A broken edit is reported and skipped
+    fs::write(path, "{ not json").unwrap();
+    bump_mtime(20);
+    assert!(watcher.poll().is_none());
+}
@@ -0,0 +1,48 @@
+use super::SimulationConfig;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Polls a config file's modification time so parameters can be tuned during a long run.
+/// Call `poll` from the run loop and pass any returned config to `Simulation::update_config`.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let modified = Self::modified_time(&path);
+        Self { path, modified }
+    }
+
+    /// The new config if the file changed since the last poll and it is valid. On a
+    /// parse or validation error a warning is printed and `None` is returned, so the
+    /// caller keeps running with its current config.
+    pub fn poll(&mut self) -> Option<SimulationConfig> {
+        let modified = Self::modified_time(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        match SimulationConfig::load_from_file(&self.path)
+            .and_then(|config| config.validate().map(|_| config).map_err(Into::into))
+        {
+            Ok(config) => Some(config),
+            Err(e) => {
+                eprintln!(
+                    "Warning: keeping current config, {} is invalid: {}",
+                    self.path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    fn modified_time(path: &PathBuf) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
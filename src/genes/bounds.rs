@@ -0,0 +1,76 @@
+use super::TRAIT_COUNT;
+
+/// Valid range and mutation step for one continuous trait
+#[derive(Clone, Copy, Debug)]
+pub struct GeneBounds {
+    pub name: &'static str,
+    pub min: f32,
+    pub max: f32,
+    pub mutation_step: f32, // Mutations shift the trait by up to +/- this amount
+    pub wraps: bool,        // An angle, wrapped round into the range instead of clamped
+}
+
+impl GeneBounds {
+    /// `value` brought into range: wrapped for angles, clamped otherwise
+    pub fn fit(&self, value: f32) -> f32 {
+        if self.wraps {
+            self.min + (value - self.min).rem_euclid(self.max - self.min)
+        } else {
+            value.clamp(self.min, self.max)
+        }
+    }
+
+    /// `dominance` of the way from the lower allele to the higher. Angles are blended
+    /// along the shorter arc, whose far end counts as the higher allele.
+    pub fn blend(&self, a: f32, b: f32, dominance: f32) -> f32 {
+        let (mut low, mut high) = (a.min(b), a.max(b));
+        let turn = self.max - self.min;
+        if self.wraps && high - low > turn / 2.0 {
+            (low, high) = (high, low + turn);
+        }
+        self.fit(low + (high - low) * dominance)
+    }
+}
+
+const fn bounds(name: &'static str, min: f32, max: f32, mutation_step: f32) -> GeneBounds {
+    GeneBounds {
+        name,
+        min,
+        max,
+        mutation_step,
+        wraps: false,
+    }
+}
+
+/// Bounds for a heading in radians, which wraps at a full turn
+const fn angle(name: &'static str, mutation_step: f32) -> GeneBounds {
+    GeneBounds {
+        wraps: true,
+        ..bounds(name, 0.0, std::f32::consts::TAU, mutation_step)
+    }
+}
+
+/// Bounds for every trait, in the same order as `Genes::traits`
+pub const GENE_BOUNDS: [GeneBounds; TRAIT_COUNT] = [
+    bounds("speed", 0.05, 3.0, 0.15),
+    bounds("sense_radius", 2.0, 180.0, 8.0),
+    bounds("efficiency", 0.2, 4.0, 0.15),
+    bounds("loss_rate", 0.02, 3.0, 0.15),
+    bounds("gain_rate", 0.1, 5.0, 0.25),
+    bounds("size_factor", 0.1, 3.5, 0.15),
+    bounds("digestion_efficiency", 0.1, 1.0, 0.05),
+    bounds("rate", 0.0001, 0.25, 0.025),
+    bounds("mutation_rate", 0.001, 0.25, 0.025),
+    bounds("strategy", 0.0, 1.0, 0.05),
+    bounds("hue", 0.0, 1.0, 0.1),
+    bounds("saturation", 0.1, 1.0, 0.1),
+    bounds("flocking_strength", 0.0, 1.0, 0.1),
+    bounds("separation_distance", 2.0, 30.0, 2.0),
+    bounds("alignment_strength", 0.0, 1.0, 0.1),
+    bounds("cohesion_strength", 0.0, 1.0, 0.1),
+    angle("migration_heading", 0.3),
+    bounds("flock_kin_threshold", 0.0, 1.0, 0.05),
+    bounds("gene_preference_strength", 0.0, 1.0, 0.1),
+    bounds("social_tendency", 0.0, 1.0, 0.1),
+    bounds("fearfulness", 0.0, 1.0, 0.1),
+];
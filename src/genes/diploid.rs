@@ -0,0 +1,107 @@
+use super::{Genes, GENE_BOUNDS};
+use crate::config::GeneticsConfig;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Diploid genotype - two allele sets whose dominance-weighted blend is the expressed phenotype
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DiploidGenes {
+    pub a: Genes,
+    pub b: Genes,
+}
+
+impl DiploidGenes {
+    pub fn new_random(rng: &mut impl Rng) -> Self {
+        Self {
+            a: Genes::new_random(rng),
+            b: Genes::new_random(rng),
+        }
+    }
+
+    /// Blend each trait between the two alleles. A dominance of 0.5 is purely additive,
+    /// 1.0 makes the higher allele fully dominant and 0.0 the lower one. Angles blend
+    /// along the shorter arc between the alleles.
+    /// The discrete movement type is taken from allele `a`.
+    pub fn express(&self, dominance: f32) -> Genes {
+        let mut phenotype = self.a.clone();
+        let other = self.b.traits();
+
+        let traits = phenotype.traits_mut().into_iter().zip(other);
+        for ((trait_value, other_value), bounds) in traits.zip(GENE_BOUNDS.iter()) {
+            *trait_value = bounds.blend(*trait_value, other_value, dominance);
+        }
+
+        phenotype
+    }
+
+    /// Mendelian segregation: pass on one of the two allele sets at random, with mutation
+    pub fn gamete(&self, rng: &mut impl Rng, genetics: &GeneticsConfig) -> Genes {
+        let allele = if rng.gen::<bool>() { &self.a } else { &self.b };
+        allele.mutate_with(rng, genetics)
+    }
+
+    /// Sexual reproduction: one gamete from each parent
+    pub fn cross(
+        &self,
+        mate: &DiploidGenes,
+        rng: &mut impl Rng,
+        genetics: &GeneticsConfig,
+    ) -> DiploidGenes {
+        DiploidGenes {
+            a: self.gamete(rng, genetics),
+            b: mate.gamete(rng, genetics),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    fn allele_with_speed(rng: &mut impl Rng, speed: f32) -> Genes {
+        let mut genes = Genes::new_random(rng);
+        genes.movement.speed = speed;
+        genes.reproduction.mutation_rate = 0.0; // Keep alleles stable across generations
+        genes
+    }
+
+    #[test]
+    fn test_express_dominance() {
+        let mut rng = thread_rng();
+        let genotype = DiploidGenes {
+            a: allele_with_speed(&mut rng, 1.0),
+            b: allele_with_speed(&mut rng, 2.0),
+        };
+
+        assert!((genotype.express(0.5).speed() - 1.5).abs() < 1e-6);
+        assert!((genotype.express(1.0).speed() - 2.0).abs() < 1e-6);
+        assert!((genotype.express(0.0).speed() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mendelian_segregation() {
+        let mut rng = thread_rng();
+        let heterozygote = DiploidGenes {
+            a: allele_with_speed(&mut rng, 1.0),
+            b: allele_with_speed(&mut rng, 2.0),
+        };
+
+        // Aa x Aa should give AA:Aa:aa in a 1:2:1 ratio
+        let mut counts = [0usize; 3];
+        let trials = 4000;
+        for _ in 0..trials {
+            let child = heterozygote.cross(&heterozygote, &mut rng, &GeneticsConfig::default());
+            let fast_alleles = [&child.a, &child.b]
+                .iter()
+                .filter(|allele| allele.speed() == 2.0)
+                .count();
+            counts[fast_alleles] += 1;
+        }
+
+        let fractions = counts.map(|c| c as f32 / trials as f32);
+        assert!((fractions[0] - 0.25).abs() < 0.05, "aa: {:?}", fractions);
+        assert!((fractions[1] - 0.5).abs() < 0.05, "Aa: {:?}", fractions);
+        assert!((fractions[2] - 0.25).abs() < 0.05, "AA: {:?}", fractions);
+    }
+}
@@ -1,6 +1,30 @@
 use crate::components::{Color, MovementStyle, MovementType};
+use crate::config::{EnergyConfig, GeneticsConfig};
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+mod bounds;
+mod diploid;
+mod pedigree;
+mod phenotype;
+mod share;
+pub use bounds::GENE_BOUNDS;
+pub use diploid::DiploidGenes;
+pub use pedigree::Pedigree;
+pub use phenotype::Phenotype;
+
+/// Number of continuous traits exposed by `Genes::traits`
+pub const TRAIT_COUNT: usize = 21;
+
+/// Fixed weight in [-1, 1] of trait `index` on colour channel `channel`, from a SplitMix64 hash
+fn projection_weight(channel: usize, index: usize) -> f32 {
+    let mut z = ((channel * TRAIT_COUNT + index) as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+}
 
 // Grouped gene structures for better organization
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -15,12 +39,14 @@ pub struct EnergyGenes {
     pub loss_rate: f32,
     pub gain_rate: f32,
     pub size_factor: f32,
+    pub digestion_efficiency: f32, // Fraction of a meal's energy actually absorbed; the rest is lost
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ReproductionGenes {
     pub rate: f32,
     pub mutation_rate: f32,
+    pub strategy: f32, // r/K trade-off (0.0 = few well-provisioned offspring, 1.0 = many cheap ones)
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -34,6 +60,7 @@ pub struct BehaviorGenes {
     pub movement_style: MovementStyle,
     pub gene_preference_strength: f32, // How strongly to prefer different genes (0.0 = no preference, 1.0 = strong preference)
     pub social_tendency: f32, // Tendency to be social vs solitary (0.0 = solitary, 1.0 = social)
+    pub fearfulness: f32, // How strongly to flee from nearby predators (0.0 = fearless, 1.0 = always flee)
 }
 
 // Main genes structure that groups related traits
@@ -47,14 +74,8 @@ pub struct Genes {
 }
 
 impl Genes {
-    pub fn new_random(rng: &mut ThreadRng) -> Self {
-        let movement_type = match rng.gen_range(0..5) {
-            0 => MovementType::Random,
-            1 => MovementType::Flocking,
-            2 => MovementType::Solitary,
-            3 => MovementType::Predatory,
-            _ => MovementType::Grazing,
-        };
+    pub fn new_random(rng: &mut impl Rng) -> Self {
+        let movement_type = MovementType::random(rng);
 
         Self {
             movement: MovementGenes {
@@ -66,10 +87,12 @@ impl Genes {
                 loss_rate: rng.gen_range(0.05..2.0),
                 gain_rate: rng.gen_range(0.2..4.5),
                 size_factor: rng.gen_range(0.3..2.5),
+                digestion_efficiency: rng.gen_range(0.4..1.0),
             },
             reproduction: ReproductionGenes {
                 rate: rng.gen_range(0.0005..0.15),
                 mutation_rate: rng.gen_range(0.005..0.15),
+                strategy: rng.gen_range(0.0..1.0),
             },
             appearance: AppearanceGenes {
                 hue: rng.gen_range(0.0..1.0),
@@ -82,114 +105,86 @@ impl Genes {
                     separation_distance: rng.gen_range(5.0..25.0),
                     alignment_strength: rng.gen_range(0.0..1.0),
                     cohesion_strength: rng.gen_range(0.0..1.0),
+                    migration_heading: rng.gen_range(0.0..std::f32::consts::TAU),
+                    flock_kin_threshold: rng.gen_range(0.4..1.0),
                 },
                 gene_preference_strength: rng.gen_range(0.0..1.0),
                 social_tendency: rng.gen_range(0.0..1.0),
+                fearfulness: rng.gen_range(0.0..1.0),
             },
         }
     }
 
-    pub fn mutate(&self, rng: &mut ThreadRng) -> Self {
-        let mut new_genes = self.clone();
-
-        // Movement mutations
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.movement.speed =
-                (new_genes.movement.speed + rng.gen_range(-0.15..0.15)).clamp(0.05, 3.0);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.movement.sense_radius =
-                (new_genes.movement.sense_radius + rng.gen_range(-8.0..8.0)).clamp(2.0, 180.0);
-        }
-
-        // Energy mutations
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.energy.efficiency =
-                (new_genes.energy.efficiency + rng.gen_range(-0.15..0.15)).clamp(0.2, 4.0);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.energy.loss_rate =
-                (new_genes.energy.loss_rate + rng.gen_range(-0.15..0.15)).clamp(0.02, 3.0);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.energy.gain_rate =
-                (new_genes.energy.gain_rate + rng.gen_range(-0.25..0.25)).clamp(0.1, 5.0);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.energy.size_factor =
-                (new_genes.energy.size_factor + rng.gen_range(-0.15..0.15)).clamp(0.1, 3.5);
-        }
-
-        // Reproduction mutations
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.reproduction.rate =
-                (new_genes.reproduction.rate + rng.gen_range(-0.025..0.025)).clamp(0.0001, 0.25);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.reproduction.mutation_rate = (new_genes.reproduction.mutation_rate
-                + rng.gen_range(-0.025..0.025))
-            .clamp(0.001, 0.25);
-        }
+    pub fn mutate(&self, rng: &mut impl Rng) -> Self {
+        self.mutate_scaled(rng, 1.0)
+    }
 
-        // Appearance mutations
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.appearance.hue =
-                (new_genes.appearance.hue + rng.gen_range(-0.1..0.1)).clamp(0.0, 1.0);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.appearance.saturation =
-                (new_genes.appearance.saturation + rng.gen_range(-0.1..0.1)).clamp(0.1, 1.0);
-        }
+    /// Mutate with the heritable mutation rate multiplied by a global scale
+    pub fn mutate_scaled(&self, rng: &mut impl Rng, rate_scale: f32) -> Self {
+        let genetics = GeneticsConfig {
+            mutation_rate_scale: rate_scale,
+            ..GeneticsConfig::default()
+        };
+        self.mutate_with(rng, &genetics)
+    }
 
-        // Behavior mutations
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.behavior.movement_style.flocking_strength =
-                (new_genes.behavior.movement_style.flocking_strength + rng.gen_range(-0.1..0.1))
-                    .clamp(0.0, 1.0);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.behavior.movement_style.separation_distance =
-                (new_genes.behavior.movement_style.separation_distance + rng.gen_range(-2.0..2.0))
-                    .clamp(2.0, 30.0);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.behavior.movement_style.alignment_strength =
-                (new_genes.behavior.movement_style.alignment_strength + rng.gen_range(-0.1..0.1))
-                    .clamp(0.0, 1.0);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.behavior.movement_style.cohesion_strength =
-                (new_genes.behavior.movement_style.cohesion_strength + rng.gen_range(-0.1..0.1))
-                    .clamp(0.0, 1.0);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.behavior.gene_preference_strength =
-                (new_genes.behavior.gene_preference_strength + rng.gen_range(-0.1..0.1))
-                    .clamp(0.0, 1.0);
-        }
-        if rng.gen::<f32>() < self.reproduction.mutation_rate {
-            new_genes.behavior.social_tendency =
-                (new_genes.behavior.social_tendency + rng.gen_range(-0.1..0.1)).clamp(0.0, 1.0);
+    /// Mutate as configured: scaled by `mutation_rate_scale`, skipping `locked_genes`, with
+    /// any trait listed in `trait_mutation_rates` using its own rate instead of the heritable one
+    pub fn mutate_with(&self, rng: &mut impl Rng, genetics: &GeneticsConfig) -> Self {
+        let mut new_genes = self.clone();
+        let mutation_rate = self.reproduction.mutation_rate * genetics.mutation_rate_scale;
+
+        for (value, bounds) in new_genes.traits_mut().into_iter().zip(GENE_BOUNDS.iter()) {
+            if genetics.locked_genes.contains_key(bounds.name) {
+                continue;
+            }
+            let rate = genetics
+                .trait_mutation_rates
+                .get(bounds.name)
+                .map_or(mutation_rate, |rate| rate * genetics.mutation_rate_scale);
+            if rng.gen::<f32>() < rate {
+                *value =
+                    bounds.fit(*value + rng.gen_range(-bounds.mutation_step..bounds.mutation_step));
+            }
         }
 
         // Occasionally change movement type
-        if rng.gen::<f32>() < self.reproduction.mutation_rate * 0.1 {
-            new_genes.behavior.movement_style.style = match rng.gen_range(0..5) {
-                0 => MovementType::Random,
-                1 => MovementType::Flocking,
-                2 => MovementType::Solitary,
-                3 => MovementType::Predatory,
-                _ => MovementType::Grazing,
-            };
+        if rng.gen::<f32>() < mutation_rate * 0.1 {
+            new_genes.behavior.movement_style.style = MovementType::random(rng);
         }
 
         new_genes
     }
 
+    /// Coarse genotype label (movement type and hue band) for counting distinct genotypes
+    pub fn signature(&self) -> u32 {
+        let style = MovementType::ALL
+            .iter()
+            .position(|t| *t == self.behavior.movement_style.style)
+            .unwrap_or(0) as u32;
+        let hue_band = (self.appearance.hue.clamp(0.0, 1.0) * 7.999) as u32;
+        style * 8 + hue_band
+    }
+
     pub fn get_color(&self) -> Color {
         Color::from_hsv(self.appearance.hue, self.appearance.saturation, 0.8)
     }
 
+    /// Colour from the whole genotype: every normalised trait is projected onto three fixed
+    /// pseudo-random axes, one per RGB channel. Similar genotypes get similar colours and
+    /// distant ones differ, whatever their appearance genes.
+    pub fn genotype_color(&self) -> Color {
+        let mut channels = [0.0f32; 3];
+        for (i, (value, bounds)) in self.traits().iter().zip(GENE_BOUNDS.iter()).enumerate() {
+            let centered = (value - bounds.min) / (bounds.max - bounds.min) - 0.5;
+            for (c, channel) in channels.iter_mut().enumerate() {
+                *channel += projection_weight(c, i) * centered;
+            }
+        }
+        let [r, g, b] = channels.map(|sum| 0.5 + 0.5 * (2.0 * sum).tanh());
+        Color { r, g, b }
+    }
+
     // Predation logic based on genes
     pub fn can_eat(
         &self,
@@ -206,58 +201,18 @@ impl Genes {
 
     // Calculate how similar two sets of genes are (0.0 = identical, 1.0 = completely different)
     pub fn calculate_gene_similarity(&self, other: &Genes) -> f32 {
-        let mut total_difference = 0.0;
-        let mut total_weights = 0.0;
-
-        // Movement genes similarity
-        let speed_diff = (self.movement.speed - other.movement.speed).abs() / 2.5; // Normalize by max speed
-        let sense_diff = (self.movement.sense_radius - other.movement.sense_radius).abs() / 180.0; // Normalize by max sense radius
-        total_difference += speed_diff * 0.3 + sense_diff * 0.2;
-        total_weights += 0.5;
-
-        // Energy genes similarity
-        let efficiency_diff = (self.energy.efficiency - other.energy.efficiency).abs() / 4.0;
-        let loss_diff = (self.energy.loss_rate - other.energy.loss_rate).abs() / 3.0;
-        let gain_diff = (self.energy.gain_rate - other.energy.gain_rate).abs() / 5.0;
-        let size_factor_diff = (self.energy.size_factor - other.energy.size_factor).abs() / 3.5;
-        total_difference +=
-            efficiency_diff * 0.15 + loss_diff * 0.15 + gain_diff * 0.1 + size_factor_diff * 0.1;
-        total_weights += 0.5;
-
-        // Appearance genes similarity (color-based)
-        let hue_diff = (self.appearance.hue - other.appearance.hue).abs();
-        let saturation_diff = (self.appearance.saturation - other.appearance.saturation).abs();
-        total_difference += hue_diff * 0.3 + saturation_diff * 0.2;
-        total_weights += 0.5;
-
-        // Behavior genes similarity
-        let flocking_diff = (self.behavior.movement_style.flocking_strength
-            - other.behavior.movement_style.flocking_strength)
-            .abs();
-        let social_diff = (self.behavior.social_tendency - other.behavior.social_tendency).abs();
-        let preference_diff = (self.behavior.gene_preference_strength
-            - other.behavior.gene_preference_strength)
-            .abs();
-        total_difference += flocking_diff * 0.2 + social_diff * 0.2 + preference_diff * 0.1;
-        total_weights += 0.5;
-
-        // Movement type similarity
-        let type_similarity = if std::mem::discriminant(&self.behavior.movement_style.style)
-            == std::mem::discriminant(&other.behavior.movement_style.style)
-        {
-            0.0 // Same type
-        } else {
-            1.0 // Different type
-        };
-        total_difference += type_similarity * 0.3;
-        total_weights += 0.3;
-
-        total_difference / total_weights
+        let same_type = std::mem::discriminant(&self.behavior.movement_style.style)
+            == std::mem::discriminant(&other.behavior.movement_style.style);
+        phenotype::weighted_distance(
+            &self.similarity_vector(),
+            &other.similarity_vector(),
+            same_type,
+        )
     }
 
-    // Get predation preference based on gene similarity
-    pub fn get_predation_preference(&self, other_genes: &Genes) -> f32 {
-        let gene_similarity = self.calculate_gene_similarity(other_genes);
+    // Get predation preference based on gene similarity, from the cached phenotypes
+    pub fn get_predation_preference(&self, phenotype: &Phenotype, other: &Phenotype) -> f32 {
+        let gene_similarity = phenotype.similarity(other);
 
         // Higher preference for different genes (inverse of similarity)
         // Apply the gene preference strength to modulate this effect
@@ -274,18 +229,75 @@ impl Genes {
         other_energy: f32,
         other_size: &crate::components::Size,
         self_size: &crate::components::Size,
-        other_genes: &Genes,
+        phenotype: &Phenotype,
+        other_phenotype: &Phenotype,
+        config: &EnergyConfig,
     ) -> f32 {
         let size_ratio = other_size.radius / self_size.radius;
-        let base_gain = other_energy * self.energy.gain_rate * 0.3;
+        let base_gain = other_energy * self.energy.gain_rate * config.meal_gain_factor;
 
         // Bigger prey = more energy, but with stronger diminishing returns
-        let size_bonus = base_gain * (1.0 + size_ratio * 0.3).min(1.5);
+        let size_bonus =
+            base_gain * (1.0 + size_ratio * config.prey_size_bonus).min(config.max_size_bonus);
 
         // Gene preference bonus - more energy from preferred prey
-        let gene_bonus = self.get_predation_preference(other_genes);
+        let gene_bonus = self.get_predation_preference(phenotype, other_phenotype);
+
+        // Up to 50% bonus for preferred prey, of which only part is absorbed
+        size_bonus * (1.0 + gene_bonus * 0.5) * self.energy.digestion_efficiency
+    }
+
+    /// Position of the named trait in `traits` and `GENE_BOUNDS`
+    pub fn trait_index(name: &str) -> Option<usize> {
+        GENE_BOUNDS.iter().position(|bounds| bounds.name == name)
+    }
+
+    /// Set the named trait, fitted to its bounds; returns false for an unknown name
+    pub fn set_trait(&mut self, name: &str, value: f32) -> bool {
+        let Some(index) = Self::trait_index(name) else {
+            return false;
+        };
+        let bounds = &GENE_BOUNDS[index];
+        *self.traits_mut()[index] = bounds.fit(value);
+        true
+    }
+
+    /// Set every locked trait to its fixed value
+    pub fn lock_traits(&mut self, locked: &BTreeMap<String, f32>) {
+        for (name, &value) in locked {
+            self.set_trait(name, value);
+        }
+    }
+
+    /// Continuous traits in a fixed order, for operations applied uniformly across all genes
+    pub fn traits(&self) -> [f32; TRAIT_COUNT] {
+        self.clone().traits_mut().map(|t| *t)
+    }
 
-        size_bonus * (1.0 + gene_bonus * 0.5) // Up to 50% bonus for preferred prey
+    pub fn traits_mut(&mut self) -> [&mut f32; TRAIT_COUNT] {
+        [
+            &mut self.movement.speed,
+            &mut self.movement.sense_radius,
+            &mut self.energy.efficiency,
+            &mut self.energy.loss_rate,
+            &mut self.energy.gain_rate,
+            &mut self.energy.size_factor,
+            &mut self.energy.digestion_efficiency,
+            &mut self.reproduction.rate,
+            &mut self.reproduction.mutation_rate,
+            &mut self.reproduction.strategy,
+            &mut self.appearance.hue,
+            &mut self.appearance.saturation,
+            &mut self.behavior.movement_style.flocking_strength,
+            &mut self.behavior.movement_style.separation_distance,
+            &mut self.behavior.movement_style.alignment_strength,
+            &mut self.behavior.movement_style.cohesion_strength,
+            &mut self.behavior.movement_style.migration_heading,
+            &mut self.behavior.movement_style.flock_kin_threshold,
+            &mut self.behavior.gene_preference_strength,
+            &mut self.behavior.social_tendency,
+            &mut self.behavior.fearfulness,
+        ]
     }
 
     // Convenience getters for backward compatibility
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Generations of ancestry a pedigree keeps; relatedness through older ancestors is ignored
+pub const PEDIGREE_DEPTH: u8 = 4;
+
+/// Known ancestry of a sexually produced entity, by `Uid`: its parents and the parents of
+/// each ancestor whose own were known, once per ancestor however many lines lead to it.
+/// Founders carry no pedigree.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pedigree {
+    pub parents: (u64, u64), // Mother and father; the same uid twice when self-fertilised
+    pub ancestors: Vec<(u64, (u64, u64), u8)>, // (uid, its parents, generations back), 1 for a parent
+    pub parent_relatedness: f32, // How related the two parents were, see `relatedness`
+}
+
+impl Pedigree {
+    /// Pedigree of a child of `mother` and `father`, each given as its uid and its own
+    /// pedigree (`None` for founders). A self-fertilised child has the same parent twice.
+    pub fn offspring(mother: (u64, Option<&Pedigree>), father: (u64, Option<&Pedigree>)) -> Self {
+        let mut ancestors: Vec<(u64, (u64, u64), u8)> = Vec::new();
+        for (uid, pedigree) in [mother, father].into_iter() {
+            let Some(pedigree) = pedigree else {
+                continue;
+            };
+            let older = pedigree
+                .ancestors
+                .iter()
+                .filter(|(_, _, generations)| *generations < PEDIGREE_DEPTH - 1)
+                .map(|&(ancestor, parents, generations)| (ancestor, parents, generations + 1));
+            for (ancestor, parents, generations) in
+                std::iter::once((uid, pedigree.parents, 1)).chain(older)
+            {
+                match ancestors.iter_mut().find(|(known, ..)| *known == ancestor) {
+                    Some(known) => known.2 = known.2.min(generations),
+                    None => ancestors.push((ancestor, parents, generations)),
+                }
+            }
+        }
+        Self {
+            parents: (mother.0, father.0),
+            ancestors,
+            parent_relatedness: relatedness(mother, father),
+        }
+    }
+}
+
+/// Wright's coefficient of relationship between two entities, twice their coancestry
+/// (ignoring the ancestors' own inbreeding): 0.5 for full siblings or parent and child,
+/// 0.25 for half siblings, 0 with no shared ancestor within `PEDIGREE_DEPTH`, and 1 for an
+/// entity with itself
+pub fn relatedness(a: (u64, Option<&Pedigree>), b: (u64, Option<&Pedigree>)) -> f32 {
+    let mut parents = HashMap::new();
+    for (uid, pedigree) in [a, b] {
+        if let Some(pedigree) = pedigree {
+            parents.insert(uid, pedigree.parents);
+            parents.extend(
+                pedigree
+                    .ancestors
+                    .iter()
+                    .map(|&(ancestor, p, _)| (ancestor, p)),
+            );
+        }
+    }
+    (2.0 * coancestry(a.0, b.0, &parents)).min(1.0)
+}
+
+/// Chance that an allele drawn from `x` and one from `y` are copies of the same ancestral
+/// allele. Uids grow with birth order, so the younger of the two can't be an ancestor of
+/// the other and is the one traced back through its parents; each step halves the chance.
+fn coancestry(x: u64, y: u64, parents: &HashMap<u64, (u64, u64)>) -> f32 {
+    if x == y {
+        return 0.5;
+    }
+    let (younger, older) = (x.max(y), x.min(y));
+    parents.get(&younger).map_or(0.0, |&(mother, father)| {
+        0.5 * (coancestry(mother, older, parents) + coancestry(father, older, parents))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_siblings_are_related_and_strangers_are_not() {
+        let (mother, father, stranger_a, stranger_b) = (1, 2, 3, 4);
+        let sibling = Pedigree::offspring((mother, None), (father, None));
+        let unrelated = Pedigree::offspring((stranger_a, None), (stranger_b, None));
+        assert_eq!(sibling.parent_relatedness, 0.0);
+
+        let inbred = Pedigree::offspring((5, Some(&sibling)), (6, Some(&sibling)));
+        let outbred = Pedigree::offspring((5, Some(&sibling)), (7, Some(&unrelated)));
+        assert_eq!(inbred.parent_relatedness, 0.5);
+        assert_eq!(outbred.parent_relatedness, 0.0);
+
+        let half_sibling = Pedigree::offspring((mother, None), (stranger_a, None));
+        assert_eq!(
+            relatedness((5, Some(&sibling)), (8, Some(&half_sibling))),
+            0.25
+        );
+        assert_eq!(relatedness((5, Some(&sibling)), (mother, None)), 0.5);
+    }
+
+    #[test]
+    fn test_paths_through_a_shared_parent_are_not_counted_twice() {
+        // Grandparents 1 to 4, parents 5 and 6, then two full siblings 7 and 8
+        let mother = Pedigree::offspring((1, None), (2, None));
+        let father = Pedigree::offspring((3, None), (4, None));
+        let child = |uid: u64| {
+            (
+                uid,
+                Pedigree::offspring((5, Some(&mother)), (6, Some(&father))),
+            )
+        };
+        let ((a, a_pedigree), (b, b_pedigree)) = (child(7), child(8));
+
+        assert_eq!(
+            relatedness((a, Some(&a_pedigree)), (b, Some(&b_pedigree))),
+            0.5
+        );
+        assert_eq!(relatedness((a, Some(&a_pedigree)), (5, Some(&mother))), 0.5);
+        assert_eq!(relatedness((a, Some(&a_pedigree)), (1, None)), 0.25);
+        assert_eq!(relatedness((5, Some(&mother)), (6, Some(&father))), 0.0);
+    }
+
+    #[test]
+    fn test_pedigree_forgets_ancestors_beyond_its_depth() {
+        // A line of descent 0, 1, 2, ... each crossed with a founder
+        let mut pedigree: Option<Pedigree> = None;
+        for uid in 0..10 {
+            pedigree = Some(Pedigree::offspring(
+                (uid, pedigree.as_ref()),
+                (uid + 100, None),
+            ));
+        }
+        let pedigree = pedigree.as_ref();
+        assert!(pedigree
+            .unwrap()
+            .ancestors
+            .iter()
+            .all(|(_, _, generations)| { (1..PEDIGREE_DEPTH).contains(generations) }));
+        let to_ancestor = |uid: u64| relatedness((10, pedigree), (uid, None));
+        assert_eq!(to_ancestor(9), 0.5);
+        assert_eq!(to_ancestor(10 - PEDIGREE_DEPTH as u64), 0.0625);
+        assert_eq!(to_ancestor(9 - PEDIGREE_DEPTH as u64), 0.0);
+    }
+}
@@ -0,0 +1,69 @@
+use super::Genes;
+use crate::components::{Color, MovementType};
+
+/// Traits compared by `Genes::calculate_gene_similarity`
+const SIMILARITY_TRAITS: usize = 12;
+
+/// Weight of a differing movement type in the similarity
+const MOVEMENT_TYPE_WEIGHT: f32 = 0.3;
+
+/// Sum of every trait weight and `MOVEMENT_TYPE_WEIGHT`, so similarity stays within 0..=1
+const TOTAL_WEIGHT: f32 = 2.4;
+
+/// Values derived from an entity's genes, cached as a component when the genes are set so
+/// neighbour loops needn't recompute them. Stale if the genes change without refreshing it.
+#[derive(Clone, Debug)]
+pub struct Phenotype {
+    pub color: Color,                            // Same as `Genes::get_color`
+    similarity_vector: [f32; SIMILARITY_TRAITS], // See `Genes::similarity_vector`
+    style: MovementType,
+}
+
+impl Phenotype {
+    pub fn of(genes: &Genes) -> Self {
+        Self {
+            color: genes.get_color(),
+            similarity_vector: genes.similarity_vector(),
+            style: genes.behavior.movement_style.style.clone(),
+        }
+    }
+
+    /// `Genes::calculate_gene_similarity` between the genes the two phenotypes came from
+    pub fn similarity(&self, other: &Phenotype) -> f32 {
+        let same_type = std::mem::discriminant(&self.style) == std::mem::discriminant(&other.style);
+        weighted_distance(&self.similarity_vector, &other.similarity_vector, same_type)
+    }
+}
+
+impl Genes {
+    /// Each trait compared for similarity, divided by its typical range and scaled by its
+    /// weight, so the similarity of two genomes is an L1 distance between these vectors
+    pub(super) fn similarity_vector(&self) -> [f32; SIMILARITY_TRAITS] {
+        let movement = &self.behavior.movement_style;
+        [
+            self.movement.speed / 2.5 * 0.3,
+            self.movement.sense_radius / 180.0 * 0.2,
+            self.energy.efficiency / 4.0 * 0.15,
+            self.energy.loss_rate / 3.0 * 0.15,
+            self.energy.gain_rate / 5.0 * 0.1,
+            self.energy.size_factor / 3.5 * 0.1,
+            self.energy.digestion_efficiency * 0.1,
+            self.appearance.hue * 0.3,
+            self.appearance.saturation * 0.2,
+            movement.flocking_strength * 0.2,
+            self.behavior.social_tendency * 0.2,
+            self.behavior.gene_preference_strength * 0.1,
+        ]
+    }
+}
+
+/// Weighted difference of two similarity vectors and movement types, normalised to 0..=1
+pub(super) fn weighted_distance(
+    a: &[f32; SIMILARITY_TRAITS],
+    b: &[f32; SIMILARITY_TRAITS],
+    same_type: bool,
+) -> f32 {
+    let traits: f32 = a.iter().zip(b).map(|(a, b)| (a - b).abs()).sum();
+    let movement_type = if same_type { 0.0 } else { MOVEMENT_TYPE_WEIGHT };
+    (traits + movement_type) / TOTAL_WEIGHT
+}
@@ -0,0 +1,24 @@
+use super::{Genes, GENE_BOUNDS};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+impl Genes {
+    /// Compact, copy-pasteable code for sharing a creature between runs
+    pub fn to_share_string(&self) -> String {
+        let bytes = bincode::serialize(self).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Decode a share code, rejecting genes outside `GENE_BOUNDS`
+    pub fn from_share_string(code: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let bytes = URL_SAFE_NO_PAD.decode(code.trim())?;
+        let genes: Genes = bincode::deserialize(&bytes)?;
+
+        for (value, bounds) in genes.traits().iter().zip(GENE_BOUNDS.iter()) {
+            if !(bounds.min..=bounds.max).contains(value) {
+                return Err(format!("{} out of range: {}", bounds.name, value).into());
+            }
+        }
+        Ok(genes)
+    }
+}
@@ -1,5 +1,6 @@
 use super::*;
 use crate::components::Size;
+use crate::config::{EnergyConfig, GeneticsConfig};
 use rand::thread_rng;
 
 #[test]
@@ -93,17 +94,88 @@ fn test_genes_get_energy_gain() {
     let other_size = Size { radius: 10.0 };
     let self_size = Size { radius: 8.0 };
 
-    let energy_gain = genes.get_energy_gain(50.0, &other_size, &self_size, &genes);
+    let phenotype = Phenotype::of(&genes);
+    let energy_gain = genes.get_energy_gain(
+        50.0,
+        &other_size,
+        &self_size,
+        &phenotype,
+        &phenotype,
+        &EnergyConfig::default(),
+    );
 
     // Energy gain should be positive and reasonable
     assert!(energy_gain > 0.0);
-    // The formula is: other_energy * gain_rate * 0.3 * (1 + size_ratio * 0.3).min(1.5)
+    // With the default config the formula is:
+    // other_energy * gain_rate * 0.3 * (1 + size_ratio * 0.3).min(1.5)
     // With max gain_rate of 4.5, max size_ratio of 1.25, the theoretical max is:
     // 50 * 4.5 * 0.3 * 1.5 = 101.25
     // But in practice, we expect values around 20-60
     assert!(energy_gain <= 150.0); // Allow for the full range of possible values
 }
 
+#[test]
+fn test_better_digestion_absorbs_more_energy() {
+    let mut rng = thread_rng();
+    let mut efficient = Genes::new_random(&mut rng);
+    let mut wasteful = efficient.clone();
+    efficient.energy.digestion_efficiency = 0.9;
+    wasteful.energy.digestion_efficiency = 0.3;
+    let prey = Phenotype::of(&Genes::new_random(&mut rng));
+    let prey_size = Size { radius: 5.0 };
+    let self_size = Size { radius: 8.0 };
+
+    let efficient_gain = efficient.get_energy_gain(
+        50.0,
+        &prey_size,
+        &self_size,
+        &Phenotype::of(&efficient),
+        &prey,
+        &EnergyConfig::default(),
+    );
+    let wasteful_gain = wasteful.get_energy_gain(
+        50.0,
+        &prey_size,
+        &self_size,
+        &Phenotype::of(&wasteful),
+        &prey,
+        &EnergyConfig::default(),
+    );
+    assert!(efficient_gain > wasteful_gain);
+}
+
+#[test]
+fn test_meal_gain_factor_scales_energy_gain_proportionally() {
+    let mut rng = thread_rng();
+    let predator = Genes::new_random(&mut rng);
+    let phenotypes = (
+        Phenotype::of(&predator),
+        Phenotype::of(&Genes::new_random(&mut rng)),
+    );
+    let prey_size = Size { radius: 5.0 };
+    let self_size = Size { radius: 8.0 };
+    let base = EnergyConfig::default();
+    let doubled = EnergyConfig {
+        meal_gain_factor: base.meal_gain_factor * 2.0,
+        ..base.clone()
+    };
+
+    let gain = |config| {
+        let (predator_phenotype, prey) = &phenotypes;
+        predator.get_energy_gain(
+            50.0,
+            &prey_size,
+            &self_size,
+            predator_phenotype,
+            prey,
+            config,
+        )
+    };
+    let base_gain = gain(&base);
+    let doubled_gain = gain(&doubled);
+    assert!((doubled_gain - 2.0 * base_gain).abs() < 1e-4 * base_gain.abs().max(1.0));
+}
+
 #[test]
 fn test_genes_getter_methods() {
     let mut rng = thread_rng();
@@ -155,8 +227,9 @@ fn test_predation_preference() {
     let genes3 = genes1.clone();
 
     // Preference for different genes should be higher than for similar genes
-    let preference_different = genes1.get_predation_preference(&genes2);
-    let preference_similar = genes1.get_predation_preference(&genes3);
+    let phenotype = Phenotype::of(&genes1);
+    let preference_different = genes1.get_predation_preference(&phenotype, &Phenotype::of(&genes2));
+    let preference_similar = genes1.get_predation_preference(&phenotype, &Phenotype::of(&genes3));
 
     // The test should account for the fact that when gene_preference_strength is low,
     // the base preference (0.3) dominates, so we can't guarantee preference_different >= preference_similar
@@ -187,12 +260,12 @@ fn test_predation_preference() {
 
     // Preference should be in valid range
     assert!(
-        preference_different >= 0.0 && preference_different <= 1.0,
+        (0.0..=1.0).contains(&preference_different),
         "Predation preference should be in [0,1], got: {}",
         preference_different
     );
     assert!(
-        preference_similar >= 0.0 && preference_similar <= 1.0,
+        (0.0..=1.0).contains(&preference_similar),
         "Predation preference should be in [0,1], got: {}",
         preference_similar
     );
@@ -206,9 +279,23 @@ fn test_energy_gain_with_gene_preference() {
     let self_size = Size { radius: 8.0 };
 
     // Test energy gain with different gene preferences
-    let energy_gain_similar = genes.get_energy_gain(50.0, &other_size, &self_size, &genes);
-    let energy_gain_different =
-        genes.get_energy_gain(50.0, &other_size, &self_size, &Genes::new_random(&mut rng));
+    let phenotype = Phenotype::of(&genes);
+    let energy_gain_similar = genes.get_energy_gain(
+        50.0,
+        &other_size,
+        &self_size,
+        &phenotype,
+        &phenotype,
+        &EnergyConfig::default(),
+    );
+    let energy_gain_different = genes.get_energy_gain(
+        50.0,
+        &other_size,
+        &self_size,
+        &phenotype,
+        &Phenotype::of(&Genes::new_random(&mut rng)),
+        &EnergyConfig::default(),
+    );
 
     // Energy gain should be positive and reasonable
     assert!(energy_gain_similar > 0.0);
@@ -286,3 +373,173 @@ fn test_genes_clone() {
     assert_eq!(original.reproduction.rate, cloned.reproduction.rate);
     assert_eq!(original.appearance.hue, cloned.appearance.hue);
 }
+
+#[test]
+fn test_mutate_scaled_zero_disables_mutation() {
+    let mut rng = thread_rng();
+    let mut genes = Genes::new_random(&mut rng);
+    genes.reproduction.mutation_rate = 0.25;
+
+    for _ in 0..100 {
+        let child = genes.mutate_scaled(&mut rng, 0.0);
+        assert_eq!(child.traits(), genes.traits());
+    }
+}
+
+#[test]
+fn test_mutation_respects_gene_bounds() {
+    let mut rng = thread_rng();
+    let mut genes = Genes::new_random(&mut rng);
+    for _ in 0..200 {
+        genes = genes.mutate_scaled(&mut rng, 10.0);
+    }
+
+    for (value, bounds) in genes.traits().iter().zip(GENE_BOUNDS.iter()) {
+        assert!(
+            (bounds.min..=bounds.max).contains(value),
+            "{} out of range: {}",
+            bounds.name,
+            value
+        );
+    }
+}
+
+#[test]
+fn test_migration_heading_wraps_round_a_full_turn() {
+    use std::f32::consts::TAU;
+    let mut genes = Genes::new_random(&mut thread_rng());
+    let heading = |genes: &Genes| genes.behavior.movement_style.migration_heading;
+
+    assert!(genes.set_trait("migration_heading", -0.5));
+    assert!((heading(&genes) - (TAU - 0.5)).abs() < 1e-5);
+    assert!(genes.set_trait("migration_heading", TAU + 0.25));
+    assert!((heading(&genes) - 0.25).abs() < 1e-5);
+
+    // Clamping would pile mutated headings up at the ends of the range
+    let mut rng = thread_rng();
+    let near_ends = (0..200)
+        .filter(|_| {
+            genes = genes.mutate_scaled(&mut rng, 10.0);
+            heading(&genes) < 1e-6 || heading(&genes) > TAU - 1e-6
+        })
+        .count();
+    assert_eq!(near_ends, 0);
+}
+
+#[test]
+fn test_diploid_headings_blend_across_zero() {
+    use std::f32::consts::{PI, TAU};
+    let mut rng = thread_rng();
+    let allele = |heading: f32, rng: &mut _| {
+        let mut genes = Genes::new_random(rng);
+        genes.behavior.movement_style.migration_heading = heading;
+        genes
+    };
+    let genotype = DiploidGenes {
+        a: allele(0.1, &mut rng),
+        b: allele(6.2, &mut rng),
+    };
+
+    let heading = |dominance| {
+        genotype
+            .express(dominance)
+            .behavior
+            .movement_style
+            .migration_heading
+    };
+    let midway = heading(0.5);
+    let from_zero = midway.min(TAU - midway);
+    assert!(from_zero < 0.1, "heading {} should be near 0", midway);
+    assert!((midway - PI).abs() > 3.0);
+    assert!((heading(0.0) - 6.2).abs() < 1e-5);
+    assert!((heading(1.0) - 0.1).abs() < 1e-5);
+}
+
+#[test]
+fn test_locked_trait_never_mutates() {
+    let mut rng = thread_rng();
+    let mut genetics = GeneticsConfig {
+        mutation_rate_scale: 10.0,
+        ..GeneticsConfig::default()
+    };
+    genetics.locked_genes.insert("speed".to_string(), 1.25);
+
+    let mut genes = Genes::new_random(&mut rng);
+    genes.lock_traits(&genetics.locked_genes);
+    let original = genes.clone();
+    for _ in 0..500 {
+        genes = genes.mutate_with(&mut rng, &genetics);
+        assert_eq!(genes.speed(), 1.25);
+    }
+    assert_ne!(genes.traits()[1..], original.traits()[1..]);
+}
+
+#[test]
+fn test_zero_trait_mutation_rate_conserves_trait() {
+    let mut rng = thread_rng();
+    let mut genetics = GeneticsConfig {
+        mutation_rate_scale: 10.0,
+        ..GeneticsConfig::default()
+    };
+    genetics
+        .trait_mutation_rates
+        .insert("speed".to_string(), 0.0);
+
+    let mut genes = Genes::new_random(&mut rng);
+    let original = genes.clone();
+    for _ in 0..500 {
+        genes = genes.mutate_with(&mut rng, &genetics);
+        assert_eq!(genes.speed(), original.speed());
+    }
+    assert_ne!(genes.traits()[1..], original.traits()[1..]);
+}
+
+#[test]
+fn test_share_string_round_trip() {
+    let mut rng = thread_rng();
+    let genes = Genes::new_random(&mut rng);
+
+    let decoded = Genes::from_share_string(&genes.to_share_string()).unwrap();
+    assert_eq!(decoded.traits(), genes.traits());
+    assert_eq!(
+        decoded.behavior.movement_style.style,
+        genes.behavior.movement_style.style
+    );
+}
+
+#[test]
+fn test_share_string_rejects_invalid_codes() {
+    let mut genes = Genes::new_random(&mut thread_rng());
+    genes.movement.speed = 100.0;
+
+    assert!(Genes::from_share_string(&genes.to_share_string()).is_err());
+    assert!(Genes::from_share_string("not a gene code").is_err());
+}
+
+#[test]
+fn test_distant_genotypes_get_distinct_colors() {
+    let mut rng = thread_rng();
+    let mut low = Genes::new_random(&mut rng);
+    let mut high = low.clone();
+    for (bounds, (lo, hi)) in GENE_BOUNDS
+        .iter()
+        .zip(low.traits_mut().into_iter().zip(high.traits_mut()))
+    {
+        *lo = bounds.min;
+        *hi = bounds.max;
+    }
+    // Identical appearance genes, so only the genotype colour can tell them apart
+    low.appearance = high.appearance.clone();
+
+    let distance = |a: &Color, b: &Color| {
+        ((a.r - b.r).powi(2) + (a.g - b.g).powi(2) + (a.b - b.b).powi(2)).sqrt()
+    };
+    let (low_color, high_color) = (low.genotype_color(), high.genotype_color());
+    assert!(distance(&low_color, &high_color) > 0.5);
+    assert_eq!(low.get_color().r, high.get_color().r);
+
+    // A small change in one trait only shifts the colour slightly
+    let mut near = low.clone();
+    near.movement.speed += 0.05;
+    assert!(distance(&near.genotype_color(), &low_color) < 0.1);
+}
@@ -0,0 +1,165 @@
+use crate::render::{instances_from_buffer, EntityPipeline, SimulationUniforms};
+use crate::simulation::Simulation;
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// Renders a simulation into an offscreen texture with the same pipeline and shader as the
+/// browser, then reads the pixels back. Lets rendering be checked without a window, so
+/// shader regressions show up in tests.
+pub struct HeadlessRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: EntityPipeline,
+    texture: wgpu::Texture,
+    readback: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_row: u32, // Bytes per row in `readback`, rounded up to wgpu's copy alignment
+}
+
+impl HeadlessRenderer {
+    /// `None` when no GPU adapter, hardware or software, is available
+    pub fn new(width: u32, height: u32) -> Option<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))?;
+        let (device, queue) =
+            block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None)).ok()?;
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let padded_row =
+            (width * BYTES_PER_PIXEL).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: (padded_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let pipeline = EntityPipeline::new(&device, FORMAT);
+
+        Some(Self {
+            device,
+            queue,
+            pipeline,
+            texture,
+            readback,
+            width,
+            height,
+            padded_row,
+        })
+    }
+
+    /// Draw the whole world as it is now, returning tightly packed RGBA8 rows
+    pub fn render(&mut self, sim: &Simulation) -> Vec<u8> {
+        let layout = crate::LAYOUT_ENERGY;
+        let mut entities = Vec::new();
+        crate::pack_entities(sim, layout, &mut entities);
+        let uniforms = SimulationUniforms {
+            world_size: sim.world_size(),
+            interpolation_factor: 1.0,
+            camera_zoom: 1.0,
+            ..SimulationUniforms::default()
+        };
+        self.pipeline.upload(
+            &self.queue,
+            &uniforms,
+            &instances_from_buffer(&entities, layout),
+        );
+
+        let view = self
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Offscreen Encoder"),
+            });
+        self.pipeline.draw(&mut encoder, &view);
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            self.texture.size(),
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = self.readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::Maintain::Wait);
+        let row = (self.width * BYTES_PER_PIXEL) as usize;
+        let pixels = slice
+            .get_mapped_range()
+            .chunks(self.padded_row as usize)
+            .flat_map(|padded| padded[..row].to_vec())
+            .collect();
+        self.readback.unmap();
+        pixels
+    }
+}
+
+struct NoopWake;
+
+impl Wake for NoopWake {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Native wgpu futures complete without an event loop, so polling until ready is enough
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(NoopWake));
+    let mut context = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+
+    #[test]
+    fn test_rendered_frame_has_entity_pixels() {
+        let Some(mut renderer) = HeadlessRenderer::new(256, 256) else {
+            eprintln!("skipping: no GPU adapter available");
+            return;
+        };
+        let mut config = SimulationConfig::default();
+        config.population.initial_entities = 200;
+        config.population.entity_scale = 1.0;
+        let sim = Simulation::new_seeded(400.0, config, 21);
+
+        let pixels = renderer.render(&sim);
+        assert_eq!(pixels.len(), 256 * 256 * 4);
+        let lit = pixels
+            .chunks(4)
+            .filter(|pixel| pixel[..3].iter().any(|&channel| channel > 0))
+            .count();
+        assert!(lit > 0, "frame is entirely background");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::config::SimulationConfig;
+use crate::simulation::Simulation;
+use hecs::Entity;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+
+/// Several independent simulations (islands) with occasional migration between them, for
+/// studying speciation under limited gene flow
+pub struct IslandModel {
+    islands: Vec<Simulation>,
+    migration_rate: f32, // Chance per island per step that one random entity emigrates
+    rng: StdRng,         // Drives migration only; each island has its own seeded stream
+}
+
+impl IslandModel {
+    /// `count` islands of the same size and config, each seeded from `seed` and its index
+    pub fn new(
+        count: usize,
+        world_size: f32,
+        config: &SimulationConfig,
+        seed: u64,
+        migration_rate: f32,
+    ) -> Self {
+        let islands = (0..count as u64)
+            .map(|i| {
+                let island_seed = seed ^ (i + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                Simulation::new_seeded(world_size, config.clone(), island_seed)
+            })
+            .collect();
+        Self {
+            islands,
+            migration_rate,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn islands(&self) -> &[Simulation] {
+        &self.islands
+    }
+
+    pub fn total_population(&self) -> u32 {
+        self.islands.iter().map(|island| island.world().len()).sum()
+    }
+
+    /// Step every island in parallel, then migrate. Returns the number of migrants.
+    pub fn update(&mut self) -> usize {
+        self.islands
+            .par_iter_mut()
+            .for_each(|island| island.update());
+        self.migrate()
+    }
+
+    /// With probability `migration_rate`, move one random entity from each island to a
+    /// random other island. Returns the number of migrants.
+    pub fn migrate(&mut self) -> usize {
+        if self.islands.len() < 2 {
+            return 0;
+        }
+        let mut migrants = 0;
+        for from in 0..self.islands.len() {
+            if self.rng.gen::<f32>() >= self.migration_rate {
+                continue;
+            }
+            // Sorted so the pick depends only on the seed, not on archetype layout
+            let mut residents: Vec<Entity> = self.islands[from]
+                .world()
+                .iter()
+                .map(|entity| entity.entity())
+                .collect();
+            residents.sort_unstable_by_key(|entity| entity.to_bits());
+            let Some(&migrant) = residents.choose(&mut self.rng) else {
+                continue;
+            };
+            let to = (from + self.rng.gen_range(1..self.islands.len())) % self.islands.len();
+            if self.move_entity(from, to, migrant).is_some() {
+                migrants += 1;
+            }
+        }
+        migrants
+    }
+
+    /// Move `entity` from island `from` to island `to`, returning its id on arrival
+    pub fn move_entity(&mut self, from: usize, to: usize, entity: Entity) -> Option<Entity> {
+        if from == to || from.max(to) >= self.islands.len() {
+            return None;
+        }
+        let (low, high) = self.islands.split_at_mut(from.max(to));
+        let (source, destination) = if from < to {
+            (&mut low[from], &mut high[0])
+        } else {
+            (&mut high[0], &mut low[to])
+        };
+        source.transfer_entity(entity, destination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genes::Genes;
+
+    fn small_config() -> SimulationConfig {
+        let mut config = SimulationConfig::default();
+        config.population.initial_entities = 100;
+        config.population.entity_scale = 1.0;
+        config
+    }
+
+    #[test]
+    fn test_migration_moves_entities_and_conserves_population() {
+        let mut model = IslandModel::new(3, 200.0, &small_config(), 9, 1.0);
+        let populations: Vec<u32> = model.islands().iter().map(|i| i.world().len()).collect();
+
+        let (entity, genes) = model.islands()[0]
+            .world()
+            .query::<&Genes>()
+            .iter()
+            .map(|(entity, genes)| (entity, genes.clone()))
+            .next()
+            .unwrap();
+        let arrived = model.move_entity(0, 2, entity).unwrap();
+        assert!(!model.islands()[0].world().contains(entity));
+        assert_eq!(model.islands()[0].world().len(), populations[0] - 1);
+        assert_eq!(model.islands()[2].world().len(), populations[2] + 1);
+        let moved_genes = model.islands()[2]
+            .world()
+            .get::<&Genes>(arrived)
+            .unwrap()
+            .traits();
+        assert_eq!(moved_genes, genes.traits());
+
+        let total = model.total_population();
+        assert_eq!(model.migrate(), 3);
+        assert_eq!(model.total_population(), total);
+    }
+
+    #[test]
+    fn test_islands_update_independently() {
+        let mut model = IslandModel::new(2, 200.0, &small_config(), 4, 0.0);
+        for _ in 0..5 {
+            assert_eq!(model.update(), 0);
+        }
+        assert!(model.islands().iter().all(|island| island.step() == 5));
+    }
+}
@@ -1,9 +1,18 @@
+use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
+#[allow(dead_code)] // Monte Carlo study helpers, not exported to JS
+mod batch_processor;
 mod components;
 mod config;
 mod genes;
+#[cfg(not(target_arch = "wasm32"))]
+mod headless;
+mod island_model;
+mod render;
+mod sampling;
 mod simulation;
+mod snapshot;
 mod spatial_grid;
 mod stats;
 mod systems;
@@ -14,63 +23,279 @@ mod web;
 // Re-export the thread pool initialization
 pub use wasm_bindgen_rayon::init_thread_pool;
 
+/// Entity buffer layout. Every entity starts with 8 floats: prev_x, prev_y, cur_x, cur_y,
+/// radius, r, g, b. Optional fields follow in flag order, selected with
+/// `WebSimulation::set_buffer_layout`:
+/// - `LAYOUT_ENERGY` (1): energy fraction, 1 float
+/// - `LAYOUT_VELOCITY` (2): vx, vy, 2 floats
+pub const LAYOUT_ENERGY: u32 = 1;
+pub const LAYOUT_VELOCITY: u32 = 2;
+const BASE_STRIDE: usize = 8;
+
+/// Floats per entity for a layout
+pub fn layout_stride(layout: u32) -> usize {
+    let mut stride = BASE_STRIDE;
+    if layout & LAYOUT_ENERGY != 0 {
+        stride += 1;
+    }
+    if layout & LAYOUT_VELOCITY != 0 {
+        stride += 2;
+    }
+    stride
+}
+
+/// Fill `buffer` with every entity in `layout`, in entity id order
+fn pack_entities(sim: &simulation::Simulation, layout: u32, buffer: &mut Vec<f32>) {
+    buffer.clear();
+    for e in sim.render_entities_sorted() {
+        buffer.extend_from_slice(&[
+            e.prev_pos.x,
+            e.prev_pos.y,
+            e.pos.x,
+            e.pos.y,
+            e.radius,
+            e.color.r,
+            e.color.g,
+            e.color.b,
+        ]);
+        if layout & LAYOUT_ENERGY != 0 {
+            buffer.push(e.energy_fraction);
+        }
+        if layout & LAYOUT_VELOCITY != 0 {
+            buffer.extend_from_slice(&[e.velocity.x, e.velocity.y]);
+        }
+    }
+}
+
+/// Error handed to JavaScript as a `{ kind, field, message }` object, so the page can
+/// point at the offending input rather than parse a string
+#[derive(Debug, Serialize)]
+struct ApiError {
+    kind: &'static str,    // "parse", "invalid_config", "invalid_genes" or "serialize"
+    field: Option<String>, // "section.field" of the config field that failed validation
+    message: String,
+}
+
+impl ApiError {
+    fn new(kind: &'static str, message: String) -> Self {
+        Self {
+            kind,
+            field: None,
+            message,
+        }
+    }
+}
+
+impl From<config::ConfigError> for ApiError {
+    fn from(error: config::ConfigError) -> Self {
+        Self {
+            kind: "invalid_config",
+            field: Some(error.field),
+            message: error.message,
+        }
+    }
+}
+
+impl From<ApiError> for JsValue {
+    fn from(error: ApiError) -> Self {
+        serde_wasm_bindgen::to_value(&error).unwrap_or_else(|_| JsValue::from_str(&error.message))
+    }
+}
+
+/// Parse and validate a config passed in from JavaScript
+fn parse_config(config_json: &str) -> Result<config::SimulationConfig, ApiError> {
+    let config: config::SimulationConfig = serde_json::from_str(config_json)
+        .map_err(|e| ApiError::new("parse", format!("Config parse error: {}", e)))?;
+    config.validate()?;
+    Ok(config)
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(value)
+        .map_err(|e| ApiError::new("serialize", e.to_string()).into())
+}
+
 #[wasm_bindgen]
 pub struct WebSimulation {
     simulation: simulation::Simulation,
     config: config::SimulationConfig,
-    entity_buffer: Vec<f32>, // Reusable buffer for entity data
+    timestep: simulation::FixedTimestep,
+    followed: Option<hecs::Entity>, // Entity the camera tracks, cleared when it dies
+    entity_buffer: Vec<f32>,        // Reusable buffer for entity data
+    buffer_layout: u32,             // `LAYOUT_*` flags for the next buffer update
+    packed_layout: u32,             // `LAYOUT_*` flags the buffer currently holds
+    population_series: stats::PopulationSeries, // Recent samples for the live charts
+    time_scale: f32, // Steps per `update` call, and simulated time per real time in `tick`
+    step_accumulator: f32, // Fraction of a step `update` has owed since its last step
 }
 
 #[wasm_bindgen]
 impl WebSimulation {
+    /// Fails with a `{ kind, field, message }` object if the config doesn't parse or
+    /// validate
     #[wasm_bindgen(constructor)]
     pub fn new(world_size: f32, config_json: &str) -> Result<WebSimulation, JsValue> {
-        let config: config::SimulationConfig = serde_json::from_str(config_json)
-            .map_err(|e| JsValue::from_str(&format!("Config parse error: {}", e)))?;
+        let config = parse_config(config_json)?;
 
         let simulation = simulation::Simulation::new_with_config(world_size, config.clone());
 
         Ok(WebSimulation {
             simulation,
             config,
-            entity_buffer: Vec::with_capacity(60000), // 10000 entities * 6 floats
+            timestep: simulation::FixedTimestep::default(),
+            followed: None,
+            entity_buffer: Vec::with_capacity(10000 * layout_stride(LAYOUT_ENERGY)),
+            buffer_layout: LAYOUT_ENERGY,
+            packed_layout: LAYOUT_ENERGY,
+            population_series: stats::PopulationSeries::default(),
+            time_scale: 1.0,
+            step_accumulator: 0.0,
         })
     }
 
-    pub fn update(&mut self) {
-        self.simulation.update();
+    /// Advance by `time_scale` steps, carrying any fraction over to the next call, and
+    /// at most `timing.max_steps_per_frame` of them; returns how many ran
+    pub fn update(&mut self) -> u32 {
+        self.step_accumulator += self.time_scale;
+        let due = self.step_accumulator.floor();
+        self.step_accumulator -= due;
+        let steps = (due as u32).min(self.config.timing.max_steps_per_frame);
+        for _ in 0..steps {
+            self.step();
+        }
+        steps
     }
 
-    /// Update entity buffer and return pointer for WebGPU renderer
-    pub fn update_entity_buffer(&mut self) -> *const f32 {
-        let entity_tuples = self.simulation.get_entities();
-        self.entity_buffer.clear();
-
-        for (px, py, cx, cy, radius, r, g, b) in entity_tuples {
-            self.entity_buffer.push(px);
-            self.entity_buffer.push(py);
-            self.entity_buffer.push(cx);
-            self.entity_buffer.push(cy);
-            self.entity_buffer.push(radius);
-            self.entity_buffer.push(r);
-            self.entity_buffer.push(g);
-            self.entity_buffer.push(b);
+    /// Run the simulation `scale` times as fast: 0.5 steps every other `update`, 2.0 steps
+    /// twice per `update`, and `tick` treats elapsed time as `scale` times longer. 0 pauses.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.time_scale = if scale.is_finite() {
+            scale.max(0.0)
+        } else {
+            0.0
+        };
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Advance by real elapsed time, scaled by the time scale, running as many fixed
+    /// timesteps as are due
+    pub fn tick(&mut self, elapsed_ms: f32) -> u32 {
+        let steps = self
+            .timestep
+            .advance(elapsed_ms * self.time_scale, &self.config.timing);
+        for _ in 0..steps {
+            self.step();
         }
+        steps
+    }
+
+    /// Recent `{ step, count, average_energy, species_counts }` samples, oldest first, one
+    /// every `rendering.population_sample_interval` steps and at most
+    /// `rendering.population_series_length` of them
+    pub fn get_population_series(&self) -> Result<JsValue, JsValue> {
+        to_js(self.population_series.samples())
+    }
+
+    /// How far rendering is between the last two simulation states, in [0, 1]
+    pub fn interpolation_factor(&self) -> f32 {
+        self.timestep.interpolation_factor(&self.config.timing)
+    }
+
+    /// Refill the entity buffer and return a pointer to it for the WebGPU renderer.
+    /// The pointer is valid until the next call, which may reallocate the buffer; read
+    /// exactly `entity_count()` entities (`buffer_len()` floats) from it. Both are derived
+    /// from the buffer itself, so they always match the last call.
+    pub fn update_entity_buffer(&mut self) -> *const f32 {
+        let layout = self.buffer_layout;
+        pack_entities(&self.simulation, layout, &mut self.entity_buffer);
+        self.packed_layout = layout;
 
         self.entity_buffer.as_ptr()
     }
 
+    /// Select the optional fields packed per entity from the next `update_entity_buffer`
+    /// call on, as `LAYOUT_*` flags (1 = energy fraction, 2 = velocity)
+    pub fn set_buffer_layout(&mut self, flags: u32) {
+        self.buffer_layout = flags & (LAYOUT_ENERGY | LAYOUT_VELOCITY);
+    }
+
+    /// Layout flags of the buffer as of the last `update_entity_buffer` call
+    pub fn buffer_layout(&self) -> u32 {
+        self.packed_layout
+    }
+
+    /// Floats per entity in the buffer as of the last `update_entity_buffer` call
+    pub fn entity_stride(&self) -> u32 {
+        layout_stride(self.packed_layout) as u32
+    }
+
+    /// Entities in the buffer as of the last `update_entity_buffer` call
     pub fn entity_count(&self) -> u32 {
-        (self.entity_buffer.len() / 6) as u32
+        (self.entity_buffer.len() / layout_stride(self.packed_layout)) as u32
     }
 
-    pub fn get_stats(&self) -> JsValue {
-        let stats = stats::SimulationStats::from_world(
-            self.simulation.world(),
-            self.config.population.max_population as f32,
-            self.config.population.entity_scale,
-        );
-        serde_wasm_bindgen::to_value(&stats).unwrap_or(JsValue::NULL)
+    /// Floats in the buffer as of the last `update_entity_buffer` call
+    pub fn buffer_len(&self) -> u32 {
+        self.entity_buffer.len() as u32
+    }
+
+    /// `[style, threshold]` for marking low-energy entities, where style is 0 = none,
+    /// 1 = outline, 2 = energy bar
+    pub fn low_energy_marker(&self) -> Vec<f32> {
+        let rendering = &self.config.rendering;
+        vec![
+            rendering.low_energy_style as u32 as f32,
+            rendering.low_energy_threshold,
+        ]
+    }
+
+    pub fn get_stats(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.simulation.stats())
+    }
+
+    /// Average ms per update phase over recent steps, as `[phase, ms]` pairs
+    pub fn get_timing_report(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.simulation.timing_report())
+    }
+
+    /// Non-empty cells, fullest cell, mean per cell and cells over the soft capacity
+    pub fn get_cell_occupancy(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.simulation.cell_occupancy())
+    }
+
+    /// Non-empty grid cells as `{x, y, size, count}`, for the debug partition overlay
+    pub fn get_grid_cells(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.simulation.grid_cells())
+    }
+
+    /// Entity trails packed as `[count, r, g, b, x0, y0, x1, y1, ...]` per trail, with
+    /// `count` points from oldest to current; empty while trails are off
+    pub fn get_trails(&self) -> Vec<f32> {
+        let mut packed = Vec::new();
+        for trail in self.simulation.trails() {
+            let color = &trail.color;
+            packed.extend([trail.points.len() as f32, color.r, color.g, color.b]);
+            packed.extend(trail.points.iter().flat_map(|point| [point.x, point.y]));
+        }
+        packed
+    }
+
+    /// Turn trail rendering (`rendering.trails`) on or off
+    pub fn set_trails(&mut self, enabled: bool) {
+        self.config.rendering.trails = enabled;
+        self.simulation.update_config(self.config.clone());
+    }
+
+    pub fn trails_enabled(&self) -> bool {
+        self.config.rendering.trails
+    }
+
+    /// Per-cell gene entropy as `[[cell_x, cell_y], entropy]` pairs
+    pub fn get_cell_diversity(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.simulation.cell_diversity_map())
     }
 
     pub fn get_world_size(&self) -> f32 {
@@ -85,17 +310,226 @@ impl WebSimulation {
             "repro_threshold" => self.config.reproduction.reproduction_energy_threshold = value,
             "energy_cost" => self.config.energy.size_energy_cost_factor = value,
             "bounce_factor" => self.config.physics.velocity_bounce_factor = value,
+            "mutation_rate" => self.config.genetics.mutation_rate_scale = value,
             _ => {}
         }
         self.simulation.update_config(self.config.clone());
     }
 
+    /// Share code for the creature nearest a world position, if any is within reach
+    pub fn export_genes_at(&self, x: f32, y: f32) -> Option<String> {
+        self.simulation
+            .genes_near(x, y, 20.0)
+            .map(|genes| genes.to_share_string())
+    }
+
+    /// Start following the creature nearest a world position; returns whether one was found
+    pub fn follow_at(&mut self, x: f32, y: f32) -> bool {
+        self.followed = self.simulation.entity_near(x, y, 20.0);
+        self.followed.is_some()
+    }
+
+    pub fn unfollow(&mut self) {
+        self.followed = None;
+    }
+
+    /// `[x, y]` of the followed creature, or nothing once it has died
+    pub fn followed_position(&mut self) -> Option<Vec<f32>> {
+        let pos = self
+            .followed
+            .and_then(|entity| self.simulation.position_of(entity));
+        if pos.is_none() {
+            self.followed = None;
+        }
+        pos.map(|pos| vec![pos.x, pos.y])
+    }
+
+    /// Set a trait of the followed creature, clamped to its bounds; returns the new value,
+    /// or nothing when no creature is followed or the trait is unknown
+    pub fn set_followed_gene(&mut self, field: &str, value: f32) -> Option<f32> {
+        let entity = self.followed?;
+        self.simulation.set_gene(entity, field, value)
+    }
+
+    /// Spawn `count` copies of a shared creature
+    pub fn inject_genes(&mut self, code: &str, count: u32) -> Result<(), JsValue> {
+        let genes = genes::Genes::from_share_string(code)
+            .map_err(|e| ApiError::new("invalid_genes", format!("Invalid gene code: {}", e)))?;
+        self.simulation.inject_genes(&genes, count as usize);
+        Ok(())
+    }
+
+    pub fn get_population(&self) -> u32 {
+        self.simulation.world().len()
+    }
+
     pub fn get_step(&self) -> u32 {
         self.simulation.step()
     }
 }
 
+impl WebSimulation {
+    fn step(&mut self) {
+        self.simulation.update();
+        let rendering = &self.config.rendering;
+        let capacity = rendering.population_series_length;
+        let step = self.simulation.step();
+        if capacity > 0 && step % rendering.population_sample_interval.max(1) == 0 {
+            let sample = stats::PopulationSample::from_world(
+                self.simulation.world(),
+                step,
+                &self.simulation.species(),
+            );
+            self.population_series.record(sample, capacity);
+        }
+    }
+}
+
+/// Config field defaults and ranges, gene ranges and movement types, as JSON
+#[wasm_bindgen]
+pub fn config_schema() -> String {
+    config::SimulationConfig::schema_json().to_string()
+}
+
+/// JSON config of a named preset (see `SimulationConfig::PRESETS`), or nothing if unknown
+#[wasm_bindgen]
+pub fn preset_config(name: &str) -> Option<String> {
+    config::SimulationConfig::preset(name).and_then(|config| serde_json::to_string(&config).ok())
+}
+
 #[wasm_bindgen]
 pub fn init_panic_hook() {
     console_error_panic_hook::set_once();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entity_count_matches_living_entities() {
+        let config_json = serde_json::to_string(&config::SimulationConfig::default()).unwrap();
+        let mut sim = WebSimulation::new(500.0, &config_json).unwrap();
+        for _ in 0..5 {
+            sim.update();
+            sim.update_entity_buffer();
+            assert_eq!(sim.entity_count(), sim.get_population());
+            assert_eq!(sim.buffer_len(), sim.entity_count() * sim.entity_stride());
+        }
+    }
+
+    #[test]
+    fn test_population_series_is_bounded() {
+        let mut config = config::SimulationConfig::default();
+        config.rendering.population_series_length = 4;
+        config.rendering.population_sample_interval = 1;
+        let config_json = serde_json::to_string(&config).unwrap();
+        let mut sim = WebSimulation::new(500.0, &config_json).unwrap();
+        for n in 1..=6 {
+            sim.update();
+            let samples = sim.population_series.samples();
+            assert_eq!(samples.len(), n.min(4));
+            let latest = samples.back().unwrap();
+            assert_eq!(latest.step, sim.get_step());
+            assert_eq!(latest.count, sim.get_population());
+            assert_eq!(
+                latest.species_counts.iter().sum::<usize>() as u32,
+                latest.count
+            );
+        }
+        assert_eq!(sim.population_series.samples()[0].step, 3);
+    }
+
+    #[test]
+    fn test_population_series_samples_every_interval() {
+        let mut config = config::SimulationConfig::default();
+        config.population.initial_entities = 20;
+        config.rendering.population_sample_interval = 3;
+        let config_json = serde_json::to_string(&config).unwrap();
+        let mut sim = WebSimulation::new(500.0, &config_json).unwrap();
+        for _ in 0..7 {
+            sim.update();
+        }
+        let steps: Vec<u32> = sim
+            .population_series
+            .samples()
+            .iter()
+            .map(|s| s.step)
+            .collect();
+        assert_eq!(steps, [3, 6]);
+    }
+
+    #[test]
+    fn test_time_scale_accumulates_fractional_steps() {
+        let mut config = config::SimulationConfig::default();
+        config.population.initial_entities = 20;
+        let config_json = serde_json::to_string(&config).unwrap();
+        let mut sim = WebSimulation::new(500.0, &config_json).unwrap();
+
+        sim.set_time_scale(0.5);
+        let steps: Vec<u32> = (0..6).map(|_| sim.update()).collect();
+        assert_eq!(steps, [0, 1, 0, 1, 0, 1]);
+        assert_eq!(sim.get_step(), 3);
+
+        sim.set_time_scale(2.0);
+        assert_eq!(sim.update(), 2);
+        assert_eq!(sim.get_step(), 5);
+
+        sim.set_time_scale(0.0);
+        assert_eq!(sim.update(), 0);
+        assert_eq!(sim.get_step(), 5);
+    }
+
+    #[test]
+    fn test_invalid_config_gives_descriptive_error() {
+        let mut config = config::SimulationConfig::default();
+        config.physics.max_velocity = 50.0;
+        let error = parse_config(&serde_json::to_string(&config).unwrap()).unwrap_err();
+        assert_eq!(error.kind, "invalid_config");
+        assert_eq!(error.field.as_deref(), Some("physics.max_velocity"));
+        assert!(error.message.contains("outside"), "{}", error.message);
+
+        let error = parse_config("{\"physics\": {\"max_velocity\": \"fast\"}}").unwrap_err();
+        assert_eq!(error.kind, "parse");
+        assert!(error.message.contains("invalid type"), "{}", error.message);
+
+        assert!(parse_config("{}").is_ok());
+    }
+
+    #[test]
+    fn test_buffer_layout_sets_stride_and_fields() {
+        let config_json = serde_json::to_string(&config::SimulationConfig::default()).unwrap();
+        let mut sim = WebSimulation::new(500.0, &config_json).unwrap();
+        sim.update();
+
+        sim.set_buffer_layout(0);
+        sim.update_entity_buffer();
+        assert_eq!(sim.entity_stride(), 8);
+        assert_eq!(sim.buffer_len(), sim.get_population() * 8);
+
+        sim.set_buffer_layout(LAYOUT_ENERGY);
+        sim.update_entity_buffer();
+        assert_eq!(sim.entity_stride(), 9);
+        assert_eq!(sim.buffer_len(), sim.get_population() * 9);
+
+        // Entity order varies between queries, so compare sorted (x, y, energy) triples
+        let sorted = |mut triples: Vec<[f32; 3]>| {
+            triples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            triples
+        };
+        let packed = sorted(
+            sim.entity_buffer
+                .chunks(9)
+                .map(|e| [e[2], e[3], e[8]])
+                .collect(),
+        );
+        let expected = sorted(
+            sim.simulation
+                .render_entities()
+                .iter()
+                .map(|e| [e.pos.x, e.pos.y, e.energy_fraction])
+                .collect(),
+        );
+        assert_eq!(packed, expected);
+    }
+}
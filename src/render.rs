@@ -0,0 +1,227 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Instance data for each entity (36 bytes each)
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct Instance {
+    prev_curr_pos: [f32; 4], // xy = prev_pos, zw = curr_pos
+    radius_color: [f32; 4],  // x = radius, yzw = color (rgb)
+    energy: f32,             // Fraction of max energy, in [0, 1]
+}
+
+/// Capacity of the instance buffer; entities beyond this are not drawn
+pub const MAX_INSTANCES: usize = 20000;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, Pod, Zeroable)]
+pub struct SimulationUniforms {
+    pub world_size: f32,
+    pub interpolation_factor: f32,
+    pub camera_zoom: f32,
+    pub camera_x: f32,
+    pub camera_y: f32,
+    pub highlight_x: f32, // World position of the followed entity
+    pub highlight_y: f32,
+    pub highlight_active: f32, // 1.0 while an entity is followed
+    pub low_energy_style: f32, // 0 = none, 1 = outline, 2 = energy bar
+    pub low_energy_threshold: f32,
+    pub _padding: [f32; 2],
+}
+
+/// Instances from an entity buffer laid out per `layout` (see `crate::layout_stride`):
+/// prev_x, prev_y, cur_x, cur_y, radius, r, g, b, then the energy fraction when packed
+pub fn instances_from_buffer(entity_data: &[f32], layout: u32) -> Vec<Instance> {
+    let stride = crate::layout_stride(layout);
+    entity_data
+        .chunks_exact(stride)
+        .take(MAX_INSTANCES)
+        .map(|chunk| Instance {
+            prev_curr_pos: [chunk[0], chunk[1], chunk[2], chunk[3]],
+            radius_color: [chunk[4], chunk[5], chunk[6], chunk[7]],
+            energy: if layout & crate::LAYOUT_ENERGY != 0 {
+                chunk[8]
+            } else {
+                1.0
+            },
+        })
+        .collect()
+}
+
+/// The instanced circle pipeline and its buffers, independent of what it draws into, so
+/// the browser surface and offscreen textures share one code path
+pub struct EntityPipeline {
+    render_pipeline: wgpu::RenderPipeline,
+    instance_buffer: wgpu::Buffer,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    num_instances: u32,
+}
+
+impl EntityPipeline {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Simulation Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[SimulationUniforms {
+                world_size: 1000.0, // Default, will be updated
+                camera_zoom: 1.0,
+                ..SimulationUniforms::default()
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Simulation Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Simulation Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        // Create shader
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+        });
+
+        // Create pipeline layout
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        // Create render pipeline with instanced rendering
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &[
+                        wgpu::VertexAttribute {
+                            offset: 0,
+                            shader_location: 0,
+                            format: wgpu::VertexFormat::Float32x4, // prev_curr_pos
+                        },
+                        wgpu::VertexAttribute {
+                            offset: 16,
+                            shader_location: 1,
+                            format: wgpu::VertexFormat::Float32x4, // radius_color
+                        },
+                        wgpu::VertexAttribute {
+                            offset: 32,
+                            shader_location: 2,
+                            format: wgpu::VertexFormat::Float32, // energy
+                        },
+                    ],
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        // Create instance buffer (pre-allocate for MAX_INSTANCES entities)
+        let initial_instances = vec![Instance::zeroed(); MAX_INSTANCES];
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: bytemuck::cast_slice(&initial_instances),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        Self {
+            render_pipeline,
+            instance_buffer,
+            uniform_buffer,
+            bind_group,
+            num_instances: 0,
+        }
+    }
+
+    /// Upload this frame's uniforms and entities
+    pub fn upload(
+        &mut self,
+        queue: &wgpu::Queue,
+        uniforms: &SimulationUniforms,
+        instances: &[Instance],
+    ) {
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[*uniforms]));
+        self.num_instances = instances.len() as u32;
+        if !instances.is_empty() {
+            queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(instances));
+        }
+    }
+
+    /// Clear `view` to black and draw the uploaded entities into it
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.0,
+                        g: 0.0,
+                        b: 0.0,
+                        a: 1.0,
+                    }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.num_instances);
+    }
+}
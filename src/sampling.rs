@@ -0,0 +1,56 @@
+use rand::prelude::*;
+use std::f32::consts::TAU;
+
+/// A point uniformly distributed in the disc of `radius` around the origin, as an (x, y)
+/// offset. Sampled in polar form, so it takes exactly two draws.
+pub fn random_in_disc(rng: &mut impl Rng, radius: f32) -> (f32, f32) {
+    let angle = rng.gen_range(0.0..TAU);
+    let distance = radius * rng.gen::<f32>().sqrt(); // Square root for uniform distribution
+    (distance * angle.cos(), distance * angle.sin())
+}
+
+/// A uniformly random unit vector
+pub fn random_direction(rng: &mut impl Rng) -> (f32, f32) {
+    let angle = rng.gen_range(0.0..TAU);
+    (angle.cos(), angle.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_random_in_disc_is_uniform_within_radius() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let radius = 10.0;
+        let samples: Vec<(f32, f32)> = (0..20_000)
+            .map(|_| random_in_disc(&mut rng, radius))
+            .collect();
+
+        assert!(samples
+            .iter()
+            .all(|(x, y)| (x * x + y * y).sqrt() <= radius + 1e-4));
+
+        let n = samples.len() as f32;
+        let mean_x = samples.iter().map(|(x, _)| x).sum::<f32>() / n;
+        let mean_y = samples.iter().map(|(_, y)| y).sum::<f32>() / n;
+        assert!(mean_x.abs() < 0.2 && mean_y.abs() < 0.2);
+
+        // Uniform in area: half the points fall inside radius / sqrt(2)
+        let inner = samples
+            .iter()
+            .filter(|(x, y)| x * x + y * y <= radius * radius / 2.0)
+            .count() as f32;
+        assert!((inner / n - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_random_direction_is_unit_length() {
+        let mut rng = StdRng::seed_from_u64(6);
+        for _ in 0..100 {
+            let (x, y) = random_direction(&mut rng);
+            assert!(((x * x + y * y).sqrt() - 1.0).abs() < 1e-5);
+        }
+    }
+}
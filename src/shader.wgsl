@@ -5,24 +5,31 @@ struct SimulationUniforms {
     camera_zoom: f32,
     camera_x: f32,
     camera_y: f32,
-    padding1: f32,
-    padding2: f32,
-    padding3: f32,
+    highlight_x: f32,
+    highlight_y: f32,
+    highlight_active: f32,
+    low_energy_style: f32, // 0 = none, 1 = outline, 2 = energy bar
+    low_energy_threshold: f32,
+    _padding0: f32,
+    _padding1: f32,
 };
 
 @group(0) @binding(0)
 var<uniform> uniforms: SimulationUniforms;
 
-// Instance data: prev_pos (xy), curr_pos (xy), radius, color (rgb)
+// Instance data: prev_pos (xy), curr_pos (xy), radius, color (rgb), energy fraction
 struct InstanceInput {
     @location(0) prev_curr_pos: vec4<f32>, // xy = prev_pos, zw = curr_pos
     @location(1) radius_color: vec4<f32>, // x = radius, yzw = color (rgb)
+    @location(2) energy: f32, // Fraction of max energy
 }
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) color: vec3<f32>,
     @location(1) uv: vec2<f32>,
+    @location(2) highlight: f32,
+    @location(3) energy: f32,
 }
 
 // Quad vertices (generated in shader)
@@ -72,6 +79,11 @@ fn vs_main(
     out.color = instance.radius_color.yzw;
     out.uv = quad_pos;  // -1 to 1 range
 
+    // Flag the followed entity so the fragment stage can ring it
+    let highlight_pos = vec2<f32>(uniforms.highlight_x, uniforms.highlight_y);
+    out.highlight = select(0.0, uniforms.highlight_active, distance(curr_pos, highlight_pos) < 0.01);
+    out.energy = instance.energy;
+
     return out;
 }
 
@@ -95,5 +107,29 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let white_glow = smoothstep(1.0, 0.0, dist * 0.4) * 0.2;
     let final_color_with_glow = final_color + vec3<f32>(white_glow);
 
-    return vec4<f32>(final_color_with_glow, alpha);
+    // Selection ring near the edge of the quad
+    let ring = smoothstep(0.08, 0.0, abs(dist - 0.9)) * in.highlight;
+
+    var color = final_color_with_glow + vec3<f32>(ring);
+    var out_alpha = max(alpha, ring);
+
+    // Mark entities close to starving
+    if (in.energy < uniforms.low_energy_threshold) {
+        let red = vec3<f32>(1.0, 0.15, 0.1);
+        if (uniforms.low_energy_style > 0.5 && uniforms.low_energy_style < 1.5) {
+            let outline = smoothstep(0.06, 0.0, abs(dist - 0.75));
+            color = mix(color, red, outline);
+            out_alpha = max(out_alpha, outline);
+        } else if (uniforms.low_energy_style > 1.5) {
+            // Bar below the body; the filled part shows the energy fraction
+            let in_bar = abs(in.uv.y - 0.8) < 0.06 && abs(in.uv.x) < 0.6;
+            if (in_bar) {
+                let filled = in.uv.x < -0.6 + 1.2 * in.energy;
+                color = select(vec3<f32>(0.25), red, filled);
+                out_alpha = 1.0;
+            }
+        }
+    }
+
+    return vec4<f32>(color, out_alpha);
 }
\ No newline at end of file
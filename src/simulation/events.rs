@@ -0,0 +1,109 @@
+use super::{EntityUpdate, Simulation};
+use crate::components::Uid;
+use hecs::Entity;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Something that happened to an entity during a step, identified by `Uid`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SimulationEvent {
+    pub step: u32,
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum EventKind {
+    Birth { child: u64, parent: u64 },
+    Death { entity: u64, cause: DeathCause },
+    Predation { predator: u64, prey: u64 },
+    Reseed { entity: u64 }, // Spawned to hold the population at `min_viable_population`
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DeathCause {
+    Starvation, // Energy at or below the death threshold, once any grace ran out
+    Eaten,
+    Crowding, // Random death driven by the population cap
+}
+
+impl Simulation {
+    /// Start or stop collecting events. Off by default, so runs that never read events
+    /// pay nothing; turning it off discards any not yet drained.
+    pub fn set_event_logging(&mut self, enabled: bool) {
+        self.events = enabled.then(|| self.events.take().unwrap_or_default());
+    }
+
+    /// Events collected since the last call, oldest first
+    pub fn drain_events(&mut self) -> Vec<SimulationEvent> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    pub(super) fn logging_events(&self) -> bool {
+        self.events.is_some()
+    }
+
+    /// Record an event between `a` and `b`, built by `kind` from their uids; dropped if
+    /// either has no `Uid`
+    pub(super) fn log_event(
+        &mut self,
+        a: Entity,
+        b: Entity,
+        kind: impl FnOnce(u64, u64) -> EventKind,
+    ) {
+        let Some(events) = &mut self.events else {
+            return;
+        };
+        let uid = |entity| self.world.get::<&Uid>(entity).ok().map(|uid| uid.0);
+        if let (Some(a), Some(b)) = (uid(a), uid(b)) {
+            events.push(SimulationEvent {
+                step: self.step,
+                kind: kind(a, b),
+            });
+        }
+    }
+
+    pub(super) fn log_death(&mut self, entity: Entity, cause: DeathCause) {
+        self.log_event(entity, entity, |entity, _| EventKind::Death {
+            entity,
+            cause,
+        });
+    }
+
+    /// Meals and deaths that `apply_updates_in_place` is about to carry out; an eaten
+    /// entity counts as eaten even if it would also have starved
+    pub(super) fn log_update_events(&mut self, updates: &[EntityUpdate]) {
+        if !self.logging_events() {
+            return;
+        }
+        let meals: Vec<(Entity, Entity)> = updates
+            .iter()
+            .flat_map(|update| {
+                let predator = update.entity;
+                update
+                    .eaten_entities
+                    .iter()
+                    .map(move |&(prey, _)| (predator, prey))
+            })
+            .collect();
+        for &(predator, prey) in &meals {
+            self.log_event(predator, prey, |predator, prey| EventKind::Predation {
+                predator,
+                prey,
+            });
+        }
+        for &(_, prey) in &meals {
+            self.log_death(prey, DeathCause::Eaten);
+        }
+        let eaten: HashSet<Entity> = meals.iter().map(|&(_, prey)| prey).collect();
+        for update in updates {
+            if !update.survives(&self.config) && !eaten.contains(&update.entity) {
+                let cause = if update.crowded {
+                    DeathCause::Crowding
+                } else {
+                    DeathCause::Starvation
+                };
+                self.log_death(update.entity, cause);
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use super::Simulation;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// One entity in one frame of a position export
+#[derive(Debug, Serialize)]
+struct PositionRecord {
+    frame: u32,
+    step: u32,
+    x: f32,
+    y: f32,
+    radius: f32,
+    r: f32,
+    g: f32,
+    b: f32,
+}
+
+/// Streams entity positions, sizes and colours to a file as JSON lines, one line per
+/// entity per frame, for plotting or animating with external tools
+pub struct PositionExporter {
+    writer: BufWriter<File>,
+    frames: u32,
+}
+
+impl PositionExporter {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            frames: 0,
+        })
+    }
+
+    /// Append the simulation's current state as the next frame, entities in id order
+    pub fn write_frame(&mut self, sim: &Simulation) -> Result<(), Box<dyn std::error::Error>> {
+        for entity in sim.render_entities_sorted() {
+            let record = PositionRecord {
+                frame: self.frames,
+                step: sim.step(),
+                x: entity.pos.x,
+                y: entity.pos.y,
+                radius: entity.radius,
+                r: entity.color.r,
+                g: entity.color.g,
+                b: entity.color.b,
+            };
+            serde_json::to_writer(&mut self.writer, &record)?;
+            self.writer.write_all(b"\n")?;
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Frames written so far
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    pub fn finish(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Simulation {
+    /// Write the current state as a single-frame position export
+    pub fn export_positions<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut exporter = PositionExporter::create(path)?;
+        exporter.write_frame(self)?;
+        exporter.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+    use serde_json::Value;
+    use std::fs;
+
+    #[test]
+    fn test_export_has_one_record_per_entity_per_frame() {
+        let mut sim = Simulation::new_seeded(200.0, SimulationConfig::default(), 11);
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+
+        let mut exporter = PositionExporter::create(temp_file.path()).unwrap();
+        let mut populations = Vec::new();
+        for _ in 0..3 {
+            sim.update();
+            populations.push(sim.world().len() as usize);
+            exporter.write_frame(&sim).unwrap();
+        }
+        assert_eq!(exporter.frames(), 3);
+        exporter.finish().unwrap();
+
+        let records: Vec<Value> = fs::read_to_string(temp_file.path())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        for (frame, population) in populations.iter().enumerate() {
+            let in_frame = records
+                .iter()
+                .filter(|record| record["frame"] == frame)
+                .count();
+            assert_eq!(in_frame, *population);
+        }
+        assert_eq!(records.len(), populations.iter().sum::<usize>());
+        assert!(records[0]["radius"].as_f64().unwrap() > 0.0);
+
+        sim.export_positions(temp_file.path()).unwrap();
+        let lines = fs::read_to_string(temp_file.path())
+            .unwrap()
+            .lines()
+            .count();
+        assert_eq!(lines, sim.world().len() as usize);
+    }
+}
@@ -0,0 +1,47 @@
+use super::{replay, Simulation};
+use crate::components::Uid;
+use crate::genes::{Genes, Phenotype};
+use hecs::Entity;
+
+impl Simulation {
+    /// Set a living entity's named trait, fitted to its bounds, e.g. to see how a faster
+    /// creature fares. Returns the trait's new value, or `None` for an unknown entity or
+    /// trait. Only the expressed genes change; a diploid genotype keeps its alleles.
+    pub fn set_gene(&mut self, entity: Entity, field: &str, value: f32) -> Option<f32> {
+        let index = Genes::trait_index(field)?;
+        let uid = self.world.get::<&Uid>(entity).ok()?.0;
+        let genes = {
+            let mut genes = self.world.get::<&mut Genes>(entity).ok()?;
+            genes.set_trait(field, value);
+            genes.clone()
+        };
+        let value = genes.traits()[index];
+        // Derived components would otherwise keep describing the old genes
+        let _ = self
+            .world
+            .insert(entity, (Phenotype::of(&genes), genes.get_color()));
+        self.forget_species();
+        self.recording.events.push(replay::ReplayEvent::GeneEdited {
+            step: self.step,
+            uid,
+            field: field.to_string(),
+            value,
+        });
+        Some(value)
+    }
+
+    /// Shift a living entity's named trait by `delta`, fitted as in `set_gene`
+    pub fn mutate_entity(&mut self, entity: Entity, field: &str, delta: f32) -> Option<f32> {
+        let index = Genes::trait_index(field)?;
+        let current = self.world.get::<&Genes>(entity).ok()?.traits()[index];
+        self.set_gene(entity, field, current + delta)
+    }
+
+    pub(super) fn entity_with_uid(&self, uid: u64) -> Option<Entity> {
+        self.world
+            .query::<&Uid>()
+            .iter()
+            .find(|(_, entity_uid)| entity_uid.0 == uid)
+            .map(|(entity, _)| entity)
+    }
+}
@@ -0,0 +1,273 @@
+use super::{EntityUpdate, Simulation};
+use crate::config::{FitnessConfig, SimulationConfig};
+use crate::genes::{DiploidGenes, Genes};
+use hecs::{Entity, World};
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use std::collections::HashMap;
+
+/// Fixed-size generational GA mode. Entities live out a generation without births,
+/// accumulating fitness per `FitnessConfig`; at the end of the generation the population
+/// is resampled by fitness-proportional (roulette) selection and mutated.
+#[derive(Default)]
+pub struct GenerationalScheduler {
+    fitness: HashMap<Entity, FitnessRecord>,
+    steps_in_generation: u32,
+}
+
+/// What one entity achieved over the current generation
+#[derive(Debug, Clone)]
+struct FitnessRecord {
+    genes: Genes,
+    genotype: Option<DiploidGenes>, // Crossed with a mate's at the generation boundary
+    energy: f32,                    // Energy held, summed over the steps lived
+    lifespan: u32,                  // Steps lived
+    offspring: u32,                 // Steps it was ready to reproduce
+}
+
+impl FitnessRecord {
+    fn new(genes: Genes, genotype: Option<DiploidGenes>) -> Self {
+        Self {
+            genes,
+            genotype,
+            energy: 0.0,
+            lifespan: 0,
+            offspring: 0,
+        }
+    }
+
+    fn fitness(&self, weights: &FitnessConfig) -> f32 {
+        self.energy * weights.energy_weight
+            + self.lifespan as f32 * weights.lifespan_weight
+            + self.offspring as f32 * weights.offspring_weight
+    }
+}
+
+impl GenerationalScheduler {
+    /// Replaces `Simulation::apply_entity_updates` while generational mode is enabled.
+    /// Meals must already be settled.
+    pub fn apply(
+        &mut self,
+        world: &mut World,
+        updates: Vec<EntityUpdate>,
+        world_size: f32,
+        config: &SimulationConfig,
+        rng: &mut impl Rng,
+        next_uid: &mut u64,
+    ) {
+        self.accumulate(world, &updates);
+        Simulation::apply_updates_in_place(world, updates, config);
+
+        self.steps_in_generation += 1;
+        if self.steps_in_generation >= config.genetics.generation_steps.max(1) {
+            self.next_generation(world, world_size, config, rng, next_uid);
+        }
+    }
+
+    fn accumulate(&mut self, world: &World, updates: &[EntityUpdate]) {
+        for update in updates {
+            let record = self.fitness.entry(update.entity).or_insert_with(|| {
+                let genotype = world.get::<&DiploidGenes>(update.entity).ok();
+                FitnessRecord::new(update.genes.clone(), genotype.map(|g| (*g).clone()))
+            });
+            record.energy += update.energy.current.max(0.0);
+            record.lifespan += 1;
+            record.offspring += update.ready_to_reproduce as u32;
+        }
+    }
+
+    /// Roulette-wheel selection of `count` parents weighted by fitness
+    pub fn select_parents(
+        &self,
+        rng: &mut impl Rng,
+        count: usize,
+        weights: &FitnessConfig,
+    ) -> Vec<Genes> {
+        self.select(rng, count, weights)
+            .into_iter()
+            .map(|record| record.genes.clone())
+            .collect()
+    }
+
+    fn select(
+        &self,
+        rng: &mut impl Rng,
+        count: usize,
+        weights: &FitnessConfig,
+    ) -> Vec<&FitnessRecord> {
+        // Sorted by entity so the draw doesn't depend on hash map order
+        let mut candidates: Vec<(&Entity, &FitnessRecord)> = self.fitness.iter().collect();
+        candidates.sort_unstable_by_key(|(entity, _)| entity.to_bits());
+        let candidates: Vec<&FitnessRecord> = candidates.into_iter().map(|(_, c)| c).collect();
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+
+        match WeightedIndex::new(candidates.iter().map(|record| record.fitness(weights))) {
+            Ok(wheel) => (0..count).map(|_| candidates[wheel.sample(rng)]).collect(),
+            // Nobody gained any fitness - fall back to uniform selection
+            Err(_) => (0..count)
+                .map(|_| candidates[rng.gen_range(0..candidates.len())])
+                .collect(),
+        }
+    }
+
+    fn next_generation(
+        &mut self,
+        world: &mut World,
+        world_size: f32,
+        config: &SimulationConfig,
+        rng: &mut impl Rng,
+        next_uid: &mut u64,
+    ) {
+        let size =
+            (config.population.initial_entities as f32 * config.population.entity_scale) as usize;
+        let spawn_radius = world_size * config.population.spawn_radius_factor;
+        let parents = self.select(rng, size, &config.fitness);
+        let positions = super::spawn::spawn_positions(
+            &config.population.spawn_pattern,
+            parents.len(),
+            spawn_radius,
+            rng,
+        );
+        // Diploid parents each get a mate from a second roulette draw
+        let mates = if config.genetics.diploid {
+            self.select(rng, parents.len(), &config.fitness)
+        } else {
+            Vec::new()
+        };
+
+        world.clear();
+        for (i, (parent, pos)) in parents.iter().zip(positions).enumerate() {
+            let mate = mates.get(i).and_then(|mate| mate.genotype.as_ref());
+            match (&parent.genotype, mate) {
+                (Some(genotype), Some(mate)) => {
+                    let child = genotype.cross(mate, rng, &config.genetics);
+                    let genes = child.express(config.genetics.dominance);
+                    let entity = Simulation::spawn_entity(world, rng, genes, pos, config, next_uid);
+                    let _ = world.insert_one(entity, child);
+                }
+                _ => {
+                    let genes = parent.genes.mutate_with(rng, &config.genetics);
+                    Simulation::spawn_entity(world, rng, genes, pos, config, next_uid);
+                }
+            }
+        }
+
+        self.fitness.clear();
+        self.steps_in_generation = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Uid;
+
+    fn record(genes: Genes, energy: f32, offspring: u32) -> FitnessRecord {
+        FitnessRecord {
+            energy,
+            offspring,
+            ..FitnessRecord::new(genes, None)
+        }
+    }
+
+    #[test]
+    fn test_diploid_genotypes_survive_a_generation_boundary() {
+        let mut config = SimulationConfig::default();
+        config.population.initial_entities = 60;
+        config.genetics.diploid = true;
+        config.genetics.generational = true;
+        config.genetics.generation_steps = 3;
+        let mut sim = Simulation::new_seeded(400.0, config, 15);
+        for _ in 0..4 {
+            sim.update();
+        }
+
+        // Every entity belongs to the new generation, spawned after the 30 founders
+        assert!(!sim.world.is_empty());
+        let founders = sim
+            .world
+            .query::<&Uid>()
+            .iter()
+            .filter(|(_, uid)| uid.0 < 30)
+            .count();
+        assert_eq!(founders, 0);
+        let diploid = sim.world.query::<&DiploidGenes>().iter().count();
+        assert_eq!(diploid, sim.world.len() as usize);
+    }
+
+    #[test]
+    fn test_roulette_favors_higher_energy_genotypes() {
+        let mut rng = thread_rng();
+        let mut world = World::new();
+        let mut scheduler = GenerationalScheduler::default();
+
+        let mut fit = Genes::new_random(&mut rng);
+        fit.movement.speed = 2.0;
+        let mut unfit = Genes::new_random(&mut rng);
+        unfit.movement.speed = 1.0;
+        scheduler
+            .fitness
+            .insert(world.spawn(()), record(fit, 90.0, 0));
+        scheduler
+            .fitness
+            .insert(world.spawn(()), record(unfit, 10.0, 0));
+
+        let parents = scheduler.select_parents(&mut rng, 2000, &FitnessConfig::default());
+        let fit_share = parents.iter().filter(|genes| genes.speed() == 2.0).count() as f32 / 2000.0;
+        assert!((fit_share - 0.9).abs() < 0.05, "fit share: {}", fit_share);
+    }
+
+    #[test]
+    fn test_uniform_selection_without_fitness() {
+        let mut rng = thread_rng();
+        let mut world = World::new();
+        let mut scheduler = GenerationalScheduler::default();
+        scheduler
+            .fitness
+            .insert(world.spawn(()), record(Genes::new_random(&mut rng), 0.0, 0));
+
+        let parents = scheduler.select_parents(&mut rng, 5, &FitnessConfig::default());
+        assert_eq!(parents.len(), 5);
+    }
+
+    #[test]
+    fn test_offspring_fitness_selects_different_genotypes_than_energy() {
+        let mut rng = thread_rng();
+        let mut world = World::new();
+        let mut scheduler = GenerationalScheduler::default();
+
+        // A hoarder keeps its energy; a breeder is often ready to reproduce but runs low
+        let mut hoarder = Genes::new_random(&mut rng);
+        hoarder.movement.speed = 1.0;
+        let mut breeder = Genes::new_random(&mut rng);
+        breeder.movement.speed = 2.0;
+        scheduler
+            .fitness
+            .insert(world.spawn(()), record(hoarder, 90.0, 1));
+        scheduler
+            .fitness
+            .insert(world.spawn(()), record(breeder, 10.0, 9));
+
+        let breeder_share = |weights: &FitnessConfig, rng: &mut ThreadRng| {
+            let parents = scheduler.select_parents(rng, 2000, weights);
+            parents.iter().filter(|genes| genes.speed() == 2.0).count() as f32 / 2000.0
+        };
+        let by_energy = breeder_share(&FitnessConfig::default(), &mut rng);
+        let by_offspring = breeder_share(
+            &FitnessConfig {
+                energy_weight: 0.0,
+                offspring_weight: 1.0,
+                ..FitnessConfig::default()
+            },
+            &mut rng,
+        );
+        assert!((by_energy - 0.1).abs() < 0.05, "by energy: {}", by_energy);
+        assert!(
+            (by_offspring - 0.9).abs() < 0.05,
+            "by offspring: {}",
+            by_offspring
+        );
+    }
+}
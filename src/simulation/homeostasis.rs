@@ -0,0 +1,57 @@
+use crate::config::PopulationConfig;
+
+/// Proportional controller steering the population toward `population.target_population`.
+/// Births and deaths already respond to population pressure (the fraction of the cap in
+/// use), so the controller adds its correction to that pressure: above the target births
+/// slow and density deaths rise, below it pressure falls away.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Homeostasis {
+    correction: f32,
+}
+
+impl Homeostasis {
+    /// Correction for `population` entities; zero while no target is set
+    pub fn new(population: u32, config: &PopulationConfig) -> Self {
+        let target = config.target_population as f32 * config.entity_scale;
+        if target <= 0.0 {
+            return Self::default();
+        }
+        let error = (population as f32 - target) / target;
+        Self {
+            correction: (config.homeostasis_gain * error).clamp(-1.0, 1.0),
+        }
+    }
+
+    /// Gain times the relative error, positive above the target and negative below it.
+    /// Clamped to [-1, 1] so a high gain can't kill off an overshooting population at once.
+    pub fn correction(&self) -> f32 {
+        self.correction
+    }
+
+    /// `cap_fraction` with the correction applied, never negative
+    pub fn pressure(&self, cap_fraction: f32) -> f32 {
+        (cap_fraction + self.correction).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_correction_follows_error_from_target() {
+        let mut config = PopulationConfig {
+            entity_scale: 1.0,
+            target_population: 200,
+            homeostasis_gain: 0.5,
+            ..PopulationConfig::default()
+        };
+        assert_eq!(Homeostasis::new(200, &config).correction(), 0.0);
+        assert_eq!(Homeostasis::new(400, &config).correction(), 0.5);
+        assert_eq!(Homeostasis::new(100, &config).pressure(0.1), 0.0);
+        assert_eq!(Homeostasis::new(300, &config).pressure(0.1), 0.35);
+
+        config.target_population = 0;
+        assert_eq!(Homeostasis::new(400, &config).pressure(0.1), 0.1);
+    }
+}
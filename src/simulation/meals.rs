@@ -0,0 +1,162 @@
+use super::{EntityUpdate, Simulation};
+use crate::components::Starving;
+use crate::genes::Genes;
+use hecs::Entity;
+use rand::rngs::StdRng;
+use std::collections::{HashMap, HashSet};
+
+impl Simulation {
+    /// Whether crowding kills the entity this step, with a chance rising with population
+    /// pressure. Its energy is zeroed; no starvation grace, gift or meal can save it.
+    pub(super) fn crowding_death(
+        &self,
+        cap_fraction: f32,
+        energy: &mut f32,
+        rng: &mut StdRng,
+    ) -> bool {
+        let crowded = self
+            .reproduction_system
+            .check_death(cap_fraction, &self.config, rng);
+        if crowded {
+            *energy = 0.0;
+        }
+        crowded
+    }
+
+    /// Add each energy gift to its recipient's update, up to the recipient's max energy,
+    /// so a rescued entity survives the step. A gift to kin killed by crowding is lost.
+    pub(super) fn deliver_gifts(updates: &mut [EntityUpdate]) {
+        let gifts: Vec<(Entity, f32)> = updates.iter().filter_map(|update| update.gift).collect();
+        if gifts.is_empty() {
+            return;
+        }
+        let slots: HashMap<Entity, usize> = updates
+            .iter()
+            .enumerate()
+            .map(|(slot, update)| (update.entity, slot))
+            .collect();
+        for (recipient, amount) in gifts {
+            if let Some(&slot) = slots.get(&recipient) {
+                let update = &mut updates[slot];
+                if update.crowded {
+                    continue;
+                }
+                let max = update.energy.max;
+                update.energy.current = (update.energy.current + amount).min(max);
+                update.birth_energy = (update.birth_energy + amount).min(max);
+            }
+        }
+    }
+
+    /// Resolve contested prey and log the meals that stand in the food web
+    pub(super) fn settle_meals(&mut self, updates: &mut [EntityUpdate]) {
+        let forgone = if self.config.physics.symmetric_interactions {
+            Self::resolve_meals_in_order(updates)
+        } else {
+            Self::resolve_meal_conflicts(updates)
+        };
+        for (update, lost) in updates.iter_mut().zip(forgone) {
+            if lost != 0.0 {
+                self.forgo_meals(update, lost);
+            }
+        }
+        for update in updates.iter() {
+            for &(prey, _) in &update.eaten_entities {
+                if let Ok(prey_genes) = self.world.get::<&Genes>(prey) {
+                    self.food_web.record(self.step, &update.genes, &prey_genes);
+                }
+            }
+        }
+        self.food_web.prune(self.step);
+    }
+
+    /// Take back the energy of meals lost in settlement and redo the decisions it fed:
+    /// starvation is counted from what the entity is really left with, and a birth it can
+    /// no longer afford is called off, undoing what the parent paid for it
+    fn forgo_meals(&self, update: &mut EntityUpdate, lost: f32) {
+        if update.crowded {
+            return; // Killed by crowding whatever it ate
+        }
+        let energy = update.birth_energy - lost;
+        update.starving_steps = if self.config.energy.is_alive(energy) {
+            0
+        } else {
+            let starving = self.world.get::<&Starving>(update.entity);
+            starving
+                .map_or(0, |starving| starving.steps)
+                .saturating_add(1)
+        };
+        let affordable = update.starving_steps == 0
+            && energy > update.energy.max * self.config.reproduction.reproduction_energy_threshold;
+        update.ready_to_reproduce &= affordable;
+        update.should_reproduce &= affordable;
+        update.birth_energy = energy;
+        if update.should_reproduce {
+            (update.energy.current, update.inherited_energy) = self
+                .reproduction_system
+                .debit_parent(energy, update.energy.max, &update.genes, &self.config);
+        } else {
+            update.energy.current = energy;
+            update.inherited_energy = None;
+            update.child_genotype = None;
+            update.child_pedigree = None;
+        }
+    }
+
+    /// Predators are processed in parallel against the same snapshot, so several can claim
+    /// one prey. Each prey goes to the claimant with the lowest entity id; the others
+    /// lose the meal. Returns the energy each update lost with its dropped meals.
+    fn resolve_meal_conflicts(updates: &mut [EntityUpdate]) -> Vec<f32> {
+        let mut winners: HashMap<Entity, Entity> = HashMap::new();
+        for update in updates.iter() {
+            for &(prey, _) in &update.eaten_entities {
+                winners
+                    .entry(prey)
+                    .and_modify(|winner| {
+                        if update.entity.to_bits() < winner.to_bits() {
+                            *winner = update.entity;
+                        }
+                    })
+                    .or_insert(update.entity);
+            }
+        }
+
+        updates
+            .iter_mut()
+            .map(|update| {
+                let predator = update.entity;
+                let mut lost = 0.0;
+                update.eaten_entities.retain(|&(prey, gain)| {
+                    let won = winners.get(&prey) == Some(&predator);
+                    if !won {
+                        lost += gain;
+                    }
+                    won
+                });
+                lost
+            })
+            .collect()
+    }
+
+    /// Replay the meals claimed against the snapshot one predator at a time, in entity id
+    /// order: a predator eaten earlier in the replay eats nothing, and each prey goes to the
+    /// first claimant still alive. Returns the energy each update lost with its dropped meals.
+    fn resolve_meals_in_order(updates: &mut [EntityUpdate]) -> Vec<f32> {
+        let mut order: Vec<usize> = (0..updates.len()).collect();
+        order.sort_by_key(|&i| updates[i].entity.to_bits());
+        let mut eaten: HashSet<Entity> = HashSet::new();
+        let mut forgone = vec![0.0; updates.len()];
+        for i in order {
+            let update = &mut updates[i];
+            let alive = !eaten.contains(&update.entity);
+            update.eaten_entities.retain(|&(prey, gain)| {
+                let stands = alive && eaten.insert(prey);
+                if !stands {
+                    forgone[i] += gain;
+                }
+                stands
+            });
+        }
+        forgone
+    }
+}
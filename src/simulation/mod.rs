@@ -1,16 +1,57 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::too_many_arguments)]
 
-use crate::components::{Color, Energy, Position, Size, Velocity};
-use crate::config::SimulationConfig;
-use crate::genes::Genes;
-use crate::spatial_grid::SpatialGrid;
-use crate::stats::SimulationStats;
-use crate::systems::{EnergySystem, InteractionSystem, MovementSystem, ReproductionSystem};
+use crate::components::{
+    Age, Color, Energy, MovementType, Position, ReproCooldown, Size, Starving, Uid, Velocity,
+};
+use crate::config::{ColorMode, SimulationConfig, SpawnPattern};
+use crate::genes::{DiploidGenes, Genes, Pedigree, Phenotype};
+use crate::snapshot::EntitySnapshot;
+use crate::spatial_grid::{CellOccupancy, GridCell, SpatialGrid};
+use crate::stats::{detect_species, FoodWebLog, SimulationStats, Species, TrophicLevels};
+use crate::systems::{
+    EnergySystem, InteractionSystem, MovementSystem, ReproductionSystem, SocialSystem,
+};
 use hecs::*;
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub mod events;
+pub mod export;
+mod gene_edit;
+mod generational;
+mod homeostasis;
+mod meals;
+mod offspring;
+mod profiler;
+pub mod replay;
+mod reseed;
+mod season;
+mod spawn;
+pub mod step_log;
+pub mod termination;
+mod timestep;
+mod trails;
+pub use generational::GenerationalScheduler;
+pub use homeostasis::Homeostasis;
+use profiler::Phase;
+pub use season::Season;
+pub use timestep::{interpolate, FixedTimestep};
+
+/// Steps between metric logs unless changed with `set_log_interval`
+const DEFAULT_LOG_INTERVAL: u32 = 60;
+
+/// Receives the step number and stats each time metrics are logged
+pub type MetricsSink = Box<dyn FnMut(u32, &SimulationStats) + Send + Sync>;
+
+/// Independent random streams drawn by each entity within a step
+const BEHAVIOR_STREAM: u64 = 0;
+const OFFSPRING_STREAM: u64 = 1;
 
 // Simulation state
 pub struct EntityUpdate {
@@ -19,11 +60,39 @@ pub struct EntityUpdate {
     pub energy: Energy,
     pub size: Size,
     pub genes: Genes,
-    pub color: Color,
     pub velocity: Velocity,
-    pub movement_style: crate::components::MovementStyle,
     pub should_reproduce: bool,
-    pub eaten_entity: Option<Entity>,
+    pub ready_to_reproduce: bool, // Met the conditions to reproduce, even with births held back
+    pub eaten_entities: Vec<(Entity, f32)>, // Prey and the net energy each meal gave
+    pub child_genotype: Option<DiploidGenes>,
+    pub child_pedigree: Option<Pedigree>, // Set with `child_genotype` when both parents have a `Uid`
+    pub repro_cooldown: u32,              // Steps left before the entity may reproduce again
+    pub inherited_energy: Option<f32>, // Energy the parent handed its child, in `ChildEnergyMode::Inherit`
+    pub birth_energy: f32, // Energy before paying for any birth, which reproduction was decided on
+    pub starving_steps: u32, // Steps in a row at or below the death threshold, this one included
+    pub crowded: bool,     // Killed by crowding, which no starvation grace or gift can save
+    pub gift: Option<(Entity, f32)>, // Kin this entity shared energy with and the amount, delivered by `deliver_gifts`
+}
+
+impl EntityUpdate {
+    /// Whether the entity lives on after this step, allowing for starvation grace
+    fn survives(&self, config: &SimulationConfig) -> bool {
+        !self.crowded
+            && config
+                .energy
+                .survives(self.energy.current, self.starving_steps)
+    }
+}
+
+/// One entity as the renderer sees it
+pub struct RenderEntity {
+    pub entity: Entity,
+    pub prev_pos: Position, // Position before the last step, for interpolation
+    pub pos: Position,
+    pub radius: f32,
+    pub color: Color,         // Per `rendering.color_mode`
+    pub energy_fraction: f32, // Current energy over max, in [0, 1]
+    pub velocity: Velocity,
 }
 
 pub struct Simulation {
@@ -31,14 +100,31 @@ pub struct Simulation {
     world_size: f32,
     step: u32,
     grid: SpatialGrid,
-    previous_positions: HashMap<Entity, Position>, // For smooth interpolation
+    grid_stale: AtomicBool, // The world changed since the grid was last built
+    snapshot: EntitySnapshot, // Contiguous copy of hot components, read by neighbour loops
+    previous_positions: HashMap<Entity, VecDeque<Position>>, // Recent positions, newest (last step's, for interpolation) last
     config: SimulationConfig,
+    seed: u64, // Seeds every random draw, so a run is reproducible from its seed and inputs
+    rng: StdRng, // Sequential draws: initial spawn, injections and generational resampling
+    recording: replay::Replay, // Starting state and external inputs, for `replay`
+    seasonal_config: SimulationConfig, // `config` with this step's seasonal modulation applied
+    generational: GenerationalScheduler,
+    profiler: profiler::PhaseProfiler,
+    log_interval: u32, // Steps between metric logs; 0 disables logging
+    metrics_sink: Option<MetricsSink>, // Where metric logs go; stdout when unset
+    food_web: FoodWebLog, // Recent meals, for trophic levels
+    next_uid: u64,     // `Uid` of the next spawned entity
+    step_log: Option<step_log::StepLogWriter>, // Where per-step records stream, if anywhere
+    over_budget: bool, // The last step overran `timing.step_budget_ms`
+    events: Option<Vec<events::SimulationEvent>>, // Collected events, while event logging is on
+    species: Mutex<Option<Vec<Species>>>, // Detected since the world last changed, see `species`
 
     // System instances
     movement_system: MovementSystem,
     interaction_system: InteractionSystem,
     energy_system: EnergySystem,
     reproduction_system: ReproductionSystem,
+    social_system: SocialSystem,
 }
 
 struct ProcessEntityParams<'a> {
@@ -47,9 +133,9 @@ struct ProcessEntityParams<'a> {
     energy: &'a Energy,
     size: &'a Size,
     genes: &'a Genes,
-    color: &'a Color,
+    phenotype: &'a Phenotype,
     velocity: &'a Velocity,
-    movement_style: &'a crate::components::MovementStyle,
+    starving_steps: u32,
 }
 
 impl Simulation {
@@ -59,99 +145,300 @@ impl Simulation {
     }
 
     pub fn new_with_config(world_size: f32, config: SimulationConfig) -> Self {
+        Self::new_seeded(world_size, config, thread_rng().gen())
+    }
+
+    /// A simulation whose every random draw derives from `seed`. Two simulations with the
+    /// same seed, config and external inputs evolve identically.
+    pub fn new_seeded(world_size: f32, config: SimulationConfig, seed: u64) -> Self {
         let mut world = World::new();
-        let mut rng = thread_rng();
-        let grid = SpatialGrid::new(config.physics.grid_cell_size);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut grid = SpatialGrid::new(config.physics.grid_cell_size);
+        grid.set_cell_capacity(config.physics.grid_cell_capacity);
 
-        Self::spawn_initial_entities(&mut world, &mut rng, world_size, &config);
+        let mut next_uid = 0;
+        Self::spawn_initial_entities(&mut world, &mut rng, world_size, &config, &mut next_uid);
 
         Self {
             world,
             world_size,
             step: 0,
             grid,
+            grid_stale: AtomicBool::new(true),
+            snapshot: EntitySnapshot::default(),
             previous_positions: HashMap::new(),
+            seasonal_config: config.clone(),
+            recording: replay::Replay {
+                world_size,
+                seed,
+                config: config.clone(),
+                events: Vec::new(),
+                steps: 0,
+            },
             config,
+            seed,
+            rng,
+            generational: GenerationalScheduler::default(),
+            profiler: profiler::PhaseProfiler::default(),
+            log_interval: DEFAULT_LOG_INTERVAL,
+            metrics_sink: None,
+            step_log: None,
+            over_budget: false,
+            events: None,
+            species: Mutex::new(None),
+            food_web: FoodWebLog::default(),
+            next_uid,
             movement_system: MovementSystem,
             interaction_system: InteractionSystem,
             energy_system: EnergySystem,
             reproduction_system: ReproductionSystem,
+            social_system: SocialSystem,
         }
     }
 
     fn spawn_initial_entities(
         world: &mut World,
-        rng: &mut ThreadRng,
+        rng: &mut impl Rng,
         world_size: f32,
         config: &SimulationConfig,
+        next_uid: &mut u64,
     ) {
         let total_entities =
             (config.population.initial_entities as f32 * config.population.entity_scale) as usize;
         let spawn_radius = world_size * config.population.spawn_radius_factor;
+        let positions = spawn::spawn_positions(
+            &config.population.spawn_pattern,
+            total_entities,
+            spawn_radius,
+            rng,
+        );
 
-        for _ in 0..total_entities {
-            // Use perfectly uniform distribution in a circle
-            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
-            let distance = spawn_radius * rng.gen::<f32>().sqrt(); // Square root for uniform distribution
-            let x = distance * angle.cos();
-            let y = distance * angle.sin();
-
-            let genes = Genes::new_random(rng);
-            let energy = rng.gen_range(15.0..75.0);
-            let color = genes.get_color();
-            let radius = (energy / 15.0 * genes.size_factor()).clamp(
-                config.physics.min_entity_radius,
-                config.physics.max_entity_radius,
-            );
+        for pos in positions {
+            Self::spawn_random_entity(world, rng, pos, config, next_uid);
+        }
+    }
 
-            world.spawn((
-                Position { x, y },
-                Energy {
-                    current: energy,
-                    max: energy * 1.3,
-                },
-                Size { radius },
-                genes.clone(),
-                color,
-                Velocity { x: 0.0, y: 0.0 },
-                genes.behavior.movement_style.clone(),
-            ));
+    /// Spawn one entity with random genes, as in the initial population: movement types
+    /// follow `initial_movement_type_weights` and locked genes keep their values
+    fn spawn_random_entity(
+        world: &mut World,
+        rng: &mut impl Rng,
+        pos: Position,
+        config: &SimulationConfig,
+        next_uid: &mut u64,
+    ) -> Entity {
+        let locked = &config.genetics.locked_genes;
+        let mut genotype = config
+            .genetics
+            .diploid
+            .then(|| DiploidGenes::new_random(rng));
+        let style = MovementType::weighted(rng, &config.population.initial_movement_type_weights);
+        let genes = match &mut genotype {
+            // Allele `a` carries the expressed movement type
+            Some(genotype) => {
+                genotype.a.behavior.movement_style.style = style;
+                genotype.a.lock_traits(locked);
+                genotype.b.lock_traits(locked);
+                genotype.express(config.genetics.dominance)
+            }
+            None => {
+                let mut genes = Genes::new_random(rng);
+                genes.behavior.movement_style.style = style;
+                genes.lock_traits(locked);
+                genes
+            }
+        };
+
+        let entity = Self::spawn_entity(world, rng, genes, pos, config, next_uid);
+        if let Some(genotype) = genotype {
+            let _ = world.insert_one(entity, genotype);
         }
+        entity
+    }
+
+    /// Spawn one entity with the given genes and a random starting energy
+    fn spawn_entity(
+        world: &mut World,
+        rng: &mut impl Rng,
+        genes: Genes,
+        pos: Position,
+        config: &SimulationConfig,
+        next_uid: &mut u64,
+    ) -> Entity {
+        let energy = config.energy.random_initial_energy(rng);
+        let color = genes.get_color();
+        let radius = (energy / 15.0 * genes.size_factor()).clamp(
+            config.physics.min_entity_radius,
+            config.physics.max_entity_radius,
+        );
+
+        world.spawn((
+            pos,
+            Energy {
+                current: energy,
+                max: energy * config.energy.max_energy_ratio,
+            },
+            Size { radius },
+            Phenotype::of(&genes),
+            genes.clone(),
+            color,
+            Velocity { x: 0.0, y: 0.0 },
+            genes.behavior.movement_style.clone(),
+            Age::default(),
+            Uid::take(next_uid),
+        ))
     }
 
     pub fn update(&mut self) {
         self.step += 1;
+        self.forget_species();
         self.update_simulation();
+        self.check_step_budget();
+        self.record_step();
 
-        if self.step % 60 == 0 {
+        let skip_stats = self.over_budget && self.config.timing.skip_stats_over_budget;
+        if self.log_interval > 0 && self.step % self.log_interval == 0 && !skip_stats {
             self.log_simulation_metrics();
         }
     }
 
-    fn log_simulation_metrics(&self) {
-        let stats = SimulationStats::from_world(
+    /// Wall time the last `update` spent simulating, excluding metric logging
+    pub fn last_step_duration(&self) -> Duration {
+        self.profiler.last_step()
+    }
+
+    /// Note whether the step just taken overran `timing.step_budget_ms`, warning once at the
+    /// start of each run of overruns rather than on every step
+    fn check_step_budget(&mut self) {
+        let budget_ms = self.config.timing.step_budget_ms;
+        let elapsed_ms = self.last_step_duration().as_secs_f32() * 1000.0;
+        let over = budget_ms > 0.0 && elapsed_ms > budget_ms;
+        if over && !self.over_budget {
+            eprintln!(
+                "step {} took {:.2} ms, over the {:.2} ms budget",
+                self.step, elapsed_ms, budget_ms
+            );
+        }
+        self.over_budget = over;
+    }
+
+    /// Log metrics every `interval` steps, or never if it is 0
+    pub fn set_log_interval(&mut self, interval: u32) {
+        self.log_interval = interval;
+    }
+
+    /// Send metric logs to `sink` instead of printing them
+    pub fn set_metrics_sink(&mut self, sink: MetricsSink) {
+        self.metrics_sink = Some(sink);
+    }
+
+    fn log_simulation_metrics(&mut self) {
+        let stats = self.stats();
+        match &mut self.metrics_sink {
+            Some(sink) => sink(self.step, &stats),
+            None => println!("{}", stats.format_detailed(self.step)),
+        }
+    }
+
+    pub fn stats(&self) -> SimulationStats {
+        let mut stats = SimulationStats::from_world(
             &self.world,
             self.config.population.max_population as f32,
             self.config.population.entity_scale,
+            self.species(),
+            self.season().phase(),
         );
-        println!("{}", stats.format_detailed(self.step));
+        stats.spatial_density = self.spatial_density();
+        stats.trophic_levels = self.trophic_levels();
+        stats
+    }
+
+    /// Gene-space species, detected on first use and reused until the world changes, so
+    /// reading stats often doesn't repeat the clustering
+    pub fn species(&self) -> Vec<Species> {
+        let mut species = self.species.lock().unwrap();
+        species
+            .get_or_insert_with(|| detect_species(&self.world))
+            .clone()
+    }
+
+    /// Drop the cached species after the world changes
+    fn forget_species(&mut self) {
+        *self.species.get_mut().unwrap() = None;
     }
 
     fn update_simulation(&mut self) {
+        self.profiler.start();
+        self.seasonal_config = self.season().apply(&self.config);
+        self.remove_dead();
+        self.reseed_below_floor();
         self.store_previous_positions();
+        self.profiler.record(Phase::StorePositions);
         self.rebuild_spatial_grid();
-        let updates = self.process_entities_parallel();
-        self.apply_entity_updates(updates);
+        self.profiler.record(Phase::RebuildGrid);
+        self.snapshot.rebuild(&self.world);
+        let mut updates = self.process_entities_parallel();
+        Self::deliver_gifts(&mut updates);
+        self.profiler.record(Phase::ProcessEntities);
+        if self.config.genetics.generational {
+            self.settle_meals(&mut updates);
+            self.generational.apply(
+                &mut self.world,
+                updates,
+                self.world_size,
+                &self.config,
+                &mut self.rng,
+                &mut self.next_uid,
+            );
+        } else {
+            self.apply_entity_updates(updates);
+        }
+        self.grid_stale.store(true, Ordering::Relaxed);
+        self.profiler.record(Phase::ApplyUpdates);
+        self.profiler.finish();
     }
 
-    fn store_previous_positions(&mut self) {
-        self.previous_positions.clear();
-        for (entity, (pos,)) in self.world.query::<(&Position,)>().iter() {
-            self.previous_positions.insert(entity, pos.clone());
-        }
+    pub fn season(&self) -> Season {
+        Season::at(self.step, &self.config.seasons)
+    }
+
+    /// Average milliseconds spent in each phase of `update` over recent steps
+    pub fn timing_report(&self) -> Vec<(&'static str, f32)> {
+        self.profiler.report()
     }
 
     fn rebuild_spatial_grid(&mut self) {
+        self.grid.reseed(self.derive_seed(&[self.step as u64]));
+        if !(self.config.physics.incremental_grid && self.update_grid_positions()) {
+            self.fill_grid();
+        }
+        *self.grid_stale.get_mut() = false;
+    }
+
+    /// Move grid entries to the entities' current positions. Only valid while the grid
+    /// holds exactly the living entities: returns false after spawns or despawns, and the
+    /// caller falls back to a full rebuild.
+    fn update_grid_positions(&self) -> bool {
+        if self.grid.is_empty() || self.grid.len() != self.world.len() as usize {
+            return false;
+        }
+        self.grid.update_positions(|entity| {
+            self.world
+                .get::<&Position>(entity)
+                .ok()
+                .map(|pos| (pos.x, pos.y))
+        })
+    }
+
+    /// The grid, rebuilt first if the world has changed since it was last built
+    fn fresh_grid(&self) -> &SpatialGrid {
+        if self.grid_stale.swap(false, Ordering::Relaxed) {
+            self.fill_grid();
+        }
+        &self.grid
+    }
+
+    fn fill_grid(&self) {
         self.grid.clear();
 
         // Parallel inserts directly into DashMap (thread-safe)
@@ -162,24 +449,55 @@ impl Simulation {
             .for_each(|(entity, (pos,))| {
                 self.grid.insert(entity, pos.x, pos.y);
             });
+        self.grid.sort_cells();
+    }
+
+    /// Despawn entities already at or below the death threshold, which no update would
+    /// remove: newborns or spawns below it, or anything caught by a raised threshold or a
+    /// shortened starvation grace. Only entities that starved during a step get grace.
+    fn remove_dead(&mut self) {
+        let energy_config = &self.config.energy;
+        let dead: Vec<Entity> = self
+            .world
+            .query::<(&Energy, Option<&Starving>)>()
+            .iter()
+            .filter(|(_, (energy, starving))| {
+                let steps = starving.map_or(0, |starving| starving.steps);
+                !energy_config.is_alive(energy.current)
+                    && (steps == 0 || !energy_config.survives(energy.current, steps))
+            })
+            .map(|(entity, _)| entity)
+            .collect();
+        if dead.is_empty() {
+            return;
+        }
+        for &entity in &dead {
+            self.log_death(entity, events::DeathCause::Starvation);
+            let _ = self.world.despawn(entity);
+            self.previous_positions.remove(&entity);
+        }
+        self.grid.remove_many(&dead);
+        self.grid_stale.store(true, Ordering::Relaxed);
     }
 
     fn process_entities_parallel(&self) -> Vec<EntityUpdate> {
-        self.world
+        let mut updates = self
+            .world
             .query::<(
                 &Position,
                 &Energy,
                 &Size,
                 &Genes,
-                &Color,
+                &Phenotype,
                 &Velocity,
-                &crate::components::MovementStyle,
+                Option<&Starving>,
             )>()
             .iter()
             .par_bridge()
             .filter_map(
-                |(entity, (pos, energy, size, genes, color, velocity, movement_style))| {
-                    if energy.current <= 0.0 {
+                |(entity, (pos, energy, size, genes, phenotype, velocity, starving))| {
+                    let starving_steps = starving.map_or(0, |starving| starving.steps);
+                    if !self.config.energy.is_alive(energy.current) && starving_steps == 0 {
                         return None;
                     }
 
@@ -189,13 +507,16 @@ impl Simulation {
                         energy,
                         size,
                         genes,
-                        color,
+                        phenotype,
                         velocity,
-                        movement_style,
+                        starving_steps,
                     })
                 },
             )
-            .collect()
+            .collect::<Vec<_>>();
+        // Apply in entity order so births and despawns don't depend on thread scheduling
+        updates.par_sort_unstable_by_key(|update| update.entity.to_bits());
+        updates
     }
 
     fn process_entity(&self, params: ProcessEntityParams) -> Option<EntityUpdate> {
@@ -205,25 +526,30 @@ impl Simulation {
             energy,
             size,
             genes,
-            color,
+            phenotype,
             velocity,
-            movement_style,
+            starving_steps,
         } = params;
 
         let nearby_entities = self.get_nearby_entities_for_entity(pos, genes);
+        let mut rng = self.entity_rng(entity, BEHAVIOR_STREAM);
 
         let mut new_pos = pos.clone();
         let mut new_velocity = velocity.clone();
         let mut new_energy = energy.current;
-        let mut eaten_entity = None;
+        let mut eaten_entities = Vec::new();
 
         self.apply_movement_to_entity(
             genes,
+            phenotype,
+            size,
             &mut new_pos,
             &mut new_velocity,
             &mut new_energy,
+            energy.max,
             pos,
             &nearby_entities,
+            &mut rng,
         );
 
         self.movement_system.handle_boundaries(
@@ -235,37 +561,95 @@ impl Simulation {
 
         self.apply_interactions_to_entity(
             &mut new_energy,
-            &mut eaten_entity,
+            &mut eaten_entities,
             &new_pos,
             size,
             genes,
+            phenotype,
             &nearby_entities,
         );
 
         self.energy_system
             .update_energy(&mut new_energy, size, genes, &self.config);
 
-        let population_density = self.calculate_population_density();
-        let should_reproduce =
-            self.check_reproduction_for_entity(new_energy, energy.max, genes, population_density);
+        let gift = self
+            .social_system
+            .share_energy(crate::systems::ShareParams {
+                entity,
+                new_energy: &mut new_energy,
+                max_energy: energy.max,
+                pos: &new_pos,
+                genes,
+                phenotype,
+                nearby_entities: &nearby_entities,
+                neighbors: &self.snapshot,
+                config: &self.config,
+            });
+
+        let starving_steps = if self.config.energy.is_alive(new_energy) {
+            0
+        } else {
+            starving_steps.saturating_add(1)
+        };
 
-        if self
-            .reproduction_system
-            .check_death(population_density, &self.config)
-        {
-            new_energy = 0.0; // Kill the entity
-        }
+        let cap_fraction = self.homeostasis().pressure(self.population_cap_fraction());
+        let cooldown = self
+            .world
+            .get::<&ReproCooldown>(entity)
+            .map_or(0, |cooldown| cooldown.remaining);
+        let age = self
+            .world
+            .get::<&Age>(entity)
+            .map_or(u32::MAX, |age| age.steps);
+        // Generational mode has no births within a generation. It only checks readiness
+        // when that counts towards fitness.
+        let generational = self.config.genetics.generational;
+        let ready_to_reproduce = (!generational || self.config.fitness.offspring_weight > 0.0)
+            && cooldown == 0
+            && starving_steps == 0
+            && self.check_reproduction_for_entity(
+                new_energy,
+                energy.max,
+                genes,
+                age,
+                cap_fraction,
+                &mut rng,
+            );
+        let should_reproduce = ready_to_reproduce && !generational;
 
+        let crowded = self.crowding_death(cap_fraction, &mut new_energy, &mut rng);
+
+        let birth_energy = new_energy;
+        let mut inherited_energy = None;
         if should_reproduce {
             // Don't spawn child here - we'll handle it in apply_entity_updates
             // Reduce parent energy
-            new_energy *= self.config.reproduction.reproduction_energy_cost;
+            (new_energy, inherited_energy) =
+                self.reproduction_system
+                    .debit_parent(new_energy, energy.max, genes, &self.config);
         }
 
         let new_size_radius =
             self.energy_system
                 .calculate_new_size(new_energy, genes, &self.config);
 
+        let (child_genotype, child_pedigree) = self
+            .world
+            .get::<&DiploidGenes>(entity)
+            .ok()
+            .filter(|_| should_reproduce)
+            .map_or((None, None), |genotype| {
+                let (child, mate) = self.reproduction_system.cross_with_nearby_mate(
+                    &genotype,
+                    entity,
+                    &nearby_entities,
+                    &self.world,
+                    &self.config,
+                    &mut rng,
+                );
+                (Some(child), self.offspring_pedigree(entity, mate))
+            });
+
         Some(EntityUpdate {
             entity,
             pos: new_pos,
@@ -277,218 +661,294 @@ impl Simulation {
                 radius: new_size_radius,
             },
             genes: genes.clone(),
-            color: color.clone(),
             velocity: new_velocity,
-            movement_style: movement_style.clone(),
             should_reproduce,
-            eaten_entity,
+            ready_to_reproduce,
+            eaten_entities,
+            child_genotype,
+            child_pedigree,
+            repro_cooldown: cooldown.saturating_sub(1), // Reset in apply_entity_updates if admitted
+            inherited_energy,
+            birth_energy,
+            starving_steps,
+            crowded,
+            gift,
         })
     }
 
+    /// Random stream for one entity in the current step. It depends only on the seed, step,
+    /// entity and stream, so parallel scheduling can't change what an entity draws.
+    fn entity_rng(&self, entity: Entity, stream: u64) -> StdRng {
+        StdRng::seed_from_u64(self.derive_seed(&[self.step as u64, entity.to_bits().get(), stream]))
+    }
+
+    /// Mix `values` into the simulation seed
+    fn derive_seed(&self, values: &[u64]) -> u64 {
+        values.iter().fold(self.seed, |seed, value| {
+            (seed ^ value)
+                .wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                .rotate_left(31)
+        })
+    }
+
+    /// Neighbours within the sense radius, widened by `query_radius_margin` steps of
+    /// maximum displacement so entities about to close in aren't missed
     fn get_nearby_entities_for_entity(&self, pos: &Position, genes: &Genes) -> Vec<Entity> {
-        let nearby_entities = self
-            .grid
-            .get_nearby_entities(pos.x, pos.y, genes.sense_radius());
-        nearby_entities.iter().take(20).copied().collect::<Vec<_>>()
+        let physics = &self.config.physics;
+        let margin = physics.query_radius_margin * physics.max_velocity;
+        self.grid.get_nearest_entities(
+            pos.x,
+            pos.y,
+            genes.sense_radius() + margin,
+            physics.max_neighbors,
+        )
     }
 
     fn apply_movement_to_entity(
         &self,
         genes: &Genes,
+        phenotype: &Phenotype,
+        size: &Size,
         new_pos: &mut Position,
         new_velocity: &mut Velocity,
         new_energy: &mut f32,
+        max_energy: f32,
         pos: &Position,
         nearby_entities: &[Entity],
+        rng: &mut StdRng,
     ) {
         self.movement_system
             .update_movement(crate::systems::MovementUpdateParams {
                 genes,
+                phenotype,
+                size,
                 new_pos,
                 new_velocity,
                 new_energy,
+                max_energy,
                 pos,
                 nearby_entities,
-                world: &self.world,
-                config: &self.config,
+                neighbors: &self.snapshot,
+                config: &self.seasonal_config,
                 world_size: self.world_size,
+                rng,
             });
     }
 
     fn apply_interactions_to_entity(
         &self,
         new_energy: &mut f32,
-        eaten_entity: &mut Option<Entity>,
+        eaten_entities: &mut Vec<(Entity, f32)>,
         new_pos: &Position,
         size: &Size,
         genes: &Genes,
+        phenotype: &Phenotype,
         nearby_entities: &[Entity],
     ) {
         self.interaction_system
             .handle_interactions(crate::systems::InteractionParams {
                 new_energy,
-                eaten_entity,
+                eaten_entities,
                 new_pos,
                 size,
                 genes,
+                phenotype,
                 nearby_entities,
-                world: &self.world,
+                neighbors: &self.snapshot,
                 config: &self.config,
             });
     }
 
-    fn calculate_population_density(&self) -> f32 {
-        self.world.len() as f32
-            / (self.config.population.max_population as f32 * self.config.population.entity_scale)
+    /// Population over the configured cap. Birth and death rates respond to this rather
+    /// than `spatial_density`, so the cap holds whatever the world size.
+    fn population_cap_fraction(&self) -> f32 {
+        let cap =
+            self.config.population.max_population as f32 * self.config.population.entity_scale;
+        if cap > 0.0 {
+            self.world.len() as f32 / cap
+        } else {
+            0.0
+        }
     }
 
-    fn check_reproduction_for_entity(
-        &self,
-        energy: f32,
-        max_energy: f32,
-        genes: &Genes,
-        population_density: f32,
-    ) -> bool {
-        self.reproduction_system.check_reproduction(
-            energy,
-            max_energy,
-            genes,
-            population_density,
-            &self.config,
-        )
+    /// The population controller's correction for the current population
+    pub fn homeostasis(&self) -> Homeostasis {
+        Homeostasis::new(self.world.len(), &self.config.population)
     }
 
-    fn apply_entity_updates(&mut self, updates: Vec<EntityUpdate>) {
-        // Remove eaten entities in parallel
-        let entities_to_remove: Vec<_> = updates
-            .par_iter()
-            .filter_map(|update| update.eaten_entity)
-            .collect();
+    /// Entities per unit of world area, for behaviour that should respond to crowding
+    pub fn spatial_density(&self) -> f32 {
+        let area = self.world_size * self.world_size;
+        if area > 0.0 {
+            self.world.len() as f32 / area
+        } else {
+            0.0
+        }
+    }
+
+    fn apply_entity_updates(&mut self, mut updates: Vec<EntityUpdate>) {
+        self.settle_meals(&mut updates);
+        self.log_update_events(&updates);
+        let offspring = self.prepare_offspring(&updates);
+        // Only parents whose birth was admitted wait out the cooldown
+        let parents: HashSet<Entity> = offspring.iter().map(|&(parent, ..)| parent).collect();
+        for update in updates.iter_mut() {
+            if parents.contains(&update.entity) {
+                update.repro_cooldown = self.config.reproduction.reproduction_cooldown;
+            }
+        }
+        let removed = Self::apply_updates_in_place(&mut self.world, updates, &self.config);
+        // Keep the grid in step with the world so the next rebuild can stay incremental
+        self.grid.remove_many(&removed);
+        self.spawn_offspring(offspring);
+    }
 
+    /// Survivors are updated in place so entity ids stay stable across steps;
+    /// eaten and starved entities are removed in one batch and returned
+    fn apply_updates_in_place(
+        world: &mut World,
+        updates: Vec<EntityUpdate>,
+        config: &SimulationConfig,
+    ) -> Vec<Entity> {
+        // Eaten first, then starved, in update order: freed ids are reused by the
+        // offspring spawned next, so this order decides which ids they get
+        let mut removed = Vec::new();
+        let eaten = updates
+            .iter()
+            .flat_map(|update| update.eaten_entities.iter().map(|&(prey, _)| prey));
+        let starved = updates
+            .iter()
+            .filter(|update| !update.survives(config))
+            .map(|update| update.entity);
         // Despawn entities (this needs to be sequential due to Hecs limitations)
-        for &entity in &entities_to_remove {
-            let _ = self.world.despawn(entity);
+        for entity in eaten.chain(starved) {
+            if world.despawn(entity).is_ok() {
+                removed.push(entity);
+            }
         }
 
-        // Prepare spawn data in parallel
-        let spawn_data: Vec<_> = updates
-            .par_iter()
-            .filter_map(|update| {
-                if update.energy.current <= 0.0 {
-                    return None;
+        for update in updates {
+            if !update.survives(config) {
+                continue;
+            }
+            // Eaten entities were despawned above, so their own update is dropped here
+            if let Ok((pos, energy, size, velocity, age)) = world.query_one_mut::<(
+                &mut Position,
+                &mut Energy,
+                &mut Size,
+                &mut Velocity,
+                Option<&mut Age>,
+            )>(update.entity)
+            {
+                *pos = update.pos;
+                *energy = update.energy;
+                *size = update.size;
+                *velocity = update.velocity;
+                if let Some(age) = age {
+                    age.steps += 1;
                 }
-
-                // Store values before spawning to avoid move issues
-                let energy_max = update.energy.max;
-
-                let mut spawn_entities = vec![(
-                    update.pos.clone(),
-                    update.energy.clone(),
-                    update.size.clone(),
-                    update.genes.clone(),
-                    update.color.clone(),
-                    update.velocity.clone(),
-                    update.movement_style.clone(),
-                )];
-
-                // Handle reproduction with stricter population control
-                let max_population = (self.config.population.max_population as f32
-                    * self.config.population.entity_scale)
-                    as u32;
-                if update.should_reproduce && self.world.len() < max_population {
-                    let (
-                        child_pos,
-                        child_energy,
-                        child_size,
-                        child_genes,
-                        child_color,
-                        child_velocity,
-                        child_movement_style,
-                    ) = self.reproduction_system.create_offspring(
-                        &update.genes,
-                        energy_max,
-                        &update.pos,
-                        &self.config,
+            }
+            // Only entities that have reproduced with a cooldown configured carry the component
+            match world.query_one_mut::<&mut ReproCooldown>(update.entity) {
+                Ok(cooldown) => cooldown.remaining = update.repro_cooldown,
+                Err(_) if update.repro_cooldown > 0 => {
+                    let _ = world.insert_one(
+                        update.entity,
+                        ReproCooldown {
+                            remaining: update.repro_cooldown,
+                        },
                     );
-
-                    spawn_entities.push((
-                        child_pos,
-                        child_energy,
-                        child_size,
-                        child_genes,
-                        child_color,
-                        child_velocity,
-                        child_movement_style,
-                    ));
                 }
-
-                Some(spawn_entities)
-            })
-            .flatten()
-            .collect();
-
-        // Despawn old entities
-        for update in updates {
-            let _ = self.world.despawn(update.entity);
+                Err(_) => {}
+            }
+            match world.query_one_mut::<&mut Starving>(update.entity) {
+                Ok(starving) => starving.steps = update.starving_steps,
+                Err(_) if update.starving_steps > 0 => {
+                    let _ = world.insert_one(
+                        update.entity,
+                        Starving {
+                            steps: update.starving_steps,
+                        },
+                    );
+                }
+                Err(_) => {}
+            }
         }
+        removed
+    }
 
-        // Spawn new entities (this needs to be sequential due to Hecs limitations)
-        for (position, energy, size, genes, color, velocity, movement_style) in spawn_data {
-            self.world.spawn((
-                position,
-                energy,
-                size,
-                genes,
-                color,
-                velocity,
-                movement_style,
-            ));
+    /// Colour to draw an entity in, per `rendering.color_mode`
+    fn render_color(&self, color: &Color, genes: &Genes) -> Color {
+        match self.config.rendering.color_mode {
+            ColorMode::Appearance => color.clone(),
+            ColorMode::Genotype => genes.genotype_color(),
         }
     }
 
-    pub fn get_entities(&self) -> Vec<(f32, f32, f32, f32, f32, f32, f32, f32)> {
+    /// Everything the renderer may draw for each entity
+    pub fn render_entities(&self) -> Vec<RenderEntity> {
         self.world
-            .query::<(&Position, &Size, &Color)>()
+            .query::<(&Position, &Size, &Color, &Energy, &Genes, &Velocity)>()
             .iter()
             .par_bridge()
-            .map(|(entity, (pos, size, color))| {
-                let prev_pos = self.previous_positions.get(&entity).unwrap_or(pos);
-                (
-                    prev_pos.x,
-                    prev_pos.y,
-                    pos.x,
-                    pos.y,
-                    size.radius,
-                    color.r,
-                    color.g,
-                    color.b,
-                )
+            .map(|(entity, (pos, size, color, energy, genes, velocity))| {
+                let prev_pos = self.previous_position(entity, pos);
+                let energy_fraction = if energy.max > 0.0 {
+                    (energy.current / energy.max).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                RenderEntity {
+                    entity,
+                    prev_pos: prev_pos.clone(),
+                    pos: pos.clone(),
+                    radius: size.radius,
+                    color: self.render_color(color, genes),
+                    energy_fraction,
+                    velocity: velocity.clone(),
+                }
             })
             .collect()
     }
 
+    /// `render_entities` in entity id order, so the renderer draws overlapping entities
+    /// the same way every frame
+    pub fn render_entities_sorted(&self) -> Vec<RenderEntity> {
+        let mut entities = self.render_entities();
+        entities.par_sort_unstable_by_key(|e| e.entity.to_bits());
+        entities
+    }
+
+    /// Render data per entity: previous and current position, radius, colour and the
+    /// fraction of its max energy it holds
+    pub fn get_entities(&self) -> Vec<(f32, f32, f32, f32, f32, f32, f32, f32, f32)> {
+        self.render_entities().iter().map(render_tuple).collect()
+    }
+
+    /// `get_entities` in a stable order, by entity id
+    pub fn get_entities_sorted(&self) -> Vec<(f32, f32, f32, f32, f32, f32, f32, f32, f32)> {
+        self.render_entities_sorted()
+            .iter()
+            .map(render_tuple)
+            .collect()
+    }
+
     pub fn get_interpolated_entities(
         &self,
         interpolation_factor: f32,
     ) -> Vec<(f32, f32, f32, f32, f32, f32)> {
         self.world
-            .query::<(&Position, &Size, &Color)>()
+            .query::<(&Position, &Size, &Color, &Genes)>()
             .iter()
             .par_bridge()
-            .map(|(entity, (pos, size, color))| {
-                let interpolated_pos = if let Some(prev_pos) = self.previous_positions.get(&entity)
-                {
-                    // Interpolate between previous and current position
-                    let x = prev_pos.x + (pos.x - prev_pos.x) * interpolation_factor;
-                    let y = prev_pos.y + (pos.y - prev_pos.y) * interpolation_factor;
-                    (x, y)
-                } else {
-                    (pos.x, pos.y)
-                };
+            .map(|(entity, (pos, size, color, genes))| {
+                let prev_pos = self.previous_position(entity, pos);
+                let interpolated_pos = interpolate(prev_pos, pos, interpolation_factor);
+                let color = self.render_color(color, genes);
 
                 (
-                    interpolated_pos.0,
-                    interpolated_pos.1,
+                    interpolated_pos.x,
+                    interpolated_pos.y,
                     size.radius,
                     color.r,
                     color.g,
@@ -498,6 +958,132 @@ impl Simulation {
             .collect()
     }
 
+    /// Spawn `count` copies of the given genes, e.g. a creature imported from a share code.
+    /// Locked traits keep their fixed values whatever the genes carry.
+    pub fn inject_genes(&mut self, genes: &Genes, count: usize) {
+        let mut injected = genes.clone();
+        injected.lock_traits(&self.config.genetics.locked_genes);
+        self.recording
+            .events
+            .push(replay::ReplayEvent::GenesInjected {
+                step: self.step,
+                genes: genes.clone(),
+                count,
+            });
+        let rng = &mut self.rng;
+        let spawn_radius = self.world_size * self.config.population.spawn_radius_factor;
+        let positions = spawn::spawn_positions(&SpawnPattern::Disc, count, spawn_radius, rng);
+        for pos in positions {
+            let entity = Self::spawn_entity(
+                &mut self.world,
+                rng,
+                injected.clone(),
+                pos,
+                &self.config,
+                &mut self.next_uid,
+            );
+            if self.config.genetics.diploid {
+                let genotype = DiploidGenes {
+                    a: injected.clone(),
+                    b: injected.clone(),
+                };
+                let _ = self.world.insert_one(entity, genotype);
+            }
+        }
+        self.grid_stale.store(true, Ordering::Relaxed);
+        self.forget_species();
+    }
+
+    /// Move `entity` with all its components into `other`, returning its id there. It gets
+    /// a fresh `Uid` from `other`. Like any external input, a transfer is not part of either
+    /// simulation's replay.
+    pub fn transfer_entity(&mut self, entity: Entity, other: &mut Simulation) -> Option<Entity> {
+        let taken = self.world.take(entity).ok()?;
+        let moved = other.world.spawn(taken);
+        let _ = other
+            .world
+            .insert_one(moved, Uid::take(&mut other.next_uid));
+        self.previous_positions.remove(&entity);
+        self.grid_stale.store(true, Ordering::Relaxed);
+        other.grid_stale.store(true, Ordering::Relaxed);
+        self.forget_species();
+        other.forget_species();
+        Some(moved)
+    }
+
+    /// Entity closest to a world position, within `max_distance`
+    pub fn entity_near(&self, x: f32, y: f32, max_distance: f32) -> Option<Entity> {
+        self.world
+            .query::<&Position>()
+            .iter()
+            .map(|(entity, pos)| ((pos.x - x).powi(2) + (pos.y - y).powi(2), entity))
+            .filter(|(distance_sq, _)| *distance_sq <= max_distance * max_distance)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, entity)| entity)
+    }
+
+    /// Entities inside the rectangle `min..=max`, with their positions, in entity order
+    pub fn entities_in_rect(&self, min: &Position, max: &Position) -> Vec<(Entity, Position)> {
+        self.fresh_grid()
+            .entries_in_rect((min.x, min.y), (max.x, max.y))
+            .into_iter()
+            .map(|(entity, x, y)| (entity, Position { x, y }))
+            .collect()
+    }
+
+    /// Entities within `radius` of `center`, with their positions, in entity order
+    pub fn entities_in_circle(&self, center: &Position, radius: f32) -> Vec<(Entity, Position)> {
+        self.fresh_grid()
+            .entries_in_circle(center.x, center.y, radius)
+            .into_iter()
+            .map(|(entity, x, y)| (entity, Position { x, y }))
+            .collect()
+    }
+
+    /// Genes of the entity closest to a world position, within `max_distance`
+    pub fn genes_near(&self, x: f32, y: f32, max_distance: f32) -> Option<Genes> {
+        let entity = self.entity_near(x, y, max_distance)?;
+        self.world
+            .get::<&Genes>(entity)
+            .ok()
+            .map(|genes| (*genes).clone())
+    }
+
+    /// Current position of a living entity
+    pub fn position_of(&self, entity: Entity) -> Option<Position> {
+        self.world
+            .get::<&Position>(entity)
+            .ok()
+            .map(|pos| (*pos).clone())
+    }
+
+    /// How crowded the grid's cells are
+    pub fn cell_occupancy(&self) -> CellOccupancy {
+        self.fresh_grid().occupancy()
+    }
+
+    /// Bounds and entity count of each non-empty grid cell
+    pub fn grid_cells(&self) -> Vec<GridCell> {
+        self.fresh_grid().cells()
+    }
+
+    /// Moran's I of a trait over neighbours within `radius`: how strongly similar values
+    /// cluster in space. `None` for an unknown trait name.
+    pub fn gene_autocorrelation(&self, trait_name: &str, radius: f32) -> Option<f32> {
+        crate::stats::morans_i(&self.world, self.fresh_grid(), trait_name, radius)
+    }
+
+    /// How many living entities feed at each trophic level, judged from the meals of the
+    /// last `FOOD_WEB_WINDOW` steps
+    pub fn trophic_levels(&self) -> TrophicLevels {
+        crate::stats::trophic_levels(&self.world, self.food_web.records())
+    }
+
+    /// Per-cell gene diversity, for visualising the spatial structure of genotypes
+    pub fn cell_diversity_map(&self) -> Vec<((i32, i32), f32)> {
+        crate::stats::cell_diversity_map(&self.world, self.fresh_grid())
+    }
+
     pub fn world(&self) -> &World {
         &self.world
     }
@@ -506,14 +1092,46 @@ impl Simulation {
         self.world_size
     }
 
+    /// The `Uid` the next spawned entity will get; every lower uid has been handed out
+    pub fn next_uid(&self) -> u64 {
+        self.next_uid
+    }
+
     pub fn step(&self) -> u32 {
         self.step
     }
 
     pub fn update_config(&mut self, config: SimulationConfig) {
+        self.recording
+            .events
+            .push(replay::ReplayEvent::ConfigChanged {
+                step: self.step,
+                config: config.clone(),
+            });
+        if config.physics.grid_cell_size != self.config.physics.grid_cell_size {
+            // Incremental updates keep the cell size, so start a grid to be filled afresh
+            self.grid = SpatialGrid::new(config.physics.grid_cell_size);
+            self.grid_stale.store(true, Ordering::Relaxed);
+        }
+        self.grid
+            .set_cell_capacity(config.physics.grid_cell_capacity);
         self.config = config;
     }
 }
 
+fn render_tuple(e: &RenderEntity) -> (f32, f32, f32, f32, f32, f32, f32, f32, f32) {
+    (
+        e.prev_pos.x,
+        e.prev_pos.y,
+        e.pos.x,
+        e.pos.y,
+        e.radius,
+        e.color.r,
+        e.color.g,
+        e.color.b,
+        e.energy_fraction,
+    )
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,204 @@
+use super::events::EventKind;
+use super::{EntityUpdate, Simulation, OFFSPRING_STREAM};
+use crate::components::{Age, Color, Energy, MovementStyle, Position, Size, Uid, Velocity};
+use crate::genes::{DiploidGenes, Genes, Pedigree, Phenotype};
+use hecs::Entity;
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+type OffspringComponents = (
+    Position,
+    Energy,
+    Size,
+    Genes,
+    Color,
+    Velocity,
+    MovementStyle,
+);
+
+/// A child ready to spawn: its parent, components, and genotype and pedigree if diploid
+pub(super) type Offspring = (
+    Entity,
+    OffspringComponents,
+    Option<DiploidGenes>,
+    Option<Pedigree>,
+);
+
+impl Simulation {
+    /// Pedigree of a child of `parent` and `mate`, linking it to their ancestry
+    pub(super) fn offspring_pedigree(&self, parent: Entity, mate: Entity) -> Option<Pedigree> {
+        let lineage = |entity: Entity| {
+            let uid = self.world.get::<&Uid>(entity).ok()?.0;
+            Some((uid, self.world.get::<&Pedigree>(entity).ok()))
+        };
+        let (mother, father) = (lineage(parent)?, lineage(mate)?);
+        Some(Pedigree::offspring(
+            (mother.0, mother.1.as_deref()),
+            (father.0, father.1.as_deref()),
+        ))
+    }
+
+    pub(super) fn check_reproduction_for_entity(
+        &self,
+        energy: f32,
+        max_energy: f32,
+        genes: &Genes,
+        age: u32,
+        cap_fraction: f32,
+        rng: &mut StdRng,
+    ) -> bool {
+        self.reproduction_system.check_reproduction(
+            energy,
+            max_energy,
+            genes,
+            age,
+            cap_fraction,
+            &self.config,
+            rng,
+        )
+    }
+
+    /// Build offspring for admitted parents in parallel, before the world is mutated
+    pub(super) fn prepare_offspring(&self, updates: &[EntityUpdate]) -> Vec<Offspring> {
+        let eaten: HashSet<Entity> = updates
+            .par_iter()
+            .flat_map(|update| update.eaten_entities.par_iter().map(|&(prey, _)| prey))
+            .collect();
+        let admitted_parents = self.admit_reproduction(updates, &eaten);
+        if admitted_parents.is_empty() {
+            return Vec::new();
+        }
+
+        // Where survivors will be after this step, for overlap-free offspring placement
+        let survivors: HashMap<Entity, (Position, f32)> = updates
+            .iter()
+            .filter(|update| update.survives(&self.config) && !eaten.contains(&update.entity))
+            .map(|update| (update.entity, (update.pos.clone(), update.size.radius)))
+            .collect();
+
+        let prepared: Vec<_> = updates
+            .par_iter()
+            .filter(|update| admitted_parents.contains(&update.entity))
+            .map(|update| {
+                let occupied = self.occupied_near(&update.pos, &survivors);
+                let mut rng = self.entity_rng(update.entity, OFFSPRING_STREAM);
+                let child_genes = match &update.child_genotype {
+                    Some(genotype) => genotype.express(self.config.genetics.dominance),
+                    None => update.genes.mutate_with(&mut rng, &self.config.genetics),
+                };
+                (update, occupied, child_genes, rng)
+            })
+            .collect();
+
+        // Placed one at a time, so each child also keeps clear of those placed before it
+        let reach = self.placement_reach();
+        let mut placed: Vec<(Position, f32)> = Vec::new();
+        prepared
+            .into_iter()
+            .map(|(update, mut occupied, child_genes, mut rng)| {
+                occupied.extend(
+                    placed
+                        .iter()
+                        .filter(|(pos, _)| {
+                            (pos.x - update.pos.x).powi(2) + (pos.y - update.pos.y).powi(2)
+                                < reach.powi(2)
+                        })
+                        .cloned(),
+                );
+                let child = self.reproduction_system.create_offspring_with_genes(
+                    child_genes,
+                    update.energy.max,
+                    update.inherited_energy,
+                    &update.pos,
+                    &occupied,
+                    &self.config,
+                    &mut rng,
+                );
+                placed.push((child.0.clone(), child.2.radius));
+                (
+                    update.entity,
+                    child,
+                    update.child_genotype.clone(),
+                    update.child_pedigree.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// How far from a parent anything its child could overlap may be
+    fn placement_reach(&self) -> f32 {
+        let physics = &self.config.physics;
+        self.config.reproduction.child_spawn_radius
+            + 2.0 * physics.max_entity_radius
+            + physics.max_velocity
+    }
+
+    /// Survivors that could overlap a child placed around `pos`. The grid still holds
+    /// positions from the start of the step, so the query is widened by a step's movement.
+    fn occupied_near(
+        &self,
+        pos: &Position,
+        survivors: &HashMap<Entity, (Position, f32)>,
+    ) -> Vec<(Position, f32)> {
+        self.grid
+            .get_nearby_entities(pos.x, pos.y, self.placement_reach())
+            .into_iter()
+            .filter_map(|entity| survivors.get(&entity).cloned())
+            .collect()
+    }
+
+    /// Reproduction slots are limited to the capacity left after this step's survivors.
+    /// Candidates are admitted in entity id order so the outcome doesn't depend on
+    /// parallel scheduling.
+    fn admit_reproduction(
+        &self,
+        updates: &[EntityUpdate],
+        eaten: &HashSet<Entity>,
+    ) -> HashSet<Entity> {
+        let max_population = (self.config.population.max_population as f32
+            * self.config.population.entity_scale) as usize;
+        let alive = |update: &&EntityUpdate| {
+            update.survives(&self.config) && !eaten.contains(&update.entity)
+        };
+        let survivors = updates.iter().filter(alive).count();
+        let capacity = max_population.saturating_sub(survivors);
+
+        let mut candidates: Vec<Entity> = updates
+            .iter()
+            .filter(alive)
+            .filter(|update| update.should_reproduce)
+            .map(|update| update.entity)
+            .collect();
+        candidates.sort_unstable_by_key(|entity| entity.to_bits());
+        candidates.into_iter().take(capacity).collect()
+    }
+
+    /// Spawn the prepared offspring and log their births
+    pub(super) fn spawn_offspring(&mut self, offspring: Vec<Offspring>) {
+        // Spawned one at a time, as hecs needs exclusive access to the world
+        let mut born = Vec::with_capacity(offspring.len());
+        for (parent, components, genotype, pedigree) in offspring {
+            let (x, y) = (components.0.x, components.0.y);
+            let mut builder = hecs::EntityBuilder::new();
+            builder
+                .add(Phenotype::of(&components.3))
+                .add_bundle(components)
+                .add(Age::default())
+                .add(Uid::take(&mut self.next_uid));
+            if let Some(genotype) = genotype {
+                builder.add(genotype);
+            }
+            if let Some(pedigree) = pedigree {
+                builder.add(pedigree);
+            }
+            let child = self.world.spawn(builder.build());
+            self.log_event(child, parent, |child, parent| EventKind::Birth {
+                child,
+                parent,
+            });
+            born.push((child, x, y));
+        }
+        self.grid.insert_many(&born);
+    }
+}
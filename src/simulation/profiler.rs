@@ -0,0 +1,81 @@
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+use std::collections::VecDeque;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// Phases of `update_simulation`, in the order they run
+#[derive(Clone, Copy, Debug)]
+pub enum Phase {
+    StorePositions,
+    RebuildGrid,
+    ProcessEntities,
+    ApplyUpdates,
+}
+
+/// Name of each `Phase`, indexed by its discriminant
+pub const PHASES: [&str; 4] = [
+    "store_positions",
+    "rebuild_grid",
+    "process_entities",
+    "apply_updates",
+];
+
+/// Steps averaged over by `PhaseProfiler::report`
+const WINDOW: usize = 60;
+
+/// Rolling per-phase timings of recent simulation steps
+#[derive(Default)]
+pub struct PhaseProfiler {
+    samples: [VecDeque<f32>; PHASES.len()],
+    phase_start: Option<Instant>,
+    step_start: Option<Instant>,
+    last_step: Duration, // Wall time of the last complete step
+}
+
+impl PhaseProfiler {
+    pub fn start(&mut self) {
+        let now = Instant::now();
+        self.phase_start = Some(now);
+        self.step_start = Some(now);
+    }
+
+    /// End the step begun by `start`, recording how long it took
+    pub fn finish(&mut self) {
+        if let Some(start) = self.step_start.take() {
+            self.last_step = start.elapsed();
+        }
+    }
+
+    pub fn last_step(&self) -> Duration {
+        self.last_step
+    }
+
+    /// Record the time since the last `start` or `record` against `phase`
+    pub fn record(&mut self, phase: Phase) {
+        let now = Instant::now();
+        let elapsed_ms = self
+            .phase_start
+            .map_or(0.0, |start| (now - start).as_secs_f32() * 1000.0);
+        self.phase_start = Some(now);
+
+        let samples = &mut self.samples[phase as usize];
+        samples.push_back(elapsed_ms);
+        if samples.len() > WINDOW {
+            samples.pop_front();
+        }
+    }
+
+    /// Average milliseconds per phase over the recent window
+    pub fn report(&self) -> Vec<(&'static str, f32)> {
+        PHASES
+            .iter()
+            .zip(&self.samples)
+            .map(|(&phase, samples)| {
+                let average = samples.iter().sum::<f32>() / samples.len().max(1) as f32;
+                (phase, average)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,177 @@
+use super::Simulation;
+use crate::config::SimulationConfig;
+use crate::genes::Genes;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// An external input to a run, applied before the step numbered `step` is taken
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    ConfigChanged {
+        step: u32,
+        config: SimulationConfig,
+    },
+    GenesInjected {
+        step: u32,
+        genes: Genes,
+        count: usize,
+    },
+    GeneEdited {
+        step: u32,
+        uid: u64, // `Uid` of the edited entity
+        field: String,
+        value: f32, // The trait's value after the edit
+    },
+}
+
+impl ReplayEvent {
+    fn step(&self) -> u32 {
+        match self {
+            ReplayEvent::ConfigChanged { step, .. }
+            | ReplayEvent::GenesInjected { step, .. }
+            | ReplayEvent::GeneEdited { step, .. } => *step,
+        }
+    }
+}
+
+/// Everything needed to reproduce a run exactly: the seed, the starting config and any
+/// external inputs. A run nobody interfered with is stored as just its seed and config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub world_size: f32,
+    pub seed: u64,
+    pub config: SimulationConfig, // Config the run started with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<ReplayEvent>,
+    pub steps: u32,
+}
+
+impl Replay {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Re-run the recording, returning the simulation in its final state
+    pub fn play(&self) -> Simulation {
+        self.play_with(|_| {})
+    }
+
+    /// Re-run the recording, calling `on_step` after every step, e.g. to emit positions
+    /// for a viewer
+    pub fn play_with(&self, mut on_step: impl FnMut(&Simulation)) -> Simulation {
+        let mut sim = Simulation::new_seeded(self.world_size, self.config.clone(), self.seed);
+        let mut events = self.events.iter().peekable();
+
+        while sim.step() < self.steps {
+            while let Some(event) = events.next_if(|event| event.step() <= sim.step()) {
+                match event {
+                    ReplayEvent::ConfigChanged { config, .. } => sim.update_config(config.clone()),
+                    ReplayEvent::GenesInjected { genes, count, .. } => {
+                        sim.inject_genes(genes, *count)
+                    }
+                    ReplayEvent::GeneEdited {
+                        uid, field, value, ..
+                    } => {
+                        if let Some(entity) = sim.entity_with_uid(*uid) {
+                            sim.set_gene(entity, field, *value);
+                        }
+                    }
+                }
+            }
+            sim.update();
+            on_step(&sim);
+        }
+        sim
+    }
+}
+
+impl Simulation {
+    /// The run so far as a replay
+    pub fn replay(&self) -> Replay {
+        Replay {
+            steps: self.step,
+            ..self.recording.clone()
+        }
+    }
+
+    pub fn record_replay<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        self.replay().save_to_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Position;
+    use hecs::Entity;
+
+    fn final_state(sim: &Simulation) -> Vec<(u64, f32, f32)> {
+        let mut state: Vec<(u64, f32, f32)> = sim
+            .world()
+            .query::<&Position>()
+            .iter()
+            .map(|(entity, pos): (Entity, &Position)| (entity.to_bits().get(), pos.x, pos.y))
+            .collect();
+        state.sort_by_key(|(id, _, _)| *id);
+        state
+    }
+
+    #[test]
+    fn test_replayed_run_matches_original() {
+        let mut config = SimulationConfig::default();
+        config.population.initial_entities = 300;
+        config.population.entity_scale = 1.0;
+        config.population.max_population = 600;
+        let mut sim = Simulation::new_seeded(300.0, config, 42);
+
+        for step in 0..60 {
+            if step == 20 {
+                let genes = sim
+                    .world()
+                    .query::<&Genes>()
+                    .iter()
+                    .next()
+                    .unwrap()
+                    .1
+                    .clone();
+                sim.inject_genes(&genes, 5);
+            }
+            if step == 30 {
+                let entity = sim.world().iter().next().unwrap().entity();
+                sim.mutate_entity(entity, "speed", 1.0).unwrap();
+            }
+            if step == 40 {
+                let mut changed = sim.config.clone();
+                changed.physics.max_velocity = 3.0;
+                sim.update_config(changed);
+            }
+            sim.update();
+        }
+
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        sim.record_replay(temp_file.path()).unwrap();
+        let replay = Replay::load_from_file(temp_file.path()).unwrap();
+        assert_eq!(replay.events.len(), 3);
+
+        let mut steps_seen = 0;
+        let replayed = replay.play_with(|_| steps_seen += 1);
+        assert_eq!(steps_seen, 60);
+        assert_eq!(replayed.step(), sim.step());
+        assert_eq!(final_state(&replayed), final_state(&sim));
+    }
+
+    #[test]
+    fn test_uninterrupted_run_stores_only_seed_and_config() {
+        let sim = Simulation::new_seeded(100.0, SimulationConfig::default(), 7);
+        let json = serde_json::to_string(&sim.replay()).unwrap();
+        assert!(!json.contains("events"));
+        assert!(json.contains("\"seed\":7"));
+    }
+}
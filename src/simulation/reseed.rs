@@ -0,0 +1,85 @@
+use super::events::EventKind;
+use super::{spawn, Simulation};
+use crate::config::SpawnPattern;
+use std::sync::atomic::Ordering;
+
+impl Simulation {
+    /// `population.min_viable_population` in entities, scaled like `max_population`
+    fn viable_floor(&self) -> usize {
+        let population = &self.config.population;
+        (population.min_viable_population as f32 * population.entity_scale) as usize
+    }
+
+    /// Top a population below the floor back up with at most `reseed_count` random
+    /// entities, so an open-ended run isn't ended early by extinction
+    pub(super) fn reseed_below_floor(&mut self) {
+        let shortfall = self
+            .viable_floor()
+            .saturating_sub(self.world.len() as usize);
+        let count = shortfall.min(self.config.population.reseed_count as usize);
+        if count == 0 {
+            return;
+        }
+        let spawn_radius = self.world_size * self.config.population.spawn_radius_factor;
+        let positions =
+            spawn::spawn_positions(&SpawnPattern::Disc, count, spawn_radius, &mut self.rng);
+        for pos in positions {
+            let entity = Self::spawn_random_entity(
+                &mut self.world,
+                &mut self.rng,
+                pos,
+                &self.config,
+                &mut self.next_uid,
+            );
+            self.log_event(entity, entity, |entity, _| EventKind::Reseed { entity });
+        }
+        self.grid_stale.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+
+    fn reseeding_config(initial_entities: usize) -> SimulationConfig {
+        let mut config = SimulationConfig::default();
+        config.population.entity_scale = 1.0;
+        config.population.initial_entities = initial_entities;
+        config.population.min_viable_population = 20;
+        config.population.reseed_count = 8;
+        config
+    }
+
+    #[test]
+    fn test_population_below_the_floor_is_reseeded_up_to_it() {
+        let mut sim = Simulation::new_seeded(400.0, reseeding_config(4), 6);
+        sim.set_event_logging(true);
+
+        let mut populations = Vec::new();
+        for _ in 0..3 {
+            sim.reseed_below_floor();
+            populations.push(sim.world.len());
+        }
+        assert_eq!(populations, [12, 20, 20]);
+        let reseeds = sim
+            .drain_events()
+            .iter()
+            .filter(|event| matches!(event.kind, EventKind::Reseed { .. }))
+            .count();
+        assert_eq!(reseeds, 16);
+
+        sim.config.population.min_viable_population = 0;
+        sim.world.clear();
+        sim.reseed_below_floor();
+        assert!(sim.is_extinct());
+    }
+
+    #[test]
+    fn test_extinct_run_is_reseeded_on_the_next_step() {
+        let mut sim = Simulation::new_seeded(400.0, reseeding_config(0), 6);
+        assert!(sim.is_extinct());
+        sim.update();
+        assert!(!sim.is_extinct());
+    }
+}
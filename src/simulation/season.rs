@@ -0,0 +1,83 @@
+use crate::config::{SeasonConfig, SimulationConfig};
+use std::f32::consts::TAU;
+
+/// Point in the seasonal cycle at a given step. Seasonal parameters swing
+/// sinusoidally around their configured base value, so each averages to the base
+/// over a full period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Season {
+    phase: f32,
+}
+
+impl Season {
+    pub fn at(step: u32, config: &SeasonConfig) -> Self {
+        let phase = if config.enabled {
+            let period = config.period.max(1);
+            (step % period) as f32 / period as f32
+        } else {
+            0.0
+        };
+        Self { phase }
+    }
+
+    /// Progress through the current cycle, in [0, 1)
+    pub fn phase(&self) -> f32 {
+        self.phase
+    }
+
+    /// Multiplier on a base value for a swing of `amplitude` (0.5 = ±50%)
+    pub fn factor(&self, amplitude: f32) -> f32 {
+        1.0 + amplitude * (TAU * self.phase).sin()
+    }
+
+    /// Copy of `config` with seasonal parameters set to their value for this season
+    pub fn apply(&self, config: &SimulationConfig) -> SimulationConfig {
+        let mut effective = config.clone();
+        if config.seasons.enabled {
+            let seasons = &config.seasons;
+            effective.energy.movement_energy_cost *= self.factor(seasons.movement_cost_amplitude);
+            effective.physics.center_pressure_strength *=
+                self.factor(seasons.center_pressure_amplitude);
+        }
+        effective
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seasonal_cost_averages_to_base_over_period() {
+        let mut config = SimulationConfig::default();
+        config.seasons.enabled = true;
+        config.seasons.period = 400;
+        config.seasons.movement_cost_amplitude = 0.8;
+        let base = config.energy.movement_energy_cost;
+
+        let costs: Vec<f32> = (0..config.seasons.period)
+            .map(|step| {
+                Season::at(step, &config.seasons)
+                    .apply(&config)
+                    .energy
+                    .movement_energy_cost
+            })
+            .collect();
+        let average = costs.iter().sum::<f32>() / costs.len() as f32;
+
+        assert!((average - base).abs() < 1e-4, "average cost: {}", average);
+        let peak = costs.iter().copied().fold(f32::MIN, f32::max);
+        assert!((peak - base * 1.8).abs() < 1e-3, "peak cost: {}", peak);
+    }
+
+    #[test]
+    fn test_disabled_seasons_leave_config_unchanged() {
+        let config = SimulationConfig::default();
+        let season = Season::at(123, &config.seasons);
+        assert_eq!(season.phase(), 0.0);
+        assert_eq!(
+            season.apply(&config).energy.movement_energy_cost,
+            config.energy.movement_energy_cost
+        );
+    }
+}
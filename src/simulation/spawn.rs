@@ -0,0 +1,134 @@
+use crate::components::Position;
+use crate::config::SpawnPattern;
+use crate::sampling::random_in_disc;
+use rand::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+/// Starting positions for `count` entities laid out in `pattern` within `radius` of the origin
+pub fn spawn_positions(
+    pattern: &SpawnPattern,
+    count: usize,
+    radius: f32,
+    rng: &mut impl Rng,
+) -> Vec<Position> {
+    (0..count)
+        .map(|i| match pattern {
+            SpawnPattern::Disc => point_in_disc(0.0, 0.0, radius, rng),
+            SpawnPattern::UniformSquare => Position {
+                x: rng.gen_range(-radius..=radius),
+                y: rng.gen_range(-radius..=radius),
+            },
+            SpawnPattern::Grid => grid_point(i, count, radius),
+            SpawnPattern::Clusters { n } => cluster_point(i, (*n).max(1), radius, rng),
+            SpawnPattern::Ring => {
+                let angle = rng.gen_range(0.0..TAU);
+                let distance = radius * rng.gen_range(0.9..=1.0);
+                Position {
+                    x: distance * angle.cos(),
+                    y: distance * angle.sin(),
+                }
+            }
+        })
+        .collect()
+}
+
+fn point_in_disc(center_x: f32, center_y: f32, radius: f32, rng: &mut impl Rng) -> Position {
+    let (dx, dy) = random_in_disc(rng, radius);
+    Position {
+        x: center_x + dx,
+        y: center_y + dy,
+    }
+}
+
+/// Square lattice filled row by row, centred on the origin
+fn grid_point(index: usize, count: usize, radius: f32) -> Position {
+    let side = (count as f32).sqrt().ceil().max(1.0) as usize;
+    let spacing = 2.0 * radius / side as f32;
+    let offset = -radius + spacing / 2.0;
+    Position {
+        x: offset + (index % side) as f32 * spacing,
+        y: offset + (index / side) as f32 * spacing,
+    }
+}
+
+/// Entities are dealt round-robin to `n` groups evenly spaced on a circle
+fn cluster_point(index: usize, n: usize, radius: f32, rng: &mut impl Rng) -> Position {
+    let orbit = if n == 1 { 0.0 } else { radius * 0.6 };
+    let cluster_radius = if n == 1 {
+        radius * 0.2
+    } else {
+        (orbit * (PI / n as f32).sin() * 0.5).min(radius * 0.2)
+    };
+
+    let angle = TAU * (index % n) as f32 / n as f32;
+    point_in_disc(
+        orbit * angle.cos(),
+        orbit * angle.sin(),
+        cluster_radius,
+        rng,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_groups(positions: &[Position], link_distance: f32) -> usize {
+        // Single-linkage clustering via union-find
+        let mut parent: Vec<usize> = (0..positions.len()).collect();
+        fn root(parent: &mut [usize], i: usize) -> usize {
+            let mut i = i;
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        for i in 0..positions.len() {
+            for j in (i + 1)..positions.len() {
+                let (a, b) = (&positions[i], &positions[j]);
+                if (a.x - b.x).powi(2) + (a.y - b.y).powi(2) < link_distance.powi(2) {
+                    let (ri, rj) = (root(&mut parent, i), root(&mut parent, j));
+                    parent[ri] = rj;
+                }
+            }
+        }
+        (0..positions.len())
+            .filter(|&i| root(&mut parent, i) == i)
+            .count()
+    }
+
+    #[test]
+    fn test_grid_centered_on_origin() {
+        let positions = spawn_positions(&SpawnPattern::Grid, 100, 50.0, &mut thread_rng());
+
+        let mean_x = positions.iter().map(|p| p.x).sum::<f32>() / 100.0;
+        let mean_y = positions.iter().map(|p| p.y).sum::<f32>() / 100.0;
+        assert!(mean_x.abs() < 1e-3 && mean_y.abs() < 1e-3);
+        assert!(positions
+            .iter()
+            .all(|p| p.x.abs() <= 50.0 && p.y.abs() <= 50.0));
+    }
+
+    #[test]
+    fn test_clusters_form_requested_groups() {
+        let radius = 100.0;
+        for n in [2, 4, 6] {
+            let pattern = SpawnPattern::Clusters { n };
+            let positions = spawn_positions(&pattern, 100 * n, radius, &mut thread_rng());
+            assert_eq!(count_groups(&positions, radius * 0.15), n);
+        }
+    }
+
+    #[test]
+    fn test_ring_and_disc_stay_within_radius() {
+        let mut rng = thread_rng();
+        let ring = spawn_positions(&SpawnPattern::Ring, 200, 50.0, &mut rng);
+        let disc = spawn_positions(&SpawnPattern::Disc, 200, 50.0, &mut rng);
+
+        let distance = |p: &Position| (p.x * p.x + p.y * p.y).sqrt();
+        assert!(ring.iter().all(|p| (44.9..=50.01).contains(&distance(p))));
+        assert!(disc.iter().all(|p| distance(p) <= 50.01));
+    }
+}
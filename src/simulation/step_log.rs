@@ -0,0 +1,257 @@
+use super::Simulation;
+use crate::components::Energy;
+use crate::genes::Genes;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+
+/// First bytes of every step log, naming the format and its version
+const MAGIC: &[u8; 8] = b"EVOSTEP1";
+
+/// Summary of one step as stored in a step log: `RECORD_SIZE` bytes, each field a
+/// little-endian u32 or f32 in declaration order
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StepRecord {
+    pub step: u32,
+    pub population: u32,
+    pub average_energy: f32,
+    pub average_speed: f32,
+    pub average_size: f32,
+    pub average_sense_radius: f32,
+}
+
+/// Bytes per `StepRecord`; records follow the header back to back, so record `i` is at
+/// `MAGIC.len() + i * RECORD_SIZE`
+pub const RECORD_SIZE: usize = 24;
+
+impl StepRecord {
+    /// Summary of `sim` as it is now, cheap enough to take every step
+    pub fn of(sim: &Simulation) -> Self {
+        let world = sim.world();
+        let mut record = Self {
+            step: sim.step(),
+            population: world.len(),
+            ..Self::default()
+        };
+        if record.population == 0 {
+            return record;
+        }
+        for (_, (energy, genes)) in world.query::<(&Energy, &Genes)>().iter() {
+            record.average_energy += energy.current;
+            record.average_speed += genes.speed();
+            record.average_size += genes.size_factor();
+            record.average_sense_radius += genes.sense_radius();
+        }
+        let n = record.population as f32;
+        record.average_energy /= n;
+        record.average_speed /= n;
+        record.average_size /= n;
+        record.average_sense_radius /= n;
+        record
+    }
+
+    fn to_bytes(self) -> [u8; RECORD_SIZE] {
+        let words = [
+            self.step.to_le_bytes(),
+            self.population.to_le_bytes(),
+            self.average_energy.to_le_bytes(),
+            self.average_speed.to_le_bytes(),
+            self.average_size.to_le_bytes(),
+            self.average_sense_radius.to_le_bytes(),
+        ];
+        let mut bytes = [0; RECORD_SIZE];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&word);
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let word = |i: usize| bytes[i * 4..][..4].try_into().unwrap_or_default();
+        Self {
+            step: u32::from_le_bytes(word(0)),
+            population: u32::from_le_bytes(word(1)),
+            average_energy: f32::from_le_bytes(word(2)),
+            average_speed: f32::from_le_bytes(word(3)),
+            average_size: f32::from_le_bytes(word(4)),
+            average_sense_radius: f32::from_le_bytes(word(5)),
+        }
+    }
+}
+
+/// Appends step records to a file as the simulation runs, so runs too long to keep in
+/// memory can be analysed afterwards with a `StepLogReader`
+pub struct StepLogWriter {
+    writer: BufWriter<File>,
+    interval: u32,
+    records: usize,
+    error: Option<io::Error>, // First write failure; later records are dropped
+}
+
+impl StepLogWriter {
+    /// Start a log at `path` that takes a record every `interval` steps (at least 1)
+    pub fn create<P: AsRef<Path>>(path: P, interval: u32) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        Ok(Self {
+            writer,
+            interval: interval.max(1),
+            records: 0,
+            error: None,
+        })
+    }
+
+    pub fn append(&mut self, record: &StepRecord) -> io::Result<()> {
+        self.writer.write_all(&record.to_bytes())?;
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Records written so far
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Flush the file, reporting the first error met while recording
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        self.writer.flush()
+    }
+
+    /// Record `sim` if this is one of the logged steps, holding any error for `finish`
+    fn record(&mut self, sim: &Simulation) {
+        if self.error.is_none() && sim.step() % self.interval == 0 {
+            if let Err(error) = self.append(&StepRecord::of(sim)) {
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+/// Random access to the records of a step log, reading only the requested ones from disk
+pub struct StepLogReader {
+    file: File,
+    len: usize,
+}
+
+impl StepLogReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut magic = [0; MAGIC.len()];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a step log"));
+        }
+        let body = file.metadata()?.len() as usize - MAGIC.len();
+        Ok(Self {
+            file,
+            len: body / RECORD_SIZE, // A partly written last record is ignored
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Records with indices in `range`, clipped to the log
+    pub fn read(&mut self, range: Range<usize>) -> io::Result<Vec<StepRecord>> {
+        let (start, end) = (range.start.min(self.len), range.end.min(self.len));
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        let offset = MAGIC.len() + start * RECORD_SIZE;
+        self.file.seek(SeekFrom::Start(offset as u64))?;
+        let mut bytes = vec![0; (end - start) * RECORD_SIZE];
+        self.file.read_exact(&mut bytes)?;
+        Ok(bytes
+            .chunks_exact(RECORD_SIZE)
+            .map(StepRecord::from_bytes)
+            .collect())
+    }
+}
+
+impl Simulation {
+    /// Stream a record of each logged step to `log` from now on, replacing any earlier log
+    pub fn set_step_log(&mut self, log: StepLogWriter) {
+        self.step_log = Some(log);
+    }
+
+    /// Stop recording and hand back the log, to `finish` it
+    pub fn take_step_log(&mut self) -> Option<StepLogWriter> {
+        self.step_log.take()
+    }
+
+    pub(super) fn record_step(&mut self) {
+        if let Some(mut log) = self.step_log.take() {
+            log.record(self);
+            self.step_log = Some(log);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+
+    #[test]
+    fn test_step_log_reads_back_slices_of_a_thousand_records() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = StepLogWriter::create(temp_file.path(), 1).unwrap();
+        let record = |i: u32| StepRecord {
+            step: i,
+            population: 1000 - i,
+            average_energy: i as f32 * 0.5,
+            average_speed: 1.0 / (i + 1) as f32,
+            average_size: -(i as f32),
+            average_sense_radius: i as f32 * 1.25,
+        };
+        for i in 0..1000 {
+            writer.append(&record(i)).unwrap();
+        }
+        assert_eq!(writer.records(), 1000);
+        writer.finish().unwrap();
+
+        let mut reader = StepLogReader::open(temp_file.path()).unwrap();
+        assert_eq!(reader.len(), 1000);
+        let all = reader.read(0..1000).unwrap();
+        assert_eq!(all, (0..1000).map(record).collect::<Vec<_>>());
+        let slice = reader.read(500..510).unwrap();
+        assert_eq!(slice, (500..510).map(record).collect::<Vec<_>>());
+        assert_eq!(reader.read(995..2000).unwrap().len(), 5);
+        assert!(reader.read(1000..1001).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_simulation_streams_records_at_the_log_interval() {
+        let mut sim = Simulation::new_seeded(200.0, SimulationConfig::default(), 5);
+        sim.set_log_interval(0);
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        sim.set_step_log(StepLogWriter::create(temp_file.path(), 2).unwrap());
+        for _ in 0..6 {
+            sim.update();
+        }
+        sim.take_step_log().unwrap().finish().unwrap();
+
+        let mut reader = StepLogReader::open(temp_file.path()).unwrap();
+        let records = reader.read(0..reader.len()).unwrap();
+        let steps: Vec<u32> = records.iter().map(|r| r.step).collect();
+        assert_eq!(steps, [2, 4, 6]);
+        assert_eq!(records[2], StepRecord::of(&sim));
+        assert!(records.iter().all(|r| r.population > 0));
+    }
+
+    #[test]
+    fn test_reader_rejects_other_files() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp_file.path(), b"{\"not\": \"a step log\"}").unwrap();
+        assert!(StepLogReader::open(temp_file.path()).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use super::Simulation;
+use std::collections::VecDeque;
+
+/// When a long unattended run should end before its step budget
+#[derive(Debug, Clone, Default)]
+pub struct StopConditions {
+    pub stop_on_extinction: bool,
+    pub stagnation: Option<StagnationCheck>, // Stop once the population sits at the cap
+}
+
+/// A population that has filled up to the cap and stopped changing for `window` steps
+#[derive(Debug, Clone)]
+pub struct StagnationCheck {
+    pub window: usize,
+    pub cap_fraction: f32, // Population must stay at or above this fraction of the cap
+    pub tolerance: f32,    // Max spread over the window, as a fraction of the cap
+}
+
+impl Default for StagnationCheck {
+    fn default() -> Self {
+        Self {
+            window: 500,
+            cap_fraction: 0.9,
+            tolerance: 0.02,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopReason {
+    Extinct { step: u32 },
+    Stagnant { step: u32 },
+}
+
+impl StagnationCheck {
+    fn is_stagnant(&self, history: &VecDeque<usize>, cap: usize) -> bool {
+        if history.len() < self.window.max(1) {
+            return false;
+        }
+        let min = *history.iter().min().unwrap_or(&0) as f32;
+        let max = *history.iter().max().unwrap_or(&0) as f32;
+        min >= cap as f32 * self.cap_fraction && max - min <= cap as f32 * self.tolerance
+    }
+}
+
+impl Simulation {
+    pub fn is_extinct(&self) -> bool {
+        self.world.is_empty()
+    }
+
+    /// Step for up to `max_steps`, ending early when a stop condition is met.
+    /// Returns why the run stopped, or `None` if it used its whole budget.
+    pub fn run(&mut self, max_steps: u32, stop: &StopConditions) -> Option<StopReason> {
+        let cap = (self.config.population.max_population as f32
+            * self.config.population.entity_scale) as usize;
+        let mut history = VecDeque::new();
+
+        for _ in 0..max_steps {
+            if stop.stop_on_extinction && self.is_extinct() {
+                return Some(StopReason::Extinct { step: self.step });
+            }
+            self.update();
+
+            if let Some(check) = &stop.stagnation {
+                history.push_back(self.world.len() as usize);
+                if history.len() > check.window.max(1) {
+                    history.pop_front();
+                }
+                if check.is_stagnant(&history, cap) {
+                    return Some(StopReason::Stagnant { step: self.step });
+                }
+            }
+        }
+
+        (stop.stop_on_extinction && self.is_extinct())
+            .then_some(StopReason::Extinct { step: self.step })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+
+    #[test]
+    fn test_extinct_world_stops_run() {
+        let mut config = SimulationConfig::default();
+        config.population.initial_entities = 0;
+        let mut sim = Simulation::new_with_config(100.0, config);
+        assert!(sim.is_extinct());
+
+        let stop = StopConditions {
+            stop_on_extinction: true,
+            ..Default::default()
+        };
+        assert_eq!(sim.run(100, &stop), Some(StopReason::Extinct { step: 0 }));
+        assert_eq!(sim.step(), 0);
+
+        // Without the flag the loop keeps stepping the empty world
+        assert_eq!(sim.run(10, &StopConditions::default()), None);
+        assert_eq!(sim.step(), 10);
+    }
+
+    #[test]
+    fn test_population_at_cap_is_stagnant() {
+        let check = StagnationCheck {
+            window: 3,
+            ..Default::default()
+        };
+        let full: VecDeque<usize> = [995, 1000, 998].into();
+        let growing: VecDeque<usize> = [900, 950, 1000].into();
+        let short: VecDeque<usize> = [1000, 1000].into();
+
+        assert!(check.is_stagnant(&full, 1000));
+        assert!(!check.is_stagnant(&growing, 1000));
+        assert!(!check.is_stagnant(&short, 1000));
+    }
+}
@@ -1,7 +1,8 @@
 use super::*;
-use crate::components::{Color, Energy, Position, Size, Velocity};
+use crate::components::{Color, Energy, Position, Size, Starving, Uid, Velocity};
 use crate::config::SimulationConfig;
-use crate::genes::Genes;
+use crate::genes::{Genes, Phenotype, GENE_BOUNDS};
+use events::{DeathCause, EventKind};
 use rand::thread_rng;
 
 #[test]
@@ -68,6 +69,81 @@ fn test_simulation_get_entities() {
     assert!(!entities.is_empty() || sim.world.is_empty());
 }
 
+#[test]
+fn test_metrics_logged_at_configured_interval() {
+    let mut sim = Simulation::new_seeded(200.0, SimulationConfig::default(), 3);
+    let logged = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = logged.clone();
+    sim.set_metrics_sink(Box::new(move |step, _| sink.lock().unwrap().push(step)));
+    sim.set_log_interval(10);
+    for _ in 0..35 {
+        sim.update();
+    }
+    assert_eq!(*logged.lock().unwrap(), vec![10, 20, 30]);
+
+    sim.set_log_interval(0);
+    for _ in 0..10 {
+        sim.update();
+    }
+    assert_eq!(logged.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn test_step_duration_is_measured_and_overruns_skip_stats() {
+    let mut config = SimulationConfig::default();
+    config.timing.step_budget_ms = 1e-6; // Every step overruns
+    config.timing.skip_stats_over_budget = true;
+    let mut sim = Simulation::new_seeded(200.0, config, 3);
+    assert_eq!(sim.last_step_duration(), std::time::Duration::ZERO);
+    let logged = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = logged.clone();
+    sim.set_metrics_sink(Box::new(move |step, _| sink.lock().unwrap().push(step)));
+    sim.set_log_interval(1);
+
+    sim.update();
+    let seconds = sim.last_step_duration().as_secs_f64();
+    assert!(seconds.is_finite() && seconds > 0.0, "{}", seconds);
+    assert!(logged.lock().unwrap().is_empty());
+
+    let mut config = sim.config.clone();
+    config.timing.skip_stats_over_budget = false;
+    sim.update_config(config);
+    sim.update();
+    assert_eq!(*logged.lock().unwrap(), vec![2]);
+}
+
+#[test]
+fn test_raised_death_threshold_kills_weak_entities() {
+    let survivors = |threshold: f32| {
+        let mut config = SimulationConfig::default();
+        config.reproduction.death_chance_factor = 0.0;
+        config.energy.death_energy_threshold = threshold;
+        let mut sim = Simulation::new_seeded(300.0, config, 8);
+        for (_, energy) in sim.world.query_mut::<&mut Energy>() {
+            energy.current = 5.0;
+        }
+        sim.update();
+        sim.world.len()
+    };
+
+    let default_survivors = survivors(0.0);
+    assert!(default_survivors > 0);
+    assert_eq!(survivors(10.0), 0);
+}
+
+#[test]
+fn test_get_entities_sorted_is_stable() {
+    let mut sim = Simulation::new_seeded(300.0, SimulationConfig::default(), 5);
+    for _ in 0..5 {
+        sim.update();
+    }
+
+    let first = sim.get_entities_sorted();
+    let second = sim.get_entities_sorted();
+    assert_eq!(first.len(), sim.world.len() as usize);
+    assert_eq!(first, second);
+}
+
 #[test]
 fn test_simulation_get_interpolated_entities() {
     let sim = Simulation::new(100.0);
@@ -154,6 +230,45 @@ fn test_simulation_empty_world() {
     assert_eq!(sim.world.len(), 0);
 }
 
+#[test]
+fn test_extinct_simulation_keeps_finite_stats() {
+    let mut config = SimulationConfig::default();
+    config.population.max_population = 0; // Zero cap, the worst case for density
+    let mut sim = Simulation::new_with_config(100.0, config);
+    sim.world.clear();
+
+    for _ in 0..10 {
+        sim.update();
+    }
+
+    let stats = crate::stats::SimulationStats::from_world(&sim.world, 0.0, 1.0, sim.species(), 0.0);
+    let metrics = &stats.average_metrics;
+    for value in [
+        stats.population_cap_fraction,
+        stats.spatial_density,
+        stats.world_center_drift.0,
+        stats.world_center_drift.1,
+        stats.average_heading.0,
+        stats.average_heading.1,
+        metrics.average_energy,
+        metrics.average_speed,
+        metrics.average_size,
+        metrics.average_reproduction_rate,
+        metrics.average_sense_radius,
+        metrics.average_energy_efficiency,
+    ] {
+        assert_eq!(value, 0.0);
+    }
+    assert!(stats.species.is_empty());
+    let report = stats.format_detailed(sim.step());
+    assert!(
+        !report.contains("NaN") && !report.contains("inf"),
+        "{}",
+        report
+    );
+    assert!(sim.timing_report().iter().all(|(_, ms)| ms.is_finite()));
+}
+
 #[test]
 fn test_simulation_large_world() {
     let mut config = SimulationConfig::default();
@@ -190,6 +305,8 @@ fn test_simulation_entity_processing() {
             separation_distance: 10.0,
             alignment_strength: 0.5,
             cohesion_strength: 0.5,
+            migration_heading: 0.0,
+            flock_kin_threshold: 0.7,
         },
     ));
 
@@ -221,6 +338,8 @@ fn test_simulation_apply_updates() {
             separation_distance: 12.0,
             alignment_strength: 0.6,
             cohesion_strength: 0.6,
+            migration_heading: 0.0,
+            flock_kin_threshold: 0.7,
         },
     ));
 
@@ -233,28 +352,26 @@ fn test_simulation_apply_updates() {
         },
         size: Size { radius: 6.0 },
         genes: Genes::new_random(&mut thread_rng()),
-        color: Color {
-            r: 0.0,
-            g: 1.0,
-            b: 0.0,
-        },
         velocity: Velocity { x: 1.0, y: 1.0 },
-        movement_style: crate::components::MovementStyle {
-            style: crate::components::MovementType::Flocking,
-            flocking_strength: 0.7,
-            separation_distance: 12.0,
-            alignment_strength: 0.6,
-            cohesion_strength: 0.6,
-        },
         should_reproduce: false,
-        eaten_entity: None,
+        ready_to_reproduce: false,
+        eaten_entities: Vec::new(),
+        child_genotype: None,
+        child_pedigree: None,
+        repro_cooldown: 0,
+        inherited_energy: None,
+        birth_energy: 60.0,
+        starving_steps: 0,
+        crowded: false,
+        gift: None,
     }];
 
     sim.apply_entity_updates(updates);
 
-    // Entity should be updated
-    // Note: We can't easily test this due to borrowing rules
-    // In a real scenario, you'd need to restructure the code
+    // Entity should be updated in place, keeping its id
+    let pos = sim.world.get::<&Position>(_entity).unwrap();
+    assert_eq!((pos.x, pos.y), (10.0, 10.0));
+    assert_eq!(sim.world.get::<&Energy>(_entity).unwrap().current, 60.0);
 }
 
 #[test]
@@ -278,7 +395,7 @@ fn test_simulation_clustering() {
             let mut max_x = f32::NEG_INFINITY;
             let mut max_y = f32::NEG_INFINITY;
 
-            for (_px, _py, cx, cy, _, _, _, _) in &entities {
+            for (_px, _py, cx, cy, _, _, _, _, _) in &entities {
                 total_x += cx;
                 total_y += cy;
                 min_x = min_x.min(*cx);
@@ -332,7 +449,7 @@ fn test_drift_direction_analysis() {
             let mut total_x = 0.0;
             let mut total_y = 0.0;
 
-            for (_px, _py, cx, cy, _, _, _, _) in &entities {
+            for (_px, _py, cx, cy, _, _, _, _, _) in &entities {
                 total_x += cx;
                 total_y += cy;
             }
@@ -387,8 +504,9 @@ fn test_entity_data_format() {
     let sim = Simulation::new(100.0);
     let entities = sim.get_entities();
 
-    // Each entity should have 8 components: prev_x, prev_y, cur_x, cur_y, radius, r, g, b
-    for (_px, _py, cx, cy, radius, r, g, b) in &entities {
+    // Each entity should have 9 components: prev_x, prev_y, cur_x, cur_y, radius, r, g, b,
+    // energy fraction
+    for (_px, _py, cx, cy, radius, r, g, b, energy) in &entities {
         // Position should be within world bounds
         assert!(*cx >= -50.0 && *cx <= 50.0, "cx={} out of bounds", cx);
         assert!(*cy >= -50.0 && *cy <= 50.0, "cy={} out of bounds", cy);
@@ -400,6 +518,12 @@ fn test_entity_data_format() {
         assert!(*r >= 0.0 && *r <= 1.0, "r={} out of color range", r);
         assert!(*g >= 0.0 && *g <= 1.0, "g={} out of color range", g);
         assert!(*b >= 0.0 && *b <= 1.0, "b={} out of color range", b);
+
+        assert!(
+            (0.0..=1.0).contains(energy),
+            "energy={} out of range",
+            energy
+        );
     }
 }
 
@@ -411,8 +535,8 @@ fn test_entity_buffer_conversion() {
     let entities = sim.get_entities();
 
     // Convert to flat buffer (same as update_entity_buffer)
-    let mut buffer: Vec<f32> = Vec::with_capacity(entities.len() * 8);
-    for (px, py, cx, cy, radius, r, g, b) in entities.iter() {
+    let mut buffer: Vec<f32> = Vec::with_capacity(entities.len() * 9);
+    for (px, py, cx, cy, radius, r, g, b, energy) in entities.iter() {
         buffer.push(*px);
         buffer.push(*py);
         buffer.push(*cx);
@@ -421,18 +545,19 @@ fn test_entity_buffer_conversion() {
         buffer.push(*r);
         buffer.push(*g);
         buffer.push(*b);
+        buffer.push(*energy);
     }
 
-    // Buffer length should be 8 * entity count
-    assert_eq!(buffer.len(), entities.len() * 8);
+    // Buffer length should be 9 * entity count
+    assert_eq!(buffer.len(), entities.len() * 9);
 
     // Entity count calculation should match
-    let entity_count = buffer.len() / 8;
+    let entity_count = buffer.len() / 9;
     assert_eq!(entity_count, entities.len());
 
     // Verify data integrity by reading back
-    for (i, (px, py, cx, cy, radius, r, g, b)) in entities.iter().enumerate() {
-        let base = i * 8;
+    for (i, (px, py, cx, cy, radius, r, g, b, energy)) in entities.iter().enumerate() {
+        let base = i * 9;
         assert_eq!(buffer[base], *px);
         assert_eq!(buffer[base + 1], *py);
         assert_eq!(buffer[base + 2], *cx);
@@ -441,6 +566,7 @@ fn test_entity_buffer_conversion() {
         assert_eq!(buffer[base + 5], *r);
         assert_eq!(buffer[base + 6], *g);
         assert_eq!(buffer[base + 7], *b);
+        assert_eq!(buffer[base + 8], *energy);
     }
 }
 
@@ -456,3 +582,1390 @@ fn test_update_config() {
     assert_ne!(sim.config.physics.max_velocity, original_velocity);
     assert_eq!(sim.config.physics.max_velocity, 5.0);
 }
+
+#[test]
+fn test_diploid_mode_keeps_genotypes() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 100;
+    config.genetics.diploid = true;
+
+    let mut sim = Simulation::new_with_config(200.0, config);
+    for _ in 0..5 {
+        sim.update();
+    }
+
+    // Every entity carries a genotype whose expression is its phenotype
+    for (_, (genes, genotype)) in sim.world.query::<(&Genes, &DiploidGenes)>().iter() {
+        let expressed = genotype.express(sim.config.genetics.dominance);
+        assert_eq!(genes.speed(), expressed.speed());
+    }
+    assert_eq!(
+        sim.world.query::<&DiploidGenes>().iter().count(),
+        sim.world.len() as usize
+    );
+}
+
+#[test]
+fn test_population_cap_enforced_near_capacity() {
+    let mut config = SimulationConfig::default();
+    config.population.entity_scale = 1.0;
+    config.population.max_population = 100;
+    config.population.initial_entities = 95;
+    config.reproduction.reproduction_energy_threshold = 0.0;
+    config.reproduction.death_chance_factor = 0.0;
+
+    let mut sim = Simulation::new_with_config(500.0, config);
+    // Every entity wants to reproduce every step
+    for (_, genes) in sim.world.query_mut::<&mut Genes>() {
+        genes.reproduction.rate = 1.0;
+    }
+
+    for _ in 0..5 {
+        sim.update();
+        assert!(sim.world.len() <= 100, "population {}", sim.world.len());
+    }
+}
+
+#[test]
+fn test_generational_mode_resamples_fixed_size_population() {
+    let mut config = SimulationConfig::default();
+    config.population.entity_scale = 1.0;
+    config.population.initial_entities = 50;
+    config.genetics.generational = true;
+    config.genetics.generation_steps = 3;
+
+    let mut sim = Simulation::new_with_config(300.0, config);
+    for _ in 0..2 {
+        sim.update();
+    }
+    assert!(sim.world.len() <= 50, "no births within a generation");
+
+    sim.update();
+    assert_eq!(sim.world.len(), 50);
+}
+
+#[test]
+fn test_survivors_keep_ids_and_previous_positions() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 50;
+    config.reproduction.death_chance_factor = 0.0;
+
+    let mut sim = Simulation::new_with_config(300.0, config);
+    let before: HashSet<Entity> = sim.world.iter().map(|e| e.entity()).collect();
+    sim.update();
+    sim.update();
+
+    // Survivors are updated in place, so they can be interpolated from their last position
+    let survivors: Vec<Entity> = sim
+        .world
+        .iter()
+        .map(|e| e.entity())
+        .filter(|entity| before.contains(entity))
+        .collect();
+    assert!(!survivors.is_empty());
+    assert!(survivors
+        .iter()
+        .all(|entity| sim.previous_positions.contains_key(entity)));
+}
+
+#[test]
+fn test_inject_genes_spawns_copies() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    let mut sim = Simulation::new_with_config(300.0, config);
+
+    let mut genes = Genes::new_random(&mut thread_rng());
+    genes.movement.speed = 1.25;
+    sim.inject_genes(&genes, 7);
+
+    assert_eq!(sim.world.len(), 7);
+    for (_, genes) in sim.world.query::<&Genes>().iter() {
+        assert_eq!(genes.speed(), 1.25);
+    }
+}
+
+#[test]
+fn test_injected_genes_respect_locked_traits() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    config.genetics.diploid = true;
+    config
+        .genetics
+        .locked_genes
+        .insert("speed".to_string(), 0.5);
+    let mut sim = Simulation::new_with_config(300.0, config);
+
+    let mut genes = Genes::new_random(&mut thread_rng());
+    genes.movement.speed = 1.25;
+    sim.inject_genes(&genes, 3);
+
+    for (_, (genes, genotype)) in sim.world.query::<(&Genes, &DiploidGenes)>().iter() {
+        assert_eq!(genes.speed(), 0.5);
+        assert_eq!((genotype.a.speed(), genotype.b.speed()), (0.5, 0.5));
+    }
+    assert_eq!(sim.world.len(), 3);
+}
+
+#[test]
+fn test_species_are_detected_again_once_the_world_changes() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    let mut sim = Simulation::new_with_config(300.0, config);
+    let population = |sim: &Simulation| sim.species().iter().map(|s| s.size).sum::<usize>();
+    assert_eq!(population(&sim), 0);
+
+    sim.inject_genes(&Genes::new_random(&mut thread_rng()), 7);
+    assert_eq!(population(&sim), 7);
+    assert_eq!(sim.stats().species.len(), sim.species().len());
+
+    sim.update();
+    assert_eq!(population(&sim), sim.world.len() as usize);
+}
+
+#[test]
+fn test_entity_near_and_position_of() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    let mut sim = Simulation::new_with_config(300.0, config);
+    let genes = Genes::new_random(&mut thread_rng());
+    let entity = Simulation::spawn_entity(
+        &mut sim.world,
+        &mut thread_rng(),
+        genes,
+        Position { x: 10.0, y: 10.0 },
+        &sim.config,
+        &mut sim.next_uid,
+    );
+
+    assert_eq!(sim.entity_near(12.0, 10.0, 5.0), Some(entity));
+    assert_eq!(sim.entity_near(40.0, 40.0, 5.0), None);
+    assert_eq!(sim.position_of(entity).map(|p| p.x), Some(10.0));
+
+    sim.world.despawn(entity).unwrap();
+    assert!(sim.position_of(entity).is_none());
+}
+
+#[test]
+fn test_watched_config_swap_mid_run() {
+    let temp_file = tempfile::NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 50;
+    config.save_to_file(path).unwrap();
+
+    let mut watcher = crate::config::watch::ConfigWatcher::new(path);
+    let mut sim = Simulation::new_with_config(300.0, config.clone());
+    sim.update();
+
+    config.physics.max_velocity = 0.5;
+    config.save_to_file(path).unwrap();
+    let file = std::fs::File::options().write(true).open(path).unwrap();
+    let later = std::time::SystemTime::now() + std::time::Duration::from_secs(10);
+    file.set_modified(later).unwrap();
+
+    if let Some(new_config) = watcher.poll() {
+        sim.update_config(new_config);
+    }
+    assert_eq!(sim.config.physics.max_velocity, 0.5);
+    sim.update();
+    assert!(!sim.world.is_empty());
+}
+
+#[test]
+fn test_contested_prey_is_awarded_once() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    config.energy.max_energy_factor = 10.0; // Keep the ceiling out of the way
+    let mut sim = Simulation::new_with_config(100.0, config);
+    let mut rng = thread_rng();
+
+    let mut prey_genes = Genes::new_random(&mut rng);
+    prey_genes.movement.speed = 1.0;
+    let mut predator_genes = Genes::new_random(&mut rng);
+    predator_genes.movement.speed = 2.0;
+
+    let energy = |current| Energy {
+        current,
+        max: 100.0,
+    };
+    let prey = sim.world.spawn((
+        Position { x: 0.0, y: 0.0 },
+        energy(20.0),
+        Size { radius: 2.0 },
+        prey_genes,
+    ));
+    let predators: Vec<(Entity, Position)> = [-3.0, 3.0]
+        .into_iter()
+        .map(|x| {
+            let pos = Position { x, y: 0.0 };
+            let entity = sim.world.spawn((
+                pos.clone(),
+                energy(50.0),
+                Size { radius: 6.0 },
+                predator_genes.clone(),
+                Velocity { x: 0.0, y: 0.0 },
+            ));
+            (entity, pos)
+        })
+        .collect();
+
+    // Both predators see the same snapshot, so both claim the prey
+    sim.snapshot.rebuild(&sim.world);
+    let updates: Vec<EntityUpdate> = predators
+        .iter()
+        .map(|(entity, pos)| {
+            let mut new_energy = 50.0;
+            let mut eaten_entities = Vec::new();
+            let size = Size { radius: 6.0 };
+            sim.apply_interactions_to_entity(
+                &mut new_energy,
+                &mut eaten_entities,
+                pos,
+                &size,
+                &predator_genes,
+                &Phenotype::of(&predator_genes),
+                &[prey],
+            );
+            assert_eq!(eaten_entities.len(), 1);
+            EntityUpdate {
+                entity: *entity,
+                pos: pos.clone(),
+                energy: energy(new_energy),
+                size,
+                genes: predator_genes.clone(),
+                velocity: Velocity { x: 0.0, y: 0.0 },
+                should_reproduce: false,
+                ready_to_reproduce: false,
+                eaten_entities,
+                child_genotype: None,
+                child_pedigree: None,
+                repro_cooldown: 0,
+                inherited_energy: None,
+                birth_energy: new_energy,
+                starving_steps: 0,
+                crowded: false,
+                gift: None,
+            }
+        })
+        .collect();
+    let meal = updates[0].energy.current - 50.0;
+
+    sim.apply_entity_updates(updates);
+
+    let total: f32 = predators
+        .iter()
+        .map(|(entity, _)| sim.world.get::<&Energy>(*entity).unwrap().current)
+        .sum();
+    assert!((total - (100.0 + meal)).abs() < 1e-4, "total: {}", total);
+    assert!(!sim.world.contains(prey));
+}
+
+#[test]
+fn test_losing_a_contested_meal_undoes_the_birth_and_survival_it_paid_for() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    config.reproduction.reproduction_cooldown = 5;
+    let mut sim = Simulation::new_seeded(100.0, config.clone(), 8);
+    let genes = Genes::new_random(&mut StdRng::seed_from_u64(8));
+    let mut spawn = |x: f32| {
+        sim.world.spawn((
+            Position { x, y: 0.0 },
+            Energy {
+                current: 50.0,
+                max: 100.0,
+            },
+            Size { radius: 4.0 },
+            genes.clone(),
+            Velocity { x: 0.0, y: 0.0 },
+        ))
+    };
+    let (winner, loser, starved, prey) = (spawn(-20.0), spawn(20.0), spawn(0.0), spawn(40.0));
+
+    // Each claimed the same 30 energy meal; the parents decided to breed on 85 energy
+    let update = |entity: Entity, birth_energy: f32| {
+        let should_reproduce = birth_energy > 80.0;
+        let paid = config.reproduction.reproduction_energy_cost;
+        EntityUpdate {
+            entity,
+            pos: (*sim.world.get::<&Position>(entity).unwrap()).clone(),
+            energy: Energy {
+                current: if should_reproduce {
+                    birth_energy * paid
+                } else {
+                    birth_energy
+                },
+                max: 100.0,
+            },
+            size: Size { radius: 4.0 },
+            genes: genes.clone(),
+            velocity: Velocity { x: 0.0, y: 0.0 },
+            should_reproduce,
+            ready_to_reproduce: should_reproduce,
+            eaten_entities: vec![(prey, 30.0)],
+            child_genotype: None,
+            child_pedigree: None,
+            repro_cooldown: 0,
+            inherited_energy: None,
+            birth_energy,
+            starving_steps: 0,
+            crowded: false,
+            gift: None,
+        }
+    };
+    let updates = vec![
+        update(winner, 85.0),
+        update(loser, 85.0),
+        update(starved, 20.0),
+    ];
+    sim.apply_entity_updates(updates);
+
+    let energy = |entity| sim.world.get::<&Energy>(entity).unwrap().current;
+    assert!((energy(winner) - 85.0 * 0.7).abs() < 1e-4);
+    assert_eq!(energy(loser), 55.0);
+    assert!(sim.world.get::<&ReproCooldown>(winner).is_ok());
+    assert!(sim.world.get::<&ReproCooldown>(loser).is_err());
+    assert!(
+        !sim.world.contains(starved),
+        "a lost meal should leave it starving"
+    );
+    assert!(!sim.world.contains(prey));
+    assert_eq!(sim.world.len(), 3, "only the winner should have a child");
+}
+
+#[test]
+fn test_gift_cannot_save_kin_killed_by_crowding() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    let mut sim = Simulation::new_seeded(200.0, config, 3);
+    sim.set_event_logging(true);
+    let genes = Genes::new_random(&mut StdRng::seed_from_u64(3));
+    let mut spawn = |uid: u64| {
+        sim.world.spawn((
+            Position {
+                x: uid as f32 * 10.0,
+                y: 0.0,
+            },
+            Energy {
+                current: 50.0,
+                max: 100.0,
+            },
+            Size { radius: 4.0 },
+            genes.clone(),
+            Velocity { x: 0.0, y: 0.0 },
+            Uid(uid),
+        ))
+    };
+    let (donor, crowded, other_donor, starving) = (spawn(1), spawn(2), spawn(3), spawn(4));
+
+    // Two donors share with kin; one kin was culled by crowding, the other is starving
+    let update = |entity: Entity, energy: f32, crowded: bool, gift| EntityUpdate {
+        entity,
+        pos: (*sim.world.get::<&Position>(entity).unwrap()).clone(),
+        energy: Energy {
+            current: energy,
+            max: 100.0,
+        },
+        size: Size { radius: 4.0 },
+        genes: genes.clone(),
+        velocity: Velocity { x: 0.0, y: 0.0 },
+        should_reproduce: false,
+        ready_to_reproduce: false,
+        eaten_entities: Vec::new(),
+        child_genotype: None,
+        child_pedigree: None,
+        repro_cooldown: 0,
+        inherited_energy: None,
+        birth_energy: energy,
+        starving_steps: if energy > 0.0 { 0 } else { 1 },
+        crowded,
+        gift,
+    };
+    let mut updates = vec![
+        update(donor, 60.0, false, Some((crowded, 5.0))),
+        update(crowded, 0.0, true, None),
+        update(other_donor, 60.0, false, Some((starving, 5.0))),
+        update(starving, -2.0, false, None),
+    ];
+    Simulation::deliver_gifts(&mut updates);
+    assert_eq!(updates[1].energy.current, 0.0);
+    assert_eq!(updates[3].energy.current, 3.0);
+
+    sim.apply_entity_updates(updates);
+    assert!(!sim.world.contains(crowded));
+    assert!(sim.world.contains(starving));
+    let causes: Vec<DeathCause> = sim
+        .drain_events()
+        .iter()
+        .filter_map(|event| match event.kind {
+            EventKind::Death { cause, .. } => Some(cause),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(causes, [DeathCause::Crowding]);
+}
+
+#[test]
+fn test_siblings_born_together_do_not_overlap() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    for seed in 0..20 {
+        let mut sim = Simulation::new_seeded(200.0, config.clone(), seed);
+        let genes = Genes::new_random(&mut StdRng::seed_from_u64(seed));
+        let parents: Vec<Entity> = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+            .into_iter()
+            .map(|(x, y)| {
+                sim.world.spawn((
+                    Position { x, y },
+                    Energy {
+                        current: 30.0,
+                        max: 30.0,
+                    },
+                    Size { radius: 1.0 },
+                    genes.clone(),
+                    Velocity { x: 0.0, y: 0.0 },
+                ))
+            })
+            .collect();
+        let updates = parents
+            .iter()
+            .map(|&entity| EntityUpdate {
+                entity,
+                pos: (*sim.world.get::<&Position>(entity).unwrap()).clone(),
+                energy: Energy {
+                    current: 30.0,
+                    max: 30.0,
+                },
+                size: Size { radius: 1.0 },
+                genes: genes.clone(),
+                velocity: Velocity { x: 0.0, y: 0.0 },
+                should_reproduce: true,
+                ready_to_reproduce: true,
+                eaten_entities: Vec::new(),
+                child_genotype: None,
+                child_pedigree: None,
+                repro_cooldown: 0,
+                inherited_energy: None,
+                birth_energy: 30.0,
+                starving_steps: 0,
+                crowded: false,
+                gift: None,
+            })
+            .collect();
+        sim.apply_entity_updates(updates);
+
+        let children: Vec<(Position, f32)> = sim
+            .world
+            .query::<(&Position, &Size)>()
+            .iter()
+            .filter(|(entity, _)| !parents.contains(entity))
+            .map(|(_, (pos, size))| (pos.clone(), size.radius))
+            .collect();
+        assert_eq!(children.len(), 4);
+        for (i, (a, ra)) in children.iter().enumerate() {
+            for (b, rb) in &children[i + 1..] {
+                let distance = ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt();
+                assert!(distance >= ra + rb, "seed {}: siblings overlap", seed);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_known_predation_logs_one_predation_event() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    let mut sim = Simulation::new_seeded(100.0, config, 4);
+    let genes = Genes::new_random(&mut StdRng::seed_from_u64(4));
+    let mut spawn = |x: f32, uid: u64| {
+        sim.world.spawn((
+            Position { x, y: 0.0 },
+            Energy {
+                current: 50.0,
+                max: 100.0,
+            },
+            Size { radius: 4.0 },
+            genes.clone(),
+            Velocity { x: 0.0, y: 0.0 },
+            Uid(uid),
+        ))
+    };
+    let (predator, prey) = (spawn(0.0, 7), spawn(3.0, 8));
+    let meal = |sim: &mut Simulation| {
+        sim.apply_entity_updates(vec![EntityUpdate {
+            entity: predator,
+            pos: Position { x: 0.0, y: 0.0 },
+            energy: Energy {
+                current: 60.0,
+                max: 100.0,
+            },
+            size: Size { radius: 4.0 },
+            genes: genes.clone(),
+            velocity: Velocity { x: 0.0, y: 0.0 },
+            should_reproduce: false,
+            ready_to_reproduce: false,
+            eaten_entities: vec![(prey, 10.0)],
+            child_genotype: None,
+            child_pedigree: None,
+            repro_cooldown: 0,
+            inherited_energy: None,
+            birth_energy: 60.0,
+            starving_steps: 0,
+            crowded: false,
+            gift: None,
+        }]);
+    };
+
+    sim.set_event_logging(true);
+    meal(&mut sim);
+    let kinds: Vec<EventKind> = sim.drain_events().iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            EventKind::Predation {
+                predator: 7,
+                prey: 8
+            },
+            EventKind::Death {
+                entity: 8,
+                cause: DeathCause::Eaten
+            },
+        ]
+    );
+    assert!(sim.drain_events().is_empty());
+
+    // A real run logs births too, each naming a parent that existed
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 200;
+    let mut sim = Simulation::new_seeded(300.0, config, 4);
+    sim.set_log_interval(0);
+    sim.set_event_logging(true);
+    for _ in 0..20 {
+        sim.update();
+    }
+    let events = sim.drain_events();
+    let births = events.iter().filter_map(|event| match event.kind {
+        EventKind::Birth { child, parent } => Some((child, parent)),
+        _ => None,
+    });
+    assert!(births.clone().count() > 0);
+    assert!(births.clone().all(|(child, parent)| parent < child));
+    assert!(events.iter().all(|event| (1..=20).contains(&event.step)));
+
+    sim.set_event_logging(false);
+    sim.update();
+    assert!(sim.drain_events().is_empty());
+}
+
+#[test]
+fn test_symmetric_interactions_leave_one_survivor_of_mutual_predation() {
+    // Two entities that each claimed the other against the same snapshot
+    let run = |symmetric: bool| {
+        let mut config = SimulationConfig::default();
+        config.population.initial_entities = 0;
+        config.physics.symmetric_interactions = symmetric;
+        let mut sim = Simulation::new_seeded(100.0, config, 3);
+        let genes = Genes::new_random(&mut StdRng::seed_from_u64(3));
+        let pair: Vec<Entity> = [-2.0, 2.0]
+            .into_iter()
+            .map(|x| {
+                sim.world.spawn((
+                    Position { x, y: 0.0 },
+                    Energy {
+                        current: 50.0,
+                        max: 100.0,
+                    },
+                    Size { radius: 4.0 },
+                    genes.clone(),
+                    Velocity { x: 0.0, y: 0.0 },
+                ))
+            })
+            .collect();
+        let updates = [(pair[0], pair[1]), (pair[1], pair[0])]
+            .into_iter()
+            .map(|(predator, prey)| EntityUpdate {
+                entity: predator,
+                pos: (*sim.world.get::<&Position>(predator).unwrap()).clone(),
+                energy: Energy {
+                    current: 60.0,
+                    max: 100.0,
+                },
+                size: Size { radius: 4.0 },
+                genes: genes.clone(),
+                velocity: Velocity { x: 0.0, y: 0.0 },
+                should_reproduce: false,
+                ready_to_reproduce: false,
+                eaten_entities: vec![(prey, 10.0)],
+                child_genotype: None,
+                child_pedigree: None,
+                repro_cooldown: 0,
+                inherited_energy: None,
+                birth_energy: 60.0,
+                starving_steps: 0,
+                crowded: false,
+                gift: None,
+            })
+            .collect();
+        sim.apply_entity_updates(updates);
+        let survivors: Vec<(Entity, f32)> = pair
+            .iter()
+            .filter_map(|&entity| {
+                let energy = sim.world.get::<&Energy>(entity).ok()?.current;
+                Some((entity, energy))
+            })
+            .collect();
+        (pair, survivors)
+    };
+
+    let (_, survivors) = run(false);
+    assert!(
+        survivors.is_empty(),
+        "each claim stands without the second phase"
+    );
+
+    let (pair, survivors) = run(true);
+    let first = pair.iter().min_by_key(|entity| entity.to_bits()).unwrap();
+    assert_eq!(survivors, [(*first, 60.0)]);
+    assert_eq!(run(true).1, survivors);
+}
+
+#[test]
+fn test_set_gene_clamps_to_the_trait_bounds() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 5;
+    let mut sim = Simulation::new_seeded(100.0, config, 8);
+    let entity = sim.world.iter().next().unwrap().entity();
+    let speed_max = GENE_BOUNDS[Genes::trait_index("speed").unwrap()].max;
+
+    assert_eq!(
+        sim.set_gene(entity, "speed", speed_max + 10.0),
+        Some(speed_max)
+    );
+    assert_eq!(sim.world.get::<&Genes>(entity).unwrap().speed(), speed_max);
+    assert_eq!(
+        sim.mutate_entity(entity, "speed", -1.0),
+        Some(speed_max - 1.0)
+    );
+    assert_eq!(sim.mutate_entity(entity, "sense_radius", -1e6), Some(2.0));
+
+    assert_eq!(sim.set_gene(entity, "wingspan", 1.0), None);
+    sim.world.despawn(entity).unwrap();
+    assert_eq!(sim.set_gene(entity, "speed", 1.0), None);
+}
+
+#[test]
+fn test_cached_phenotype_matches_the_genes() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 40;
+    let mut sim = Simulation::new_seeded(200.0, config, 12);
+    for _ in 0..20 {
+        sim.update();
+    }
+    let entity = sim.world.iter().next().unwrap().entity();
+    sim.set_gene(entity, "hue", 0.9);
+
+    let same_color = |a: &Color, b: &Color| a.r == b.r && a.g == b.g && a.b == b.b;
+    let mut query = sim.world.query::<(&Genes, &Phenotype, &Color)>();
+    let cached: Vec<_> = query.iter().map(|(_, (g, p, c))| (g, p, c)).collect();
+    assert_eq!(cached.len(), sim.world.len() as usize);
+    for &(genes, phenotype, color) in &cached {
+        assert!(same_color(&phenotype.color, &genes.get_color()));
+        assert!(same_color(color, &genes.get_color()));
+    }
+    for pair in cached.windows(2) {
+        let (genes, phenotype, _) = pair[0];
+        let (other_genes, other_phenotype, _) = pair[1];
+        assert_eq!(
+            phenotype.similarity(other_phenotype),
+            genes.calculate_gene_similarity(other_genes)
+        );
+    }
+}
+
+#[test]
+fn test_spawned_entities_respect_energy_bounds() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 400;
+    config.energy.initial_energy_min = 40.0;
+    config.energy.initial_energy_max = 50.0;
+    config.energy.max_energy_ratio = 2.0;
+
+    let sim = Simulation::new_with_config(300.0, config);
+    let mut query = sim.world.query::<&Energy>();
+    let energies: Vec<&Energy> = query.iter().map(|(_, energy)| energy).collect();
+    assert!(!energies.is_empty());
+    for energy in energies {
+        assert!((40.0..=50.0).contains(&energy.current));
+        assert!((energy.max - energy.current * 2.0).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn test_migratory_population_drifts_along_heading() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    config.physics.center_pressure_strength = 0.0;
+    // Equal sizes and no random deaths, so the population stays put apart from migrating
+    config.energy.initial_energy_min = 50.0;
+    config.energy.initial_energy_max = 50.0;
+    config.reproduction.death_chance_factor = 0.0;
+    let mut sim = Simulation::new_with_config(2000.0, config);
+
+    let mut genes = Genes::new_random(&mut thread_rng());
+    genes.behavior.movement_style.style = crate::components::MovementType::Migratory;
+    genes.behavior.movement_style.migration_heading = std::f32::consts::FRAC_PI_2;
+    genes.movement.speed = 1.0;
+    genes.movement.sense_radius = 5.0;
+    genes.behavior.fearfulness = 0.0;
+    genes.energy.loss_rate = 0.05;
+    genes.reproduction.rate = 0.0001;
+    sim.inject_genes(&genes, 100);
+
+    let center_of_mass = |sim: &Simulation| {
+        let mut query = sim.world.query::<&Position>();
+        let positions: Vec<&Position> = query.iter().map(|(_, pos)| pos).collect();
+        let n = positions.len() as f32;
+        (
+            positions.iter().map(|p| p.x).sum::<f32>() / n,
+            positions.iter().map(|p| p.y).sum::<f32>() / n,
+        )
+    };
+
+    let mut previous = center_of_mass(&sim);
+    let start = previous;
+    for _ in 0..5 {
+        for _ in 0..6 {
+            sim.update();
+        }
+        let current = center_of_mass(&sim);
+        assert!(current.1 > previous.1, "{:?} -> {:?}", previous, current);
+        previous = current;
+    }
+    assert!(previous.1 - start.1 > 10.0);
+    assert!((previous.0 - start.0).abs() < (previous.1 - start.1) / 2.0);
+
+    // Offspring may carry mutated headings, but the mean still points north
+    let stats =
+        crate::stats::SimulationStats::from_world(&sim.world, 10000.0, 1.0, Vec::new(), 0.0);
+    let (hx, hy) = stats.average_heading;
+    assert!(hy > 0.9 && hx.abs() < 0.2, "heading: {:?}", (hx, hy));
+}
+
+#[test]
+fn test_timing_report_covers_all_phases() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 100;
+    let mut sim = Simulation::new_with_config(300.0, config);
+    for _ in 0..5 {
+        sim.update();
+    }
+
+    let report = sim.timing_report();
+    let phases: Vec<&str> = report.iter().map(|(phase, _)| *phase).collect();
+    assert_eq!(
+        phases,
+        [
+            "store_positions",
+            "rebuild_grid",
+            "process_entities",
+            "apply_updates"
+        ]
+    );
+    assert!(report.iter().all(|(_, ms)| ms.is_finite() && *ms >= 0.0));
+}
+
+#[test]
+fn test_region_queries_match_world_positions() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    let mut sim = Simulation::new_with_config(400.0, config.clone());
+    let mut rng = thread_rng();
+
+    let layout = [(0.0, 0.0), (30.0, 10.0), (-25.0, -25.0), (100.0, 100.0)];
+    let entities: Vec<Entity> = layout
+        .iter()
+        .map(|&(x, y)| {
+            let genes = Genes::new_random(&mut rng);
+            Simulation::spawn_entity(
+                &mut sim.world,
+                &mut rng,
+                genes,
+                Position { x, y },
+                &config,
+                &mut sim.next_uid,
+            )
+        })
+        .collect();
+
+    let ids = |found: Vec<(Entity, Position)>| -> Vec<Entity> {
+        found.into_iter().map(|(entity, _)| entity).collect()
+    };
+    let in_rect = sim.entities_in_rect(
+        &Position { x: -30.0, y: -30.0 },
+        &Position { x: 30.0, y: 10.0 },
+    );
+    assert_eq!(ids(in_rect), entities[..3].to_vec());
+    let in_circle = sim.entities_in_circle(&Position { x: 0.0, y: 0.0 }, 40.0);
+    assert_eq!(ids(in_circle), entities[..3].to_vec());
+
+    // After a step the results follow the entities' new positions
+    sim.update();
+    let everywhere = sim.entities_in_circle(&Position { x: 0.0, y: 0.0 }, 1000.0);
+    assert_eq!(everywhere.len(), sim.world.len() as usize);
+    for (entity, pos) in everywhere {
+        let current = sim.position_of(entity).unwrap();
+        assert_eq!((pos.x, pos.y), (current.x, current.y));
+    }
+}
+
+#[test]
+fn test_changing_the_grid_cell_size_rebuilds_the_grid() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 300;
+    config.population.entity_scale = 1.0;
+    config.physics.incremental_grid = true;
+    let mut sim = Simulation::new_seeded(300.0, config.clone(), 8);
+    sim.update();
+
+    config.physics.grid_cell_size = 7.0;
+    sim.update_config(config);
+    sim.rebuild_spatial_grid();
+    assert!(sim.grid_cells().iter().all(|cell| cell.size == 7.0));
+
+    let expected = SpatialGrid::new(7.0);
+    for (entity, pos) in sim.world.query::<&Position>().iter() {
+        expected.insert(entity, pos.x, pos.y);
+    }
+    let nearby = |grid: &SpatialGrid| {
+        let mut entities = grid.get_nearby_entities(0.0, 0.0, 10.0);
+        entities.sort_by_key(|entity| entity.to_bits());
+        entities
+    };
+    assert_eq!(nearby(&sim.grid), nearby(&expected));
+}
+
+#[test]
+fn test_incremental_grid_update_matches_full_rebuild() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 500;
+    config.population.entity_scale = 1.0;
+    let mut sim = Simulation::new_seeded(300.0, config, 5);
+    sim.rebuild_spatial_grid();
+
+    // Move everything far enough that many entities cross into another cell
+    let mut rng = StdRng::seed_from_u64(1);
+    for (_, pos) in sim.world.query_mut::<&mut Position>() {
+        pos.x += rng.gen_range(-30.0..30.0);
+        pos.y += rng.gen_range(-30.0..30.0);
+    }
+    let neighbors = |sim: &Simulation| -> Vec<Vec<Entity>> {
+        let mut queries: Vec<(Entity, f32, f32)> = sim
+            .world
+            .query::<&Position>()
+            .iter()
+            .map(|(entity, pos)| (entity, pos.x, pos.y))
+            .collect();
+        queries.sort_by_key(|(entity, _, _)| entity.to_bits());
+        queries
+            .iter()
+            .map(|&(_, x, y)| sim.grid.get_nearby_entities(x, y, 40.0))
+            .collect()
+    };
+
+    assert!(sim.update_grid_positions());
+    let incremental = neighbors(&sim);
+    sim.fill_grid();
+    let full = neighbors(&sim);
+    assert_eq!(incremental, full);
+
+    // A despawned entity forces a full rebuild
+    let victim = sim.world.iter().next().unwrap().entity();
+    sim.world.despawn(victim).unwrap();
+    assert!(!sim.update_grid_positions());
+}
+
+#[test]
+fn test_batch_removal_keeps_grid_and_world_consistent() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 1500;
+    config.population.entity_scale = 1.0;
+    let mut sim = Simulation::new_seeded(300.0, config, 8);
+
+    let mut meals = 0;
+    for _ in 0..10 {
+        let before = sim.food_web.records().count();
+        sim.update();
+        meals += sim.food_web.records().count() - before;
+
+        // Every grid entry is a living entity and every living entity is in the grid
+        let mut gridded: Vec<Entity> = sim
+            .grid
+            .entries_in_circle(0.0, 0.0, 1000.0)
+            .into_iter()
+            .map(|(entity, _, _)| entity)
+            .collect();
+        gridded.sort_by_key(|entity| entity.to_bits());
+        let mut living: Vec<Entity> = sim.world.iter().map(|entity| entity.entity()).collect();
+        living.sort_by_key(|entity| entity.to_bits());
+        assert_eq!(gridded, living);
+        assert_eq!(sim.grid.len(), living.len());
+    }
+    assert!(meals > 0, "no entity was eaten");
+    // Removals and births were applied to the grid, so the next step moves entries in place
+    assert!(sim.update_grid_positions());
+}
+
+#[test]
+fn test_movement_type_weights_shape_initial_population() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 1000;
+    config.population.entity_scale = 1.0;
+    config.population.initial_movement_type_weights = [1.0, 1.0, 1.0, 1.0, 96.0, 0.0];
+    let sim = Simulation::new_seeded(500.0, config, 11);
+
+    let grazers = sim
+        .world
+        .query::<&Genes>()
+        .iter()
+        .filter(|(_, genes)| genes.behavior.movement_style.style == MovementType::Grazing)
+        .count();
+    let migrants = sim
+        .world
+        .query::<&Genes>()
+        .iter()
+        .filter(|(_, genes)| genes.behavior.movement_style.style == MovementType::Migratory)
+        .count();
+
+    assert!(grazers > 900, "only {} of 1000 are grazers", grazers);
+    assert_eq!(migrants, 0);
+}
+
+#[test]
+fn test_reproduction_cooldown_spaces_out_births() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    config.population.max_population = 100_000;
+    config.reproduction.death_chance_factor = 0.0;
+    config.reproduction.reproduction_cooldown = 10;
+    let mut sim = Simulation::new_seeded(1000.0, config.clone(), 3);
+    let mut rng = StdRng::seed_from_u64(3);
+
+    let mut genes = Genes::new_random(&mut rng);
+    genes.reproduction.rate = 0.25;
+    genes.reproduction.strategy = 1.0;
+    let parent = Simulation::spawn_entity(
+        &mut sim.world,
+        &mut rng,
+        genes,
+        Position { x: 0.0, y: 0.0 },
+        &config,
+        &mut sim.next_uid,
+    );
+
+    let mut births = Vec::new();
+    for step in 0..100 {
+        // Keep the parent well fed and alone so only the cooldown limits its births
+        for (entity, energy) in sim.world.query_mut::<&mut Energy>() {
+            if entity == parent {
+                energy.current = energy.max;
+            } else {
+                energy.current = 0.0;
+            }
+        }
+        sim.update();
+        let remaining = sim.world.get::<&ReproCooldown>(parent).map(|c| c.remaining);
+        if remaining.ok() == Some(10) {
+            births.push(step);
+        }
+    }
+
+    assert!(births.len() >= 2, "parent reproduced at {:?}", births);
+    for pair in births.windows(2) {
+        assert!(pair[1] - pair[0] > 10, "births too close: {:?}", births);
+    }
+}
+
+#[test]
+fn test_parent_refused_by_the_population_cap_gets_no_cooldown() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    config.population.max_population = 1;
+    config.population.entity_scale = 1.0;
+    config.reproduction.death_chance_factor = 0.0;
+    config.reproduction.min_reproduction_chance = 1.0;
+    config.reproduction.reproduction_cooldown = 10;
+    let mut sim = Simulation::new_seeded(1000.0, config.clone(), 3);
+    let mut rng = StdRng::seed_from_u64(3);
+
+    let mut genes = Genes::new_random(&mut rng);
+    genes.reproduction.rate = 1.0;
+    genes.reproduction.strategy = 1.0;
+    let parent = Simulation::spawn_entity(
+        &mut sim.world,
+        &mut rng,
+        genes,
+        Position { x: 0.0, y: 0.0 },
+        &config,
+        &mut sim.next_uid,
+    );
+
+    let mut attempts = 0;
+    for _ in 0..20 {
+        let max = {
+            let mut energy = sim.world.get::<&mut Energy>(parent).unwrap();
+            energy.current = energy.max;
+            energy.max
+        };
+        sim.update();
+        // Paying for a birth leaves the parent well short of its max
+        if sim.world.get::<&Energy>(parent).unwrap().current < max * 0.9 {
+            attempts += 1;
+        }
+        assert!(sim.world.get::<&ReproCooldown>(parent).is_err());
+    }
+    assert!(attempts > 0, "parent never tried to reproduce");
+    assert_eq!(sim.world.len(), 1);
+}
+
+#[test]
+fn test_entities_younger_than_maturity_age_never_reproduce() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    config.population.max_population = 100_000;
+    config.reproduction.death_chance_factor = 0.0;
+    config.reproduction.maturity_age = 50;
+    let mut sim = Simulation::new_seeded(1000.0, config.clone(), 5);
+    let mut rng = StdRng::seed_from_u64(5);
+
+    let mut genes = Genes::new_random(&mut rng);
+    genes.reproduction.rate = 0.25;
+    genes.reproduction.strategy = 1.0;
+    let parent = Simulation::spawn_entity(
+        &mut sim.world,
+        &mut rng,
+        genes,
+        Position { x: 0.0, y: 0.0 },
+        &config,
+        &mut sim.next_uid,
+    );
+
+    let mut first_birth = None;
+    for step in 0..150 {
+        if let Ok(mut energy) = sim.world.get::<&mut Energy>(parent) {
+            energy.current = energy.max;
+        }
+        sim.update();
+        if first_birth.is_none() && sim.world.len() > 1 {
+            first_birth = Some(step);
+        }
+    }
+
+    assert_eq!(sim.world.get::<&Age>(parent).unwrap().steps, 150);
+    let first_birth = first_birth.expect("parent never reproduced once mature");
+    assert!(
+        first_birth >= 50,
+        "parent reproduced at step {}",
+        first_birth
+    );
+}
+
+#[test]
+fn test_spatial_density_scales_with_world_area() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 200;
+    config.population.entity_scale = 1.0;
+    let small = Simulation::new_seeded(200.0, config.clone(), 3);
+    let large = Simulation::new_seeded(400.0, config, 3);
+    assert_eq!(small.world.len(), large.world.len());
+
+    // Same count over four times the area, and the cap fraction ignores area entirely
+    assert!((small.spatial_density() / large.spatial_density() - 4.0).abs() < 1e-4);
+    assert!((small.spatial_density() - 200.0 / (200.0 * 200.0)).abs() < 1e-9);
+    assert_eq!(
+        small.stats().population_cap_fraction,
+        large.stats().population_cap_fraction
+    );
+    assert_eq!(large.stats().spatial_density, large.spatial_density());
+}
+
+#[test]
+fn test_query_margin_finds_an_approaching_predator_a_step_earlier() {
+    let first_seen = |margin: f32| {
+        let mut config = SimulationConfig::default();
+        config.population.initial_entities = 0;
+        config.physics.query_radius_margin = margin;
+        let mut sim = Simulation::new_seeded(400.0, config.clone(), 4);
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut genes = Genes::new_random(&mut rng);
+        genes.movement.sense_radius = 20.0;
+        let mut spawn = |sim: &mut Simulation, x: f32| {
+            let pos = Position { x, y: 0.0 };
+            Simulation::spawn_entity(
+                &mut sim.world,
+                &mut rng,
+                genes.clone(),
+                pos,
+                &config,
+                &mut sim.next_uid,
+            )
+        };
+        let prey = spawn(&mut sim, 0.0);
+        let predator = spawn(&mut sim, 31.0);
+
+        // The predator closes at full speed, one step at a time
+        (0..10).find(|_| {
+            sim.world.get::<&mut Position>(predator).unwrap().x -= config.physics.max_velocity;
+            sim.rebuild_spatial_grid();
+            let prey_pos = (*sim.world.get::<&Position>(prey).unwrap()).clone();
+            sim.get_nearby_entities_for_entity(&prey_pos, &genes)
+                .contains(&predator)
+        })
+    };
+
+    let without_margin = first_seen(0.0).unwrap();
+    let with_margin = first_seen(1.0).unwrap();
+    assert_eq!(with_margin + 1, without_margin);
+}
+
+#[test]
+fn test_max_neighbors_keeps_the_closest_flockmates() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    let mut sim = Simulation::new_seeded(400.0, config.clone(), 8);
+    let mut rng = StdRng::seed_from_u64(8);
+
+    let mut genes = Genes::new_random(&mut rng);
+    genes.behavior.movement_style.style = crate::components::MovementType::Flocking;
+    genes.movement.sense_radius = 100.0;
+    genes.behavior.fearfulness = 0.0; // Larger flockmates must not read as predators
+    let mut spawn = |sim: &mut Simulation, x: f32, y: f32, velocity: Velocity| {
+        let entity = Simulation::spawn_entity(
+            &mut sim.world,
+            &mut rng,
+            genes.clone(),
+            Position { x, y },
+            &config,
+            &mut sim.next_uid,
+        );
+        *sim.world.get::<&mut Velocity>(entity).unwrap() = velocity;
+        entity
+    };
+
+    // Close flockmates head east, a larger far group heads north
+    let flocker = spawn(&mut sim, 0.0, 0.0, Velocity { x: 0.0, y: 0.0 });
+    let close: Vec<Entity> = (0..3)
+        .map(|i| spawn(&mut sim, 5.0 + i as f32, 0.0, Velocity { x: 1.0, y: 0.0 }))
+        .collect();
+    for i in 0..10 {
+        spawn(&mut sim, 0.0, 50.0 + i as f32, Velocity { x: 0.0, y: 1.0 });
+    }
+    sim.rebuild_spatial_grid();
+    sim.snapshot.rebuild(&sim.world);
+
+    let steer = |sim: &mut Simulation, max_neighbors: usize| {
+        sim.config.physics.max_neighbors = max_neighbors;
+        let nearby = sim.get_nearby_entities_for_entity(&Position { x: 0.0, y: 0.0 }, &genes);
+        let mut velocity = Velocity { x: 0.0, y: 0.0 };
+        sim.apply_movement_to_entity(
+            &genes,
+            &Phenotype::of(&genes),
+            &Size { radius: 5.0 },
+            &mut Position { x: 0.0, y: 0.0 },
+            &mut velocity,
+            &mut 50.0,
+            100.0,
+            &Position { x: 0.0, y: 0.0 },
+            &nearby,
+            &mut StdRng::seed_from_u64(1),
+        );
+        (nearby, velocity)
+    };
+
+    let (all, unlimited) = steer(&mut sim, 0);
+    assert_eq!(all.len(), 14);
+    let (nearest, limited) = steer(&mut sim, 4);
+    assert_eq!(nearest[0], flocker);
+    assert_eq!(nearest[1..].to_vec(), close);
+
+    // Same random draws either way, so only the far group's northward pull differs
+    assert!(
+        unlimited.y > limited.y,
+        "unlimited: {:?}, limited: {:?}",
+        unlimited,
+        limited
+    );
+}
+
+#[test]
+fn test_homeostasis_holds_population_near_target() {
+    // Equal radii rule out predation and a zero threshold lets anyone breed, so without
+    // the controller the population climbs toward its cap
+    let mut config = SimulationConfig::default();
+    config.population.entity_scale = 1.0;
+    config.population.max_population = 400;
+    config.physics.min_entity_radius = 3.0;
+    config.physics.max_entity_radius = 3.0;
+    config.reproduction.reproduction_energy_threshold = 0.0;
+
+    let mean_population = |initial: usize, target: u32| {
+        let mut config = config.clone();
+        config.population.initial_entities = initial;
+        config.population.target_population = target;
+        let mut sim = Simulation::new_seeded(200.0, config, 1);
+        sim.set_log_interval(0);
+        let mut total = 0;
+        for step in 0..150 {
+            sim.update();
+            if step >= 75 {
+                total += sim.world.len();
+            }
+        }
+        total as f32 / 75.0
+    };
+
+    let free = mean_population(30, 0);
+    for initial in [30, 250] {
+        let held = mean_population(initial, 100);
+        assert!(
+            (held - 100.0).abs() < 0.3 * 100.0,
+            "from {} the population averaged {}",
+            initial,
+            held
+        );
+        assert!(held < free * 0.6, "held {} vs free {}", held, free);
+    }
+}
+
+#[test]
+fn test_uids_are_never_reused_after_despawn() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 20;
+    config.population.entity_scale = 1.0;
+    let mut sim = Simulation::new_seeded(200.0, config, 7);
+
+    let uids = |sim: &Simulation| -> HashMap<Entity, u64> {
+        sim.world
+            .query::<&Uid>()
+            .iter()
+            .map(|(entity, uid)| (entity, uid.0))
+            .collect()
+    };
+    let first = uids(&sim);
+    assert_eq!(first.len(), 20);
+    assert_eq!(sim.next_uid(), 20);
+
+    let despawned: Vec<Entity> = first.keys().copied().take(10).collect();
+    for &entity in &despawned {
+        sim.world.despawn(entity).unwrap();
+    }
+    let genes = Genes::new_random(&mut thread_rng());
+    sim.inject_genes(&genes, 10);
+
+    let second = uids(&sim);
+    // hecs hands the freed slots to the new entities, but their uids are fresh
+    let freed: HashSet<u32> = despawned.iter().map(|entity| entity.id()).collect();
+    assert!(second.keys().any(|entity| freed.contains(&entity.id())));
+    for entity in &despawned {
+        assert!(!second.values().any(|uid| *uid == first[entity]));
+    }
+    let distinct: HashSet<u64> = second.values().copied().collect();
+    assert_eq!(distinct.len(), second.len());
+    assert!(second.values().all(|uid| *uid < sim.next_uid()));
+}
+
+#[test]
+fn test_starving_entity_that_recovers_within_grace_survives() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    config.reproduction.death_chance_factor = 0.0;
+    config.energy.starvation_grace = 5;
+    let mut sim = Simulation::new_seeded(1000.0, config.clone(), 9);
+    let mut rng = StdRng::seed_from_u64(9);
+
+    let [fed, unfed] = [-200.0, 200.0].map(|x| {
+        let genes = Genes::new_random(&mut rng);
+        let entity = Simulation::spawn_entity(
+            &mut sim.world,
+            &mut rng,
+            genes,
+            Position { x, y: 0.0 },
+            &config,
+            &mut sim.next_uid,
+        );
+        sim.world.get::<&mut Energy>(entity).unwrap().current = 0.01;
+        entity
+    });
+
+    // Upkeep takes both below the threshold on the first step
+    for _ in 0..3 {
+        sim.update();
+    }
+    assert_eq!(sim.world.get::<&Starving>(fed).unwrap().steps, 3);
+    assert!(!config
+        .energy
+        .is_alive(sim.world.get::<&Energy>(fed).unwrap().current));
+
+    sim.world.get::<&mut Energy>(fed).unwrap().current = 50.0;
+    sim.update();
+    sim.update();
+    assert_eq!(sim.world.get::<&Starving>(fed).unwrap().steps, 0);
+    assert_eq!(sim.world.get::<&Starving>(unfed).unwrap().steps, 5);
+
+    sim.update();
+    assert!(sim.world.contains(fed));
+    assert!(!sim.world.contains(unfed), "starved past the grace period");
+}
+
+#[test]
+fn test_trails_keep_recent_positions_only_when_enabled() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 10;
+    config.population.entity_scale = 1.0;
+    config.reproduction.death_chance_factor = 0.0;
+    let mut sim = Simulation::new_seeded(300.0, config.clone(), 3);
+
+    sim.update();
+    sim.update();
+    assert!(sim.trails().is_empty(), "trails are off by default");
+    assert!(sim
+        .previous_positions
+        .values()
+        .all(|history| history.len() == 1));
+
+    config.rendering.trails = true;
+    config.rendering.trail_length = 4;
+    sim.update_config(config);
+    let mut path: HashMap<Entity, Vec<Position>> = HashMap::new();
+    for _ in 0..6 {
+        for (entity, pos) in sim.world.query::<&Position>().iter() {
+            path.entry(entity).or_default().push(pos.clone());
+        }
+        sim.update();
+    }
+
+    let trails = sim.trails();
+    assert_eq!(trails.len(), sim.world.len() as usize);
+    for (entity, pos) in sim.world.query::<&Position>().iter() {
+        let history = &sim.previous_positions[&entity];
+        assert_eq!(history.len(), 3);
+        let recorded = &path[&entity];
+        for (kept, expected) in history.iter().zip(&recorded[recorded.len() - 3..]) {
+            assert_eq!((kept.x, kept.y), (expected.x, expected.y));
+        }
+        assert_eq!(
+            sim.previous_position(entity, pos).x,
+            recorded.last().unwrap().x
+        );
+    }
+    assert!(trails.iter().all(|trail| trail.points.len() == 4));
+}
+
+#[test]
+fn test_stats_report_the_season_phase() {
+    let mut config = SimulationConfig::default();
+    config.population.initial_entities = 0;
+    config.seasons.enabled = true;
+    config.seasons.period = 4;
+    let mut sim = Simulation::new_with_config(100.0, config);
+    sim.update();
+    assert_eq!(sim.stats().season_phase, 0.25);
+}
@@ -0,0 +1,112 @@
+use crate::components::Position;
+use crate::config::TimingConfig;
+
+/// Fixed-timestep accumulator that decouples simulation steps from the render rate.
+/// Real elapsed time is accumulated and consumed in whole timesteps; the leftover
+/// fraction is used to interpolate rendering between the last two states.
+#[derive(Default)]
+pub struct FixedTimestep {
+    accumulator_ms: f32,
+}
+
+impl FixedTimestep {
+    /// Add real elapsed time and return how many simulation steps to run now
+    pub fn advance(&mut self, elapsed_ms: f32, config: &TimingConfig) -> u32 {
+        let step_ms = Self::step_ms(config);
+        self.accumulator_ms += elapsed_ms.max(0.0);
+
+        let due = (self.accumulator_ms / step_ms).floor();
+        self.accumulator_ms -= due * step_ms;
+
+        // Past the cap the backlog is dropped rather than replayed, so a stalled tab
+        // doesn't trigger a burst of catch-up steps
+        (due as u32).min(config.max_steps_per_frame)
+    }
+
+    /// Progress through the current timestep, in [0, 1]
+    pub fn interpolation_factor(&self, config: &TimingConfig) -> f32 {
+        (self.accumulator_ms / Self::step_ms(config)).clamp(0.0, 1.0)
+    }
+
+    fn step_ms(config: &TimingConfig) -> f32 {
+        1000.0 / config.steps_per_second.max(f32::EPSILON)
+    }
+}
+
+/// Render position `factor` of the way from the previous state to the current one.
+/// The factor is clamped to [0, 1] so rendering never extrapolates past either state.
+pub fn interpolate(previous: &Position, current: &Position, factor: f32) -> Position {
+    let factor = factor.clamp(0.0, 1.0);
+    Position {
+        x: previous.x + (current.x - previous.x) * factor,
+        y: previous.y + (current.y - previous.y) * factor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(steps_per_second: f32, max_steps_per_frame: u32) -> TimingConfig {
+        TimingConfig {
+            steps_per_second,
+            max_steps_per_frame,
+            ..TimingConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_step_count_follows_elapsed_time() {
+        let config = config(100.0, 10); // 10ms timestep
+        let mut timestep = FixedTimestep::default();
+
+        assert_eq!(timestep.advance(5.0, &config), 0);
+        assert_eq!(timestep.advance(5.0, &config), 1);
+        assert_eq!(timestep.advance(35.0, &config), 3);
+        assert!((timestep.interpolation_factor(&config) - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_interpolation_between_states() {
+        let previous = Position { x: 10.0, y: -4.0 };
+        let current = Position { x: 20.0, y: 4.0 };
+
+        let halfway = interpolate(&previous, &current, 0.5);
+        assert_eq!((halfway.x, halfway.y), (15.0, 0.0));
+        let quarter = interpolate(&previous, &current, 0.25);
+        assert_eq!((quarter.x, quarter.y), (12.5, -2.0));
+
+        let before = interpolate(&previous, &current, -1.0);
+        assert_eq!((before.x, before.y), (previous.x, previous.y));
+        let after = interpolate(&previous, &current, 3.0);
+        assert_eq!((after.x, after.y), (current.x, current.y));
+    }
+
+    #[test]
+    fn test_step_rate_independent_of_frame_rate() {
+        let config = config(60.0, 10);
+
+        for frame_ms in [1000.0 / 30.0, 1000.0 / 60.0, 1000.0 / 144.0] {
+            let mut timestep = FixedTimestep::default();
+            let frames = (1000.0 / frame_ms) as usize;
+            let steps: u32 = (0..frames)
+                .map(|_| timestep.advance(frame_ms, &config))
+                .sum();
+            assert!(
+                (59..=60).contains(&steps),
+                "{} steps at {}ms",
+                steps,
+                frame_ms
+            );
+        }
+    }
+
+    #[test]
+    fn test_backlog_is_capped() {
+        let config = config(100.0, 4);
+        let mut timestep = FixedTimestep::default();
+
+        assert_eq!(timestep.advance(1000.0, &config), 4);
+        assert_eq!(timestep.advance(0.0, &config), 0);
+    }
+}
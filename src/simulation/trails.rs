@@ -0,0 +1,69 @@
+use super::Simulation;
+use crate::components::{Color, Position};
+use crate::genes::Genes;
+use hecs::Entity;
+use std::collections::VecDeque;
+
+/// Where an entity has been recently, for drawing its trail
+#[derive(Debug, Clone)]
+pub struct Trail {
+    pub color: Color,          // Per `rendering.color_mode`
+    pub points: Vec<Position>, // Oldest first, ending at the current position
+}
+
+impl Simulation {
+    /// Push each entity's position onto its history, which holds just the one for
+    /// interpolation unless trails are on. Rebuilding the map drops the dead.
+    pub(super) fn store_previous_positions(&mut self) {
+        let rendering = &self.config.rendering;
+        let keep = if rendering.trails {
+            rendering.trail_length.saturating_sub(1).max(1)
+        } else {
+            1
+        };
+        let mut history = std::mem::take(&mut self.previous_positions);
+        self.previous_positions = self
+            .world
+            .query::<&Position>()
+            .iter()
+            .map(|(entity, pos)| {
+                let mut positions = history.remove(&entity).unwrap_or_default();
+                while positions.len() >= keep {
+                    positions.pop_front();
+                }
+                positions.push_back(pos.clone());
+                (entity, positions)
+            })
+            .collect();
+    }
+
+    /// Position before the last step, or `pos` for an entity with no history yet
+    pub(super) fn previous_position<'a>(
+        &'a self,
+        entity: Entity,
+        pos: &'a Position,
+    ) -> &'a Position {
+        self.previous_positions
+            .get(&entity)
+            .and_then(VecDeque::back)
+            .unwrap_or(pos)
+    }
+
+    /// Each entity's recent positions, when `rendering.trails` is on
+    pub fn trails(&self) -> Vec<Trail> {
+        if !self.config.rendering.trails {
+            return Vec::new();
+        }
+        self.world
+            .query::<(&Position, &Color, &Genes)>()
+            .iter()
+            .map(|(entity, (pos, color, genes))| {
+                let history = self.previous_positions.get(&entity).into_iter().flatten();
+                Trail {
+                    color: self.render_color(color, genes),
+                    points: history.chain(std::iter::once(pos)).cloned().collect(),
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,123 @@
+use crate::components::{Energy, Position, Size, Velocity};
+use crate::genes::{Genes, Phenotype};
+use hecs::{Entity, World};
+
+const NO_SLOT: u32 = u32::MAX;
+
+/// Read-only copy of the components read in neighbour loops, stored as contiguous arrays.
+/// It is rebuilt from the world before entities are processed; the world remains the
+/// source of truth. Lookups index by `Entity::id`, avoiding per-access borrow checks.
+#[derive(Default)]
+pub struct EntitySnapshot {
+    slots: Vec<u32>, // Entity id -> index into the arrays below
+    entities: Vec<Entity>,
+    positions: Vec<Position>,
+    velocities: Vec<Velocity>,
+    sizes: Vec<Size>,
+    energies: Vec<Energy>,
+    genes: Vec<Genes>,
+    phenotypes: Vec<Phenotype>,
+}
+
+/// Components of one entity in an `EntitySnapshot`
+pub struct Neighbor<'a> {
+    pub pos: &'a Position,
+    pub velocity: &'a Velocity,
+    pub size: &'a Size,
+    pub energy: &'a Energy,
+    pub genes: &'a Genes,
+    pub phenotype: &'a Phenotype,
+}
+
+impl EntitySnapshot {
+    pub fn from_world(world: &World) -> Self {
+        let mut snapshot = Self::default();
+        snapshot.rebuild(world);
+        snapshot
+    }
+
+    /// Refill from the world, reusing the existing allocations
+    pub fn rebuild(&mut self, world: &World) {
+        self.slots.clear();
+        self.entities.clear();
+        self.positions.clear();
+        self.velocities.clear();
+        self.sizes.clear();
+        self.energies.clear();
+        self.genes.clear();
+        self.phenotypes.clear();
+
+        let mut query = world.query::<(
+            &Position,
+            &Size,
+            &Energy,
+            &Genes,
+            Option<&Velocity>,
+            Option<&Phenotype>,
+        )>();
+        for (entity, (pos, size, energy, genes, velocity, phenotype)) in query.iter() {
+            let id = entity.id() as usize;
+            if id >= self.slots.len() {
+                self.slots.resize(id + 1, NO_SLOT);
+            }
+            self.slots[id] = self.entities.len() as u32;
+            self.entities.push(entity);
+            self.positions.push(pos.clone());
+            self.velocities
+                .push(velocity.cloned().unwrap_or(Velocity { x: 0.0, y: 0.0 }));
+            self.sizes.push(size.clone());
+            self.energies.push(energy.clone());
+            self.genes.push(genes.clone());
+            self.phenotypes
+                .push(phenotype.cloned().unwrap_or_else(|| Phenotype::of(genes)));
+        }
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<Neighbor<'_>> {
+        let slot = *self.slots.get(entity.id() as usize)? as usize;
+        if slot == NO_SLOT as usize || self.entities[slot] != entity {
+            return None;
+        }
+        Some(Neighbor {
+            pos: &self.positions[slot],
+            velocity: &self.velocities[slot],
+            size: &self.sizes[slot],
+            energy: &self.energies[slot],
+            genes: &self.genes[slot],
+            phenotype: &self.phenotypes[slot],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_snapshot_matches_world() {
+        let mut world = World::new();
+        let genes = Genes::new_random(&mut thread_rng());
+        let spawn = |world: &mut World, x: f32| {
+            world.spawn((
+                Position { x, y: 0.0 },
+                Size { radius: 2.0 },
+                Energy {
+                    current: x,
+                    max: 100.0,
+                },
+                genes.clone(),
+            ))
+        };
+        let first = spawn(&mut world, 1.0);
+        let second = spawn(&mut world, 2.0);
+        world.despawn(first).unwrap();
+        let recycled = spawn(&mut world, 3.0); // Reuses the id of `first`
+
+        let snapshot = EntitySnapshot::from_world(&world);
+        assert!(snapshot.get(first).is_none());
+        assert_eq!(snapshot.get(second).unwrap().pos.x, 2.0);
+        assert_eq!(snapshot.get(recycled).unwrap().energy.current, 3.0);
+        assert_eq!(snapshot.get(recycled).unwrap().velocity.x, 0.0);
+    }
+}
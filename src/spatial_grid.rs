@@ -1,20 +1,45 @@
 use dashmap::DashMap;
 use hecs::Entity;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+pub type Cell = (i32, i32);
+
+/// How full the grid's cells are
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CellOccupancy {
+    pub cells: usize,       // Non-empty cells
+    pub max: usize,         // Entities in the fullest cell
+    pub mean: f32,          // Mean entities per non-empty cell
+    pub overflowing: usize, // Cells holding more than the soft capacity
+}
+
+/// One non-empty grid cell, for drawing how the grid partitions the world
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GridCell {
+    pub x: f32, // World position of the cell's lower corner
+    pub y: f32,
+    pub size: f32,
+    pub count: usize, // Entities in the cell
+}
 
-/// Optimized spatial grid using DashMap for concurrent inserts
+/// Optimized spatial grid using DashMap for concurrent inserts.
+/// Each cell stores entity positions so queries can filter by exact distance, and every
+/// entity lives in exactly one cell so query results never contain duplicates.
 pub struct SpatialGrid {
     cell_size: f32,
-    grid: DashMap<(i32, i32), Vec<Entity>>,
+    grid: DashMap<Cell, Vec<(Entity, f32, f32)>>,
+    entity_cells: DashMap<Entity, Cell>,
+    shuffle_seed: u64, // Seeds the per-query cell order, so queries are reproducible
+    cell_capacity: usize, // Most entries a neighbour query scans per cell; 0 = no limit
 }
 
 impl Default for SpatialGrid {
     fn default() -> Self {
-        Self {
-            cell_size: 25.0,
-            grid: DashMap::new(),
-        }
+        Self::new(25.0)
     }
 }
 
@@ -23,11 +48,105 @@ impl SpatialGrid {
         Self {
             cell_size,
             grid: DashMap::new(),
+            entity_cells: DashMap::new(),
+            shuffle_seed: 0,
+            cell_capacity: 0,
+        }
+    }
+
+    /// Cap the entries a neighbour query scans in any one cell. Fuller cells are
+    /// subsampled with an even stride, so clustering can't make queries quadratic.
+    pub fn set_cell_capacity(&mut self, capacity: usize) {
+        self.cell_capacity = capacity;
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.shuffle_seed = seed;
+    }
+
+    /// Order each cell's entries by entity, undoing the arbitrary order of parallel inserts
+    pub fn sort_cells(&self) {
+        for mut entries in self.grid.iter_mut() {
+            entries.sort_unstable_by_key(|(entity, _, _)| entity.to_bits());
+        }
+    }
+
+    /// Number of entities in the grid
+    pub fn len(&self) -> usize {
+        self.entity_cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entity_cells.is_empty()
+    }
+
+    /// Update every entry to the position `position_of` gives for it, keeping the cell Vecs.
+    /// Entries that stay in their cell are updated in place and only those that crossed
+    /// into another cell are moved. Returns false, leaving the grid to be rebuilt, if any
+    /// entity has no position (it was despawned).
+    pub fn update_positions(&self, position_of: impl Fn(Entity) -> Option<(f32, f32)>) -> bool {
+        let mut moved = Vec::new();
+        for mut entries in self.grid.iter_mut() {
+            let cell = *entries.key();
+            let mut missing = false;
+            entries.retain_mut(|(entity, x, y)| {
+                let Some((new_x, new_y)) = position_of(*entity) else {
+                    missing = true;
+                    return true;
+                };
+                (*x, *y) = (new_x, new_y);
+                if self.get_cell_coords(new_x, new_y) == cell {
+                    true
+                } else {
+                    moved.push((*entity, new_x, new_y));
+                    false
+                }
+            });
+            if missing {
+                return false;
+            }
+        }
+
+        self.insert_many(&moved);
+        true
+    }
+
+    /// Insert entities not already in the grid, then re-sort only the cells they landed
+    /// in, leaving the grid as a full rebuild would
+    pub fn insert_many(&self, entries: &[(Entity, f32, f32)]) {
+        let mut touched = HashSet::new();
+        for &(entity, x, y) in entries {
+            let cell = self.get_cell_coords(x, y);
+            self.entity_cells.insert(entity, cell);
+            self.grid.entry(cell).or_default().push((entity, x, y));
+            touched.insert(cell);
+        }
+        for cell in touched {
+            if let Some(mut entries) = self.grid.get_mut(&cell) {
+                entries.sort_unstable_by_key(|(entity, _, _)| entity.to_bits());
+            }
+        }
+    }
+
+    /// Remove every listed entity, filtering each affected cell once however many of
+    /// its entries go. Entities not in the grid are ignored.
+    pub fn remove_many(&self, entities: &[Entity]) {
+        let mut by_cell: HashMap<Cell, HashSet<Entity>> = HashMap::new();
+        for entity in entities {
+            if let Some((_, cell)) = self.entity_cells.remove(entity) {
+                by_cell.entry(cell).or_default().insert(*entity);
+            }
+        }
+        for (cell, removed) in by_cell {
+            if let Some(mut entries) = self.grid.get_mut(&cell) {
+                entries.retain(|(entity, _, _)| !removed.contains(entity));
+            }
         }
     }
 
     pub fn clear(&self) {
         self.grid.clear();
+        self.entity_cells.clear();
     }
 
     #[inline]
@@ -37,14 +156,47 @@ impl SpatialGrid {
         (cell_x, cell_y)
     }
 
-    /// Thread-safe insert - can be called from parallel iterators
+    /// Thread-safe insert - can be called from parallel iterators.
+    /// Re-inserting an entity moves it rather than adding a second entry.
     pub fn insert(&self, entity: Entity, x: f32, y: f32) {
         let cell = self.get_cell_coords(x, y);
-        self.grid.entry(cell).or_default().push(entity);
+        if let Some(old_cell) = self.entity_cells.insert(entity, cell) {
+            if let Some(mut entries) = self.grid.get_mut(&old_cell) {
+                entries.retain(|(other, _, _)| *other != entity);
+            }
+        }
+        self.grid.entry(cell).or_default().push((entity, x, y));
     }
 
+    /// Entities within `radius` of the query point, each returned at most once
     pub fn get_nearby_entities(&self, x: f32, y: f32, radius: f32) -> Vec<Entity> {
+        self.nearby_with_distances(x, y, radius)
+            .into_iter()
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    /// The `limit` entities within `radius` closest to the point, nearest first, ties broken
+    /// by entity id. A `limit` of 0 returns every entity found.
+    pub fn get_nearest_entities(&self, x: f32, y: f32, radius: f32, limit: usize) -> Vec<Entity> {
+        let mut nearby = self.nearby_with_distances(x, y, radius);
+        let by_distance = |a: &(Entity, f32), b: &(Entity, f32)| {
+            a.1.total_cmp(&b.1)
+                .then_with(|| a.0.to_bits().cmp(&b.0.to_bits()))
+        };
+        if limit > 0 && nearby.len() > limit {
+            nearby.select_nth_unstable_by(limit - 1, by_distance);
+            nearby.truncate(limit);
+        }
+        nearby.sort_unstable_by(by_distance);
+        nearby.into_iter().map(|(entity, _)| entity).collect()
+    }
+
+    /// Entities within `radius` with their squared distances, visiting cells in a seeded
+    /// random order and subsampling overfull cells
+    fn nearby_with_distances(&self, x: f32, y: f32, radius: f32) -> Vec<(Entity, f32)> {
         let mut nearby = Vec::new();
+        let radius_sq = radius * radius;
         let center_cell = self.get_cell_coords(x, y);
         let cell_radius = (radius / self.cell_size).ceil() as i32;
 
@@ -58,18 +210,121 @@ impl SpatialGrid {
         }
 
         // Randomize the order of cell processing to eliminate bias
-        let mut rng = thread_rng();
+        let cell_hash = (center_cell.0 as u64) << 32 | center_cell.1 as u32 as u64;
+        let mut rng = StdRng::seed_from_u64(self.shuffle_seed ^ cell_hash);
         cells.shuffle(&mut rng);
 
         // Process cells in randomized order
         for cell in cells {
-            if let Some(entities) = self.grid.get(&cell) {
-                nearby.extend(entities.iter().copied());
+            if let Some(entries) = self.grid.get(&cell) {
+                // Overfull cells contribute an evenly spaced subsample from a seeded offset
+                let stride = match self.cell_capacity {
+                    0 => 1,
+                    capacity => entries.len().div_ceil(capacity).max(1),
+                };
+                let offset = if stride > 1 {
+                    rng.gen_range(0..stride)
+                } else {
+                    0
+                };
+                nearby.extend(
+                    entries
+                        .iter()
+                        .skip(offset)
+                        .step_by(stride)
+                        .map(|(entity, ex, ey)| (*entity, (ex - x).powi(2) + (ey - y).powi(2)))
+                        .filter(|(_, distance_sq)| *distance_sq <= radius_sq),
+                );
             }
         }
 
         nearby
     }
+
+    pub fn occupancy(&self) -> CellOccupancy {
+        let counts: Vec<usize> = self
+            .grid
+            .iter()
+            .map(|entries| entries.len())
+            .filter(|&count| count > 0)
+            .collect();
+        if counts.is_empty() {
+            return CellOccupancy::default();
+        }
+        let overflowing = match self.cell_capacity {
+            0 => 0,
+            capacity => counts.iter().filter(|&&count| count > capacity).count(),
+        };
+        CellOccupancy {
+            cells: counts.len(),
+            max: counts.iter().copied().max().unwrap_or(0),
+            mean: counts.iter().sum::<usize>() as f32 / counts.len() as f32,
+            overflowing,
+        }
+    }
+
+    /// Bounds and entity count of every non-empty cell, ordered by cell coordinates
+    pub fn cells(&self) -> Vec<GridCell> {
+        let mut cells: Vec<(Cell, usize)> = self
+            .grid
+            .iter()
+            .filter(|entries| !entries.is_empty())
+            .map(|entries| (*entries.key(), entries.len()))
+            .collect();
+        cells.sort_unstable_by_key(|(cell, _)| *cell);
+        cells
+            .into_iter()
+            .map(|((cell_x, cell_y), count)| GridCell {
+                x: cell_x as f32 * self.cell_size,
+                y: cell_y as f32 * self.cell_size,
+                size: self.cell_size,
+                count,
+            })
+            .collect()
+    }
+
+    pub fn contains_within(&self, x: f32, y: f32, radius: f32, entity: Entity) -> bool {
+        self.get_nearby_entities(x, y, radius).contains(&entity)
+    }
+
+    /// Entries whose stored position lies within the rectangle `min..=max`, in entity order
+    pub fn entries_in_rect(&self, min: (f32, f32), max: (f32, f32)) -> Vec<(Entity, f32, f32)> {
+        self.entries_in_cells(min, max, |x, y| {
+            (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&y)
+        })
+    }
+
+    /// Entries whose stored position lies within `radius` of a point, in entity order
+    pub fn entries_in_circle(&self, x: f32, y: f32, radius: f32) -> Vec<(Entity, f32, f32)> {
+        let radius_sq = radius * radius;
+        self.entries_in_cells(
+            (x - radius, y - radius),
+            (x + radius, y + radius),
+            |ex, ey| (ex - x).powi(2) + (ey - y).powi(2) <= radius_sq,
+        )
+    }
+
+    /// Entries of every cell overlapping the bounding box `min..=max` that pass `keep`
+    fn entries_in_cells(
+        &self,
+        min: (f32, f32),
+        max: (f32, f32),
+        keep: impl Fn(f32, f32) -> bool,
+    ) -> Vec<(Entity, f32, f32)> {
+        let (min_x, min_y) = self.get_cell_coords(min.0, min.1);
+        let (max_x, max_y) = self.get_cell_coords(max.0, max.1);
+
+        let mut entries = Vec::new();
+        for cell_x in min_x..=max_x {
+            for cell_y in min_y..=max_y {
+                if let Some(cell) = self.grid.get(&(cell_x, cell_y)) {
+                    entries.extend(cell.iter().filter(|(_, x, y)| keep(*x, *y)).copied());
+                }
+            }
+        }
+        entries.sort_unstable_by_key(|(entity, _, _)| entity.to_bits());
+        entries
+    }
 }
 
 #[cfg(test)]
@@ -78,7 +333,7 @@ mod tests {
     use crate::components::{Color, Energy, Position, Size, Velocity};
     use crate::genes::Genes;
     use hecs::World;
-    use rand::thread_rng;
+    use rand::{thread_rng, Rng};
 
     #[test]
     fn test_spatial_grid_creation() {
@@ -312,8 +567,8 @@ mod tests {
             entities.push(entity);
         }
 
-        // Get nearby entities from center with large radius
-        let nearby = grid.get_nearby_entities(150.0, 150.0, 200.0);
+        // Get nearby entities from center with a radius covering the whole diagonal
+        let nearby = grid.get_nearby_entities(150.0, 150.0, 220.0);
 
         // All entities should be found
         for entity in &entities {
@@ -369,4 +624,143 @@ mod tests {
         assert!(nearby.contains(&entity1), "Entity1 not found");
         assert!(nearby.contains(&entity2), "Entity2 not found");
     }
+
+    #[test]
+    fn test_region_queries_return_exactly_contained_entries() {
+        let grid = SpatialGrid::new(10.0);
+        let mut world = World::new();
+        let mut at = |x: f32, y: f32| {
+            let entity = world.spawn((Position { x, y },));
+            grid.insert(entity, x, y);
+            entity
+        };
+        let origin = at(0.0, 0.0);
+        let inside = at(14.0, -9.0);
+        let corner = at(20.0, 20.0);
+        let outside = at(20.5, 0.0);
+        let far = at(-80.0, 35.0);
+
+        let in_rect: Vec<Entity> = grid
+            .entries_in_rect((-5.0, -10.0), (20.0, 20.0))
+            .iter()
+            .map(|(entity, _, _)| *entity)
+            .collect();
+        assert_eq!(in_rect, vec![origin, inside, corner]);
+
+        let in_circle = grid.entries_in_circle(0.0, 0.0, 20.0);
+        assert_eq!(in_circle, vec![(origin, 0.0, 0.0), (inside, 14.0, -9.0)]);
+        assert!(!in_circle.iter().any(|(e, _, _)| *e == outside || *e == far));
+    }
+
+    #[test]
+    fn test_nearby_entities_within_radius_and_unique() {
+        let grid = SpatialGrid::new(10.0);
+        let mut world = World::new();
+        let mut rng = thread_rng();
+        let mut positions = std::collections::HashMap::new();
+
+        for _ in 0..500 {
+            let x = rng.gen_range(-100.0..100.0);
+            let y = rng.gen_range(-100.0..100.0);
+            let entity = world.spawn((Position { x, y },));
+            grid.insert(entity, x, y);
+            positions.insert(entity, (x, y));
+        }
+        // An entity inserted twice must still only be reported once
+        let (&twice, &(x, y)) = positions.iter().next().unwrap();
+        grid.insert(twice, x + 1.0, y);
+
+        let radius = 35.0;
+        let nearby = grid.get_nearby_entities(x, y, radius);
+        let unique: std::collections::HashSet<_> = nearby.iter().collect();
+        assert_eq!(unique.len(), nearby.len(), "duplicate entities returned");
+
+        for entity in &nearby {
+            let (ex, ey) = positions[entity];
+            assert!(((ex - x).powi(2) + (ey - y).powi(2)).sqrt() <= radius);
+        }
+        assert!(grid.contains_within(x, y, radius, twice));
+    }
+
+    #[test]
+    fn test_remove_many_drops_only_listed_entities() {
+        let grid = SpatialGrid::new(10.0);
+        let mut world = World::new();
+        let entities: Vec<Entity> = (0..100)
+            .map(|i| {
+                let (x, y) = ((i % 10) as f32 * 3.0, (i / 10) as f32 * 3.0);
+                let entity = world.spawn((Position { x, y },));
+                grid.insert(entity, x, y);
+                entity
+            })
+            .collect();
+
+        let (removed, kept) = entities.split_at(60);
+        // Listing an entity twice, or one the grid never held, is harmless
+        let stranger = world.spawn((Position { x: 0.0, y: 0.0 },));
+        let mut batch = removed.to_vec();
+        batch.extend([removed[0], stranger]);
+        grid.remove_many(&batch);
+
+        assert_eq!(grid.len(), kept.len());
+        let remaining: Vec<Entity> = grid
+            .entries_in_rect((-1.0, -1.0), (30.0, 30.0))
+            .into_iter()
+            .map(|(entity, _, _)| entity)
+            .collect();
+        assert_eq!(remaining.len(), kept.len());
+        assert!(kept.iter().all(|entity| remaining.contains(entity)));
+    }
+
+    #[test]
+    fn test_cells_cover_every_entity() {
+        let grid = SpatialGrid::new(20.0);
+        let mut world = World::new();
+        let mut rng = thread_rng();
+        let mut positions = Vec::new();
+        for _ in 0..300 {
+            let (x, y) = (rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0));
+            grid.insert(world.spawn((Position { x, y },)), x, y);
+            positions.push((x, y));
+        }
+
+        let cells = grid.cells();
+        assert_eq!(cells.iter().map(|cell| cell.count).sum::<usize>(), 300);
+        assert!(cells.iter().all(|cell| cell.count > 0 && cell.size == 20.0));
+        for cell in &cells {
+            let inside = positions
+                .iter()
+                .filter(|(x, y)| {
+                    (cell.x..cell.x + cell.size).contains(x)
+                        && (cell.y..cell.y + cell.size).contains(y)
+                })
+                .count();
+            assert_eq!(inside, cell.count, "cell at ({}, {})", cell.x, cell.y);
+        }
+    }
+
+    #[test]
+    fn test_overfull_cell_queries_stay_bounded() {
+        let mut grid = SpatialGrid::new(25.0);
+        grid.set_cell_capacity(50);
+        let mut world = World::new();
+        for _ in 0..5000 {
+            let entity = world.spawn((Position { x: 1.0, y: 1.0 },));
+            grid.insert(entity, 1.0, 1.0);
+        }
+        grid.sort_cells();
+
+        let nearby = grid.get_nearby_entities(1.0, 1.0, 10.0);
+        assert!(!nearby.is_empty());
+        assert!(nearby.len() <= 50, "scanned {} entries", nearby.len());
+        assert_eq!(nearby, grid.get_nearby_entities(1.0, 1.0, 10.0));
+
+        let occupancy = grid.occupancy();
+        assert_eq!(occupancy.cells, 1);
+        assert_eq!(occupancy.max, 5000);
+        assert_eq!(occupancy.overflowing, 1);
+
+        grid.set_cell_capacity(0);
+        assert_eq!(grid.get_nearby_entities(1.0, 1.0, 10.0).len(), 5000);
+    }
 }
@@ -0,0 +1,45 @@
+use crate::components::Position;
+use crate::genes::Genes;
+use crate::spatial_grid::SpatialGrid;
+use hecs::{Entity, World};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Moran's I of one trait across the population. Every pair of entities within `radius` of
+/// each other has weight 1, and all other pairs 0. Values near +1 mean similar genes sit
+/// together (kin clustering). Values near 0 mean no spatial structure, and negative values
+/// mean neighbours tend to differ. Returns `None` for an unknown trait name, and 0 when the
+/// trait doesn't vary or no entity has a neighbour. The grid must hold current positions.
+pub fn morans_i(world: &World, grid: &SpatialGrid, trait_name: &str, radius: f32) -> Option<f32> {
+    let index = Genes::trait_index(trait_name)?;
+    let values: HashMap<Entity, (f32, f32, f64)> = world
+        .query::<(&Position, &Genes)>()
+        .iter()
+        .map(|(entity, (pos, genes))| (entity, (pos.x, pos.y, genes.traits()[index] as f64)))
+        .collect();
+    if values.is_empty() {
+        return Some(0.0);
+    }
+
+    let n = values.len() as f64;
+    let mean = values.values().map(|(_, _, v)| v).sum::<f64>() / n;
+    let variance_sum: f64 = values.values().map(|(_, _, v)| (v - mean).powi(2)).sum();
+
+    let (weight_sum, cross_sum) = values
+        .par_iter()
+        .map(|(&entity, &(x, y, value))| {
+            grid.entries_in_circle(x, y, radius)
+                .into_iter()
+                .filter(|(other, _, _)| *other != entity)
+                .filter_map(|(other, _, _)| values.get(&other))
+                .fold((0.0, 0.0), |(weights, cross), (_, _, other_value)| {
+                    (weights + 1.0, cross + (value - mean) * (other_value - mean))
+                })
+        })
+        .reduce(|| (0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1));
+
+    if weight_sum == 0.0 || variance_sum == 0.0 {
+        return Some(0.0);
+    }
+    Some((n / weight_sum * cross_sum / variance_sum) as f32)
+}
@@ -0,0 +1,105 @@
+use super::{EntityMetrics, EntityType, SimulationStats};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Display order of the entity type counts in `StatsDiff::format`
+const ENTITY_TYPES: [(EntityType, &str); 5] = [
+    (EntityType::RedDominant, "Red"),
+    (EntityType::GreenDominant, "Green"),
+    (EntityType::BlueDominant, "Blue"),
+    (EntityType::Purple, "Purple"),
+    (EntityType::Mixed, "Mixed"),
+];
+
+/// Change in each count and metric between two stats snapshots, as `after - before`
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsDiff {
+    pub total_entities: i64,
+    pub entity_counts: HashMap<EntityType, i64>, // Every type present in either snapshot
+    pub average_metrics: EntityMetrics,          // Each field is a delta
+    pub population_cap_fraction: f32,
+    pub spatial_density: f32,
+    pub species: i64,
+    pub producers: i64,
+    pub herbivores: i64,
+    pub carnivores: i64,
+    pub apex: i64,
+    pub mating_relatedness: f32,
+}
+
+fn count_delta(before: usize, after: usize) -> i64 {
+    after as i64 - before as i64
+}
+
+impl SimulationStats {
+    /// How `other` differs from `self`, e.g. a run with a changed config against a baseline
+    pub fn diff(&self, other: &SimulationStats) -> StatsDiff {
+        let count = |stats: &SimulationStats, kind: &EntityType| {
+            stats.entity_counts.get(kind).copied().unwrap_or(0)
+        };
+        let entity_counts = self
+            .entity_counts
+            .keys()
+            .chain(other.entity_counts.keys())
+            .map(|kind| (*kind, count_delta(count(self, kind), count(other, kind))))
+            .collect();
+
+        let (before, after) = (&self.average_metrics, &other.average_metrics);
+        let (levels, other_levels) = (&self.trophic_levels, &other.trophic_levels);
+        StatsDiff {
+            total_entities: count_delta(self.total_entities, other.total_entities),
+            entity_counts,
+            average_metrics: EntityMetrics {
+                average_energy: after.average_energy - before.average_energy,
+                average_speed: after.average_speed - before.average_speed,
+                average_size: after.average_size - before.average_size,
+                average_reproduction_rate: after.average_reproduction_rate
+                    - before.average_reproduction_rate,
+                average_sense_radius: after.average_sense_radius - before.average_sense_radius,
+                average_energy_efficiency: after.average_energy_efficiency
+                    - before.average_energy_efficiency,
+            },
+            population_cap_fraction: other.population_cap_fraction - self.population_cap_fraction,
+            spatial_density: other.spatial_density - self.spatial_density,
+            species: count_delta(self.species.len(), other.species.len()),
+            producers: count_delta(levels.producers, other_levels.producers),
+            herbivores: count_delta(levels.herbivores, other_levels.herbivores),
+            carnivores: count_delta(levels.carnivores, other_levels.carnivores),
+            apex: count_delta(levels.apex, other_levels.apex),
+            mating_relatedness: other.mating_relatedness - self.mating_relatedness,
+        }
+    }
+}
+
+impl StatsDiff {
+    /// Signed deltas on one line, labelled as in `SimulationStats::format_detailed`
+    pub fn format(&self) -> String {
+        let types: Vec<String> = ENTITY_TYPES
+            .iter()
+            .map(|(kind, name)| {
+                let delta = self.entity_counts.get(kind).copied().unwrap_or(0);
+                format!("{}:{:+}", name, delta)
+            })
+            .collect();
+        let metrics = &self.average_metrics;
+        format!(
+            "Total={:+} ({}), CapFraction={:+.3}, Density={:+.5}, AvgEnergy={:+.1}, AvgSpeed={:+.2}, AvgSense={:+.1}, AvgEfficiency={:+.2}, AvgRepro={:+.3}, AvgSize={:+.2}, Species={:+}, Trophic=[{:+}, {:+}, {:+}, {:+}], Relatedness={:+.3}",
+            self.total_entities,
+            types.join(" "),
+            self.population_cap_fraction,
+            self.spatial_density,
+            metrics.average_energy,
+            metrics.average_speed,
+            metrics.average_sense_radius,
+            metrics.average_energy_efficiency,
+            metrics.average_reproduction_rate,
+            metrics.average_size,
+            self.species,
+            self.producers,
+            self.herbivores,
+            self.carnivores,
+            self.apex,
+            self.mating_relatedness,
+        )
+    }
+}
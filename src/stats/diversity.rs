@@ -0,0 +1,35 @@
+use crate::components::Position;
+use crate::genes::Genes;
+use crate::spatial_grid::{Cell, SpatialGrid};
+use hecs::World;
+use std::collections::HashMap;
+
+/// Shannon entropy (bits) of each grid cell's gene signatures. Zero means every entity in
+/// the cell shares one genotype; higher values mean a more mixed neighbourhood.
+pub fn cell_diversity_map(world: &World, grid: &SpatialGrid) -> Vec<(Cell, f32)> {
+    let mut cells: HashMap<Cell, HashMap<u32, usize>> = HashMap::new();
+    for (_, (pos, genes)) in world.query::<(&Position, &Genes)>().iter() {
+        let cell = grid.get_cell_coords(pos.x, pos.y);
+        *cells
+            .entry(cell)
+            .or_default()
+            .entry(genes.signature())
+            .or_default() += 1;
+    }
+
+    cells
+        .into_iter()
+        .map(|(cell, counts)| (cell, shannon_entropy(counts.values().copied())))
+        .collect()
+}
+
+fn shannon_entropy(counts: impl Iterator<Item = usize> + Clone) -> f32 {
+    let total = counts.clone().sum::<usize>() as f32;
+    counts
+        .filter(|&count| count > 0)
+        .map(|count| {
+            let p = count as f32 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
@@ -0,0 +1,110 @@
+use crate::genes::Genes;
+use hecs::World;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// Steps of predation history that trophic levels are computed from
+pub const FOOD_WEB_WINDOW: u32 = 600;
+/// Rounds of level propagation; bounds the work when feeding loops make levels diverge
+const LEVEL_ITERATIONS: usize = 8;
+
+/// One successful meal, with both parties identified by `Genes::signature`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Predation {
+    pub step: u32,
+    pub predator: u32,
+    pub prey: u32,
+}
+
+/// The meals eaten over the last `window` steps
+#[derive(Debug, Clone)]
+pub struct FoodWebLog {
+    window: u32,
+    records: VecDeque<Predation>,
+}
+
+impl Default for FoodWebLog {
+    fn default() -> Self {
+        Self::new(FOOD_WEB_WINDOW)
+    }
+}
+
+impl FoodWebLog {
+    pub fn new(window: u32) -> Self {
+        Self {
+            window,
+            records: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, step: u32, predator: &Genes, prey: &Genes) {
+        self.records.push_back(Predation {
+            step,
+            predator: predator.signature(),
+            prey: prey.signature(),
+        });
+    }
+
+    /// Forget meals that fell out of the window ending at `step`
+    pub fn prune(&mut self, step: u32) {
+        let oldest = step.saturating_sub(self.window);
+        while self.records.front().is_some_and(|r| r.step < oldest) {
+            self.records.pop_front();
+        }
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &Predation> {
+        self.records.iter()
+    }
+}
+
+/// How many living entities feed at each trophic level
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TrophicLevels {
+    pub producers: usize,  // Ate nothing in the window (level 1)
+    pub herbivores: usize, // Ate producers (level about 2)
+    pub carnivores: usize, // Ate herbivores (level about 3)
+    pub apex: usize,       // Level 3.5 and above
+}
+
+/// Trophic level of each genotype signature seen eating: one more than the mean level of
+/// what it ate, where genotypes that never ate are producers at level 1
+pub fn signature_levels<'a>(records: impl Iterator<Item = &'a Predation>) -> HashMap<u32, f32> {
+    let mut diets: HashMap<u32, Vec<u32>> = HashMap::new();
+    for record in records {
+        diets.entry(record.predator).or_default().push(record.prey);
+    }
+
+    let mut levels: HashMap<u32, f32> = diets.keys().map(|&s| (s, 2.0)).collect();
+    for _ in 0..LEVEL_ITERATIONS {
+        levels = diets
+            .iter()
+            .map(|(&predator, prey)| {
+                let total: f32 = prey
+                    .iter()
+                    .map(|p| levels.get(p).copied().unwrap_or(1.0))
+                    .sum();
+                (predator, 1.0 + total / prey.len() as f32)
+            })
+            .collect();
+    }
+    levels
+}
+
+/// Bin every living entity by the trophic level of its genotype signature
+pub fn trophic_levels<'a>(
+    world: &World,
+    records: impl Iterator<Item = &'a Predation>,
+) -> TrophicLevels {
+    let levels = signature_levels(records);
+    let mut counts = TrophicLevels::default();
+    for (_, genes) in world.query::<&Genes>().iter() {
+        match levels.get(&genes.signature()).copied().unwrap_or(1.0) {
+            level if level < 1.5 => counts.producers += 1,
+            level if level < 2.5 => counts.herbivores += 1,
+            level if level < 3.5 => counts.carnivores += 1,
+            _ => counts.apex += 1,
+        }
+    }
+    counts
+}
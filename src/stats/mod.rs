@@ -1,10 +1,22 @@
-use crate::components::{Energy, Position};
-use crate::genes::Genes;
+use crate::components::{Energy, MovementType, Position};
+use crate::genes::{Genes, Pedigree, Phenotype};
 use hecs::World;
 use rayon::prelude::*;
 use serde::Serialize;
 use std::collections::HashMap;
 
+mod autocorrelation;
+mod diff;
+mod diversity;
+mod foodweb;
+mod series;
+mod species;
+pub use autocorrelation::morans_i;
+pub use diversity::cell_diversity_map;
+pub use foodweb::{trophic_levels, FoodWebLog, TrophicLevels};
+pub use series::{PopulationSample, PopulationSeries};
+pub use species::{detect_species, Species};
+
 /// Entity type classification based on dominant traits
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum EntityType {
@@ -15,14 +27,26 @@ pub enum EntityType {
     Mixed,
 }
 
+/// Colour difference over which soft classification fades from one side of a bucket edge
+/// to the other; around 0.05 either side of an edge, membership is still about 73/27
+const SOFT_EDGE_WIDTH: f32 = 0.05;
+
 /// Comprehensive simulation statistics
 #[derive(Debug, Clone, Serialize)]
 pub struct SimulationStats {
     pub total_entities: usize,
     pub entity_counts: HashMap<EntityType, usize>,
+    pub soft_entity_counts: HashMap<EntityType, f32>, // Summed membership weights, see `classify_by_color_soft`
     pub average_metrics: EntityMetrics,
-    pub population_density: f32,
+    pub trait_extremes: EntityExtremes,
+    pub population_cap_fraction: f32, // Population over the cap, which drives birth and death rates
+    pub spatial_density: f32,         // Entities per unit of world area
     pub world_center_drift: (f32, f32),
+    pub average_heading: (f32, f32), // Mean unit heading of migratory entities
+    pub species: Vec<Species>,       // Gene-space clusters, largest first
+    pub season_phase: f32,           // Progress through the seasonal cycle, in [0, 1)
+    pub trophic_levels: TrophicLevels, // Feeding level of each living entity, from recent meals
+    pub mating_relatedness: f32, // Mean relatedness of the parents of living sexually produced entities
 }
 
 /// Average metrics across all entities
@@ -36,36 +60,81 @@ pub struct EntityMetrics {
     pub average_energy_efficiency: f32,
 }
 
+/// Lowest and highest value of a trait across the population
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TraitRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Extremes of the traits averaged in `EntityMetrics`, showing whether outlying
+/// phenotypes survive
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EntityExtremes {
+    pub speed: TraitRange,
+    pub sense_radius: TraitRange,
+    pub energy_efficiency: TraitRange,
+    pub reproduction_rate: TraitRange,
+    pub size: TraitRange,
+}
+
 impl SimulationStats {
-    pub fn from_world(world: &World, max_population: f32, entity_scale: f32) -> Self {
+    /// Statistics of the entities in `world`, at `season_phase` through the seasonal cycle.
+    /// Species detection is the costliest part, so it is left to the caller, which may
+    /// reuse an earlier `detect_species` result.
+    pub fn from_world(
+        world: &World,
+        max_population: f32,
+        entity_scale: f32,
+        species: Vec<Species>,
+        season_phase: f32,
+    ) -> Self {
         let total_entities = world.len();
 
         // Calculate entity type distribution
         let entity_counts = Self::classify_entities(world);
+        let soft_entity_counts = Self::classify_entities_soft(world);
 
-        // Calculate average metrics
-        let average_metrics = Self::calculate_average_metrics(world, total_entities as usize);
+        // Calculate average metrics and trait extremes
+        let (average_metrics, trait_extremes) =
+            Self::calculate_average_metrics(world, total_entities as usize);
 
-        // Calculate population density
-        let population_density = total_entities as f32 / (max_population * entity_scale);
+        // Calculate how full the population is, as a fraction of the cap
+        let cap = max_population * entity_scale;
+        let population_cap_fraction = if cap > 0.0 {
+            total_entities as f32 / cap
+        } else {
+            0.0
+        };
 
         // Calculate world center drift
         let world_center_drift = Self::calculate_world_center_drift(world, total_entities as usize);
 
+        let average_heading = Self::calculate_average_heading(world);
+        let mating_relatedness = Self::calculate_mating_relatedness(world);
+
         Self {
             total_entities: total_entities as usize,
             entity_counts,
+            soft_entity_counts,
             average_metrics,
-            population_density,
+            trait_extremes,
+            population_cap_fraction,
+            spatial_density: 0.0,
             world_center_drift,
+            average_heading,
+            species,
+            season_phase,
+            trophic_levels: TrophicLevels::default(),
+            mating_relatedness,
         }
     }
 
     fn classify_entities(world: &World) -> HashMap<EntityType, usize> {
         let mut counts = HashMap::new();
 
-        for (_, (genes,)) in world.query::<(&Genes,)>().iter() {
-            let color = genes.get_color();
+        for (_, (genes, phenotype)) in world.query::<(&Genes, Option<&Phenotype>)>().iter() {
+            let color = phenotype.map_or_else(|| genes.get_color(), |p| p.color.clone());
             let entity_type = Self::classify_by_color(&color);
             *counts.entry(entity_type).or_insert(0) += 1;
         }
@@ -73,6 +142,57 @@ impl SimulationStats {
         counts
     }
 
+    /// Population of each type with every entity split across types by its membership
+    /// weights, so the counts change smoothly as colours drift across bucket edges
+    fn classify_entities_soft(world: &World) -> HashMap<EntityType, f32> {
+        let mut counts = HashMap::new();
+        for (_, (genes, phenotype)) in world.query::<(&Genes, Option<&Phenotype>)>().iter() {
+            let color = phenotype.map_or_else(|| genes.get_color(), |p| p.color.clone());
+            for (entity_type, weight) in Self::classify_by_color_soft(&color) {
+                *counts.entry(entity_type).or_insert(0.0) += weight;
+            }
+        }
+        counts
+    }
+
+    /// Membership weights summing to 1, from the same rules as `classify_by_color` with
+    /// each threshold softened by a logistic of width `SOFT_EDGE_WIDTH`. The rules keep
+    /// their order, each type taking its share of what earlier types left, and `Mixed`
+    /// takes the rest, so a colour far from every edge gets its hard type with weight ~1.
+    fn classify_by_color_soft(color: &crate::components::Color) -> [(EntityType, f32); 5] {
+        let above = |value: f32, threshold: f32| {
+            1.0 / (1.0 + (-(value - threshold) / SOFT_EDGE_WIDTH).exp())
+        };
+        let (r, g, b) = (color.r, color.g, color.b);
+        let rules = [
+            (
+                EntityType::RedDominant,
+                above(r, 0.6) * above(r, g) * above(r, b),
+            ),
+            (
+                EntityType::GreenDominant,
+                above(g, 0.6) * above(g, r) * above(g, b),
+            ),
+            (
+                EntityType::BlueDominant,
+                above(b, 0.6) * above(b, r) * above(b, g),
+            ),
+            (
+                EntityType::Purple,
+                above(r, 0.5) * above(b, 0.5) * above(0.4, g),
+            ),
+        ];
+
+        let mut weights = [(EntityType::Mixed, 0.0); 5];
+        let mut remaining = 1.0;
+        for (slot, (entity_type, match_degree)) in weights.iter_mut().zip(rules) {
+            *slot = (entity_type, remaining * match_degree);
+            remaining *= 1.0 - match_degree;
+        }
+        weights[4] = (EntityType::Mixed, remaining);
+        weights
+    }
+
     fn classify_by_color(color: &crate::components::Color) -> EntityType {
         let r = color.r;
         let g = color.g;
@@ -100,9 +220,12 @@ impl SimulationStats {
         }
     }
 
-    fn calculate_average_metrics(world: &World, total_entities: usize) -> EntityMetrics {
+    fn calculate_average_metrics(
+        world: &World,
+        total_entities: usize,
+    ) -> (EntityMetrics, EntityExtremes) {
         if total_entities == 0 {
-            return EntityMetrics {
+            let metrics = EntityMetrics {
                 average_energy: 0.0,
                 average_speed: 0.0,
                 average_size: 0.0,
@@ -110,33 +233,42 @@ impl SimulationStats {
                 average_sense_radius: 0.0,
                 average_energy_efficiency: 0.0,
             };
+            return (metrics, EntityExtremes::default());
         }
 
-        let gene_stats = world
+        // Sums, minima and maxima, each as [speed, sense, efficiency, repro, size]
+        let empty = || ([0.0f32; 5], [f32::MAX; 5], [f32::MIN; 5]);
+        let (gene_stats, mins, maxs) = world
             .query::<(&Genes,)>()
             .iter()
             .par_bridge()
-            .fold(
-                || [0.0f32; 6], // [speed, sense, efficiency, repro, size, energy]
-                |mut stats, (_, (genes,))| {
-                    stats[0] += genes.speed();
-                    stats[1] += genes.sense_radius();
-                    stats[2] += genes.energy_efficiency();
-                    stats[3] += genes.reproduction_rate();
-                    stats[4] += genes.size_factor();
-                    stats[5] += 0.0; // Will be calculated separately
-                    stats
-                },
-            )
-            .reduce(
-                || [0.0f32; 6],
-                |mut a, b| {
-                    for i in 0..6 {
-                        a[i] += b[i];
-                    }
-                    a
-                },
-            );
+            .fold(empty, |(mut sums, mut mins, mut maxs), (_, (genes,))| {
+                let values = [
+                    genes.speed(),
+                    genes.sense_radius(),
+                    genes.energy_efficiency(),
+                    genes.reproduction_rate(),
+                    genes.size_factor(),
+                ];
+                for i in 0..5 {
+                    sums[i] += values[i];
+                    mins[i] = mins[i].min(values[i]);
+                    maxs[i] = maxs[i].max(values[i]);
+                }
+                (sums, mins, maxs)
+            })
+            .reduce(empty, |(mut sums, mut mins, mut maxs), (s, lo, hi)| {
+                for i in 0..5 {
+                    sums[i] += s[i];
+                    mins[i] = mins[i].min(lo[i]);
+                    maxs[i] = maxs[i].max(hi[i]);
+                }
+                (sums, mins, maxs)
+            });
+        let range = |i: usize| TraitRange {
+            min: mins[i],
+            max: maxs[i],
+        };
 
         let avg_energy = world
             .query::<(&Energy,)>()
@@ -146,14 +278,22 @@ impl SimulationStats {
             .sum::<f32>()
             / total_entities as f32;
 
-        EntityMetrics {
+        let metrics = EntityMetrics {
             average_energy: avg_energy,
             average_speed: gene_stats[0] / total_entities as f32,
             average_size: gene_stats[4] / total_entities as f32,
             average_reproduction_rate: gene_stats[3] / total_entities as f32,
             average_sense_radius: gene_stats[1] / total_entities as f32,
             average_energy_efficiency: gene_stats[2] / total_entities as f32,
-        }
+        };
+        let extremes = EntityExtremes {
+            speed: range(0),
+            sense_radius: range(1),
+            energy_efficiency: range(2),
+            reproduction_rate: range(3),
+            size: range(4),
+        };
+        (metrics, extremes)
     }
 
     fn calculate_world_center_drift(world: &World, total_entities: usize) -> (f32, f32) {
@@ -177,6 +317,37 @@ impl SimulationStats {
         (sum_x / total_entities as f32, sum_y / total_entities as f32)
     }
 
+    fn calculate_average_heading(world: &World) -> (f32, f32) {
+        let (sum_x, sum_y, count) = world
+            .query::<&Genes>()
+            .iter()
+            .filter(|(_, genes)| genes.behavior.movement_style.style == MovementType::Migratory)
+            .map(|(_, genes)| genes.behavior.movement_style.migration_heading)
+            .fold((0.0f32, 0.0f32, 0usize), |(x, y, n), heading| {
+                (x + heading.cos(), y + heading.sin(), n + 1)
+            });
+
+        if count == 0 {
+            return (0.0, 0.0);
+        }
+        (sum_x / count as f32, sum_y / count as f32)
+    }
+
+    /// Average relatedness of the matings behind the living population, as an inbreeding
+    /// measure. Founders and asexual offspring carry no pedigree and don't count.
+    fn calculate_mating_relatedness(world: &World) -> f32 {
+        let (sum, count) = world
+            .query::<&Pedigree>()
+            .iter()
+            .fold((0.0f32, 0usize), |(sum, n), (_, pedigree)| {
+                (sum + pedigree.parent_relatedness, n + 1)
+            });
+        if count == 0 {
+            return 0.0;
+        }
+        sum / count as f32
+    }
+
     /// Format statistics for console output
     pub fn format_summary(&self, step: u32) -> String {
         let red_count = self
@@ -215,18 +386,36 @@ impl SimulationStats {
     /// Format detailed metrics for analysis
     pub fn format_detailed(&self, step: u32) -> String {
         format!(
-            "Step {}: Total={}, Density={:.3}, AvgEnergy={:.1}, AvgSpeed={:.2}, AvgSense={:.1}, AvgEfficiency={:.2}, AvgRepro={:.3}, AvgSize={:.2}, Drift=({:.1}, {:.1})",
+            "Step {}: Total={}, CapFraction={:.3}, Density={:.5}, AvgEnergy={:.1}, AvgSpeed={:.2}, AvgSense={:.1}, AvgEfficiency={:.2}, AvgRepro={:.3}, AvgSize={:.2}, Speed=[{:.2}, {:.2}], Sense=[{:.1}, {:.1}], Efficiency=[{:.2}, {:.2}], Repro=[{:.3}, {:.3}], Size=[{:.2}, {:.2}], Drift=({:.1}, {:.1}), Species={}, Season={:.2}, Trophic=[{}, {}, {}, {}], Relatedness={:.3}",
             step,
             self.total_entities,
-            self.population_density,
+            self.population_cap_fraction,
+            self.spatial_density,
             self.average_metrics.average_energy,
             self.average_metrics.average_speed,
             self.average_metrics.average_sense_radius,
             self.average_metrics.average_energy_efficiency,
             self.average_metrics.average_reproduction_rate,
             self.average_metrics.average_size,
+            self.trait_extremes.speed.min,
+            self.trait_extremes.speed.max,
+            self.trait_extremes.sense_radius.min,
+            self.trait_extremes.sense_radius.max,
+            self.trait_extremes.energy_efficiency.min,
+            self.trait_extremes.energy_efficiency.max,
+            self.trait_extremes.reproduction_rate.min,
+            self.trait_extremes.reproduction_rate.max,
+            self.trait_extremes.size.min,
+            self.trait_extremes.size.max,
             self.world_center_drift.0,
             self.world_center_drift.1,
+            self.species.len(),
+            self.season_phase,
+            self.trophic_levels.producers,
+            self.trophic_levels.herbivores,
+            self.trophic_levels.carnivores,
+            self.trophic_levels.apex,
+            self.mating_relatedness,
         )
     }
 }
@@ -0,0 +1,56 @@
+use super::Species;
+use crate::components::Energy;
+use hecs::World;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// One point on the live population charts
+#[derive(Debug, Clone, Serialize)]
+pub struct PopulationSample {
+    pub step: u32,
+    pub count: u32,
+    pub average_energy: f32,
+    pub species_counts: Vec<usize>, // Size of each detected species, largest first
+}
+
+impl PopulationSample {
+    pub fn from_world(world: &World, step: u32, species: &[Species]) -> Self {
+        let count = world.len();
+        let total_energy: f32 = world
+            .query::<&Energy>()
+            .iter()
+            .map(|(_, energy)| energy.current)
+            .sum();
+        Self {
+            step,
+            count,
+            average_energy: if count > 0 {
+                total_energy / count as f32
+            } else {
+                0.0
+            },
+            species_counts: species.iter().map(|s| s.size).collect(),
+        }
+    }
+}
+
+/// The most recent samples, oldest first, dropping the oldest once `capacity` is reached
+#[derive(Debug, Clone, Default)]
+pub struct PopulationSeries {
+    samples: VecDeque<PopulationSample>,
+}
+
+impl PopulationSeries {
+    pub fn record(&mut self, sample: PopulationSample, capacity: usize) {
+        while self.samples.len() >= capacity.max(1) {
+            self.samples.pop_front();
+        }
+        if capacity > 0 {
+            self.samples.push_back(sample);
+        }
+    }
+
+    pub fn samples(&self) -> &VecDeque<PopulationSample> {
+        &self.samples
+    }
+}
@@ -0,0 +1,118 @@
+use crate::genes::{Genes, GENE_BOUNDS, TRAIT_COUNT};
+use hecs::World;
+use rayon::prelude::*;
+use serde::Serialize;
+
+/// Genotypes closer than this (RMS distance over traits normalised to [0, 1]) share a species
+const SPECIES_RADIUS: f32 = 0.2;
+/// Upper bound on clusters, keeping detection O(n·k) even for a fully random population
+const MAX_SPECIES: usize = 32;
+
+type TraitVector = [f32; TRAIT_COUNT];
+
+/// An emergent species: a cluster of similar genotypes in gene space
+#[derive(Debug, Clone, Serialize)]
+pub struct Species {
+    pub size: usize,
+    pub centroid: TraitVector, // Mean trait values, in the order of `Genes::traits`
+}
+
+/// Cluster the population's genes with a single leader pass to seed centroids, followed by a
+/// parallel k-means refinement step. Species are returned largest first.
+pub fn detect_species(world: &World) -> Vec<Species> {
+    let points: Vec<TraitVector> = world
+        .query::<&Genes>()
+        .iter()
+        .map(|(_, genes)| normalize(genes.traits()))
+        .collect();
+
+    let seeds = seed_centroids(&points);
+    let mut species: Vec<Species> = refine(&points, &seeds)
+        .into_iter()
+        .filter(|s| s.size > 0)
+        .map(|s| Species {
+            size: s.size,
+            centroid: denormalize(s.centroid),
+        })
+        .collect();
+    species.sort_by(|a, b| b.size.cmp(&a.size));
+    species
+}
+
+/// Leader clustering: each point joins the nearest centroid within `SPECIES_RADIUS`
+/// or starts a new one, until `MAX_SPECIES` is reached
+fn seed_centroids(points: &[TraitVector]) -> Vec<TraitVector> {
+    let mut centroids: Vec<(TraitVector, usize)> = Vec::new();
+    for point in points {
+        match nearest(point, centroids.iter().map(|(c, _)| c)) {
+            Some((i, distance)) if distance <= SPECIES_RADIUS || centroids.len() >= MAX_SPECIES => {
+                let (centroid, count) = &mut centroids[i];
+                *count += 1;
+                for (c, p) in centroid.iter_mut().zip(point) {
+                    *c += (p - *c) / *count as f32;
+                }
+            }
+            _ => centroids.push((*point, 1)),
+        }
+    }
+    centroids.into_iter().map(|(c, _)| c).collect()
+}
+
+/// One k-means step: assign every point to its nearest seed in parallel and recompute means
+fn refine(points: &[TraitVector], seeds: &[TraitVector]) -> Vec<Species> {
+    let empty = || vec![([0.0; TRAIT_COUNT], 0usize); seeds.len()];
+    let sums = points
+        .par_iter()
+        .fold(empty, |mut sums, point| {
+            if let Some((i, _)) = nearest(point, seeds.iter()) {
+                sums[i].1 += 1;
+                for (s, p) in sums[i].0.iter_mut().zip(point) {
+                    *s += p;
+                }
+            }
+            sums
+        })
+        .reduce(empty, |mut a, b| {
+            for ((sum_a, count_a), (sum_b, count_b)) in a.iter_mut().zip(b) {
+                *count_a += count_b;
+                sum_a.iter_mut().zip(sum_b).for_each(|(x, y)| *x += y);
+            }
+            a
+        });
+
+    sums.into_iter()
+        .map(|(sum, size)| Species {
+            size,
+            centroid: sum.map(|s| s / size.max(1) as f32),
+        })
+        .collect()
+}
+
+fn nearest<'a>(
+    point: &TraitVector,
+    centroids: impl Iterator<Item = &'a TraitVector>,
+) -> Option<(usize, f32)> {
+    centroids
+        .map(|c| rms_distance(point, c))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+fn rms_distance(a: &TraitVector, b: &TraitVector) -> f32 {
+    let sum_sq: f32 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+    (sum_sq / TRAIT_COUNT as f32).sqrt()
+}
+
+fn normalize(traits: TraitVector) -> TraitVector {
+    std::array::from_fn(|i| {
+        let bounds = &GENE_BOUNDS[i];
+        (traits[i] - bounds.min) / (bounds.max - bounds.min)
+    })
+}
+
+fn denormalize(traits: TraitVector) -> TraitVector {
+    std::array::from_fn(|i| {
+        let bounds = &GENE_BOUNDS[i];
+        bounds.min + traits[i] * (bounds.max - bounds.min)
+    })
+}
@@ -1,8 +1,9 @@
 use super::*;
 use crate::components::{Color, Energy, Position, Size};
 use crate::genes::Genes;
+use crate::genes::GENE_BOUNDS;
 use hecs::World;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 
 fn create_test_world() -> World {
     let mut world = World::new();
@@ -65,11 +66,11 @@ fn create_test_world() -> World {
 #[test]
 fn test_simulation_stats_creation() {
     let world = create_test_world();
-    let stats = SimulationStats::from_world(&world, 1000.0, 1.0);
+    let stats = SimulationStats::from_world(&world, 1000.0, 1.0, detect_species(&world), 0.0);
 
     assert_eq!(stats.total_entities, 10);
-    assert!(stats.population_density > 0.0);
-    assert!(stats.population_density <= 1.0);
+    assert!(stats.population_cap_fraction > 0.0);
+    assert!(stats.population_cap_fraction <= 1.0);
 }
 
 #[test]
@@ -143,10 +144,43 @@ fn test_classify_by_color() {
     );
 }
 
+#[test]
+fn test_soft_classification_splits_colours_near_bucket_edges() {
+    let weights = |r, g, b| {
+        let color = Color { r, g, b };
+        let weights = SimulationStats::classify_by_color_soft(&color);
+        let total: f32 = weights.iter().map(|(_, w)| w).sum();
+        assert!((total - 1.0).abs() < 1e-5, "{:?}", weights);
+        HashMap::from(weights)
+    };
+
+    // Orange just past the red threshold is mostly Red, partly Mixed
+    let orange = weights(0.62, 0.45, 0.1);
+    let (red, mixed) = (orange[&EntityType::RedDominant], orange[&EntityType::Mixed]);
+    assert!(red > mixed && mixed > 0.2, "{:?}", orange);
+    assert!(red + mixed > 0.99, "{:?}", orange);
+
+    // Nudging it across the threshold moves weight over smoothly, not all at once
+    let paler = weights(0.58, 0.45, 0.1);
+    assert!(paler[&EntityType::Mixed] > paler[&EntityType::RedDominant]);
+    assert!(paler[&EntityType::RedDominant] > 0.2);
+
+    // Far from every edge, the hard type gets nearly all the weight
+    let red = weights(0.9, 0.2, 0.2);
+    assert!(red[&EntityType::RedDominant] > 0.99);
+    let purple = weights(0.56, 0.1, 0.56);
+    let strongest = purple.iter().max_by(|a, b| a.1.total_cmp(b.1)).unwrap();
+    assert_eq!(*strongest.0, EntityType::Purple, "{:?}", purple);
+
+    let world = create_test_world();
+    let soft = SimulationStats::classify_entities_soft(&world);
+    assert!((soft.values().sum::<f32>() - world.len() as f32).abs() < 1e-3);
+}
+
 #[test]
 fn test_average_metrics_calculation() {
     let world = create_test_world();
-    let metrics = SimulationStats::calculate_average_metrics(&world, 10);
+    let (metrics, extremes) = SimulationStats::calculate_average_metrics(&world, 10);
 
     // All averages should be positive
     assert!(metrics.average_energy > 0.0);
@@ -160,6 +194,35 @@ fn test_average_metrics_calculation() {
     assert!(metrics.average_energy <= 100.0);
     assert!(metrics.average_speed <= 2.0);
     assert!(metrics.average_size <= 20.0);
+
+    // Every average lies within its range
+    assert!(extremes.speed.min <= metrics.average_speed);
+    assert!(metrics.average_speed <= extremes.speed.max);
+    assert!(extremes.size.min <= metrics.average_size);
+    assert!(metrics.average_size <= extremes.size.max);
+}
+
+#[test]
+fn test_extreme_phenotype_shows_in_max() {
+    let mut world = create_test_world();
+    let mut rng = thread_rng();
+    let mut sprinter = Genes::new_random(&mut rng);
+    sprinter.movement.speed = 2.95;
+    world.spawn((
+        Position { x: 0.0, y: 0.0 },
+        Energy {
+            current: 50.0,
+            max: 100.0,
+        },
+        sprinter.clone(),
+    ));
+
+    let stats = SimulationStats::from_world(&world, 1000.0, 1.0, detect_species(&world), 0.0);
+    assert_eq!(stats.trait_extremes.speed.max, sprinter.speed());
+    assert!(stats.average_metrics.average_speed < sprinter.speed());
+    assert!(stats
+        .format_detailed(1)
+        .contains(&format!("{:.2}]", sprinter.speed())));
 }
 
 #[test]
@@ -175,7 +238,7 @@ fn test_world_center_drift_calculation() {
 #[test]
 fn test_format_summary() {
     let world = create_test_world();
-    let stats = SimulationStats::from_world(&world, 1000.0, 1.0);
+    let stats = SimulationStats::from_world(&world, 1000.0, 1.0, detect_species(&world), 0.0);
     let summary = stats.format_summary(42);
 
     // Should contain step number
@@ -193,7 +256,7 @@ fn test_format_summary() {
 #[test]
 fn test_format_detailed() {
     let world = create_test_world();
-    let stats = SimulationStats::from_world(&world, 1000.0, 1.0);
+    let stats = SimulationStats::from_world(&world, 1000.0, 1.0, detect_species(&world), 0.0);
     let detailed = stats.format_detailed(42);
 
     // Should contain step number
@@ -247,11 +310,14 @@ fn test_entity_metrics_clone() {
 #[test]
 fn test_simulation_stats_clone() {
     let world = create_test_world();
-    let stats = SimulationStats::from_world(&world, 1000.0, 1.0);
+    let stats = SimulationStats::from_world(&world, 1000.0, 1.0, detect_species(&world), 0.0);
     let cloned = stats.clone();
 
     assert_eq!(stats.total_entities, cloned.total_entities);
-    assert_eq!(stats.population_density, cloned.population_density);
+    assert_eq!(
+        stats.population_cap_fraction,
+        cloned.population_cap_fraction
+    );
     assert_eq!(stats.world_center_drift, cloned.world_center_drift);
     assert_eq!(stats.entity_counts.len(), cloned.entity_counts.len());
 }
@@ -259,10 +325,10 @@ fn test_simulation_stats_clone() {
 #[test]
 fn test_empty_world_stats() {
     let world = World::new();
-    let stats = SimulationStats::from_world(&world, 1000.0, 1.0);
+    let stats = SimulationStats::from_world(&world, 1000.0, 1.0, detect_species(&world), 0.0);
 
     assert_eq!(stats.total_entities, 0);
-    assert_eq!(stats.population_density, 0.0);
+    assert_eq!(stats.population_cap_fraction, 0.0);
     assert_eq!(stats.entity_counts.len(), 0);
 }
 
@@ -280,3 +346,215 @@ fn test_entity_type_hash() {
     assert_eq!(map.get(&EntityType::BlueDominant), Some(&3));
     assert_eq!(map.get(&EntityType::Purple), None);
 }
+
+fn spawn_at(world: &mut World, genes: &Genes, x: f32, y: f32) {
+    world.spawn((Position { x, y }, genes.clone()));
+}
+
+#[test]
+fn test_cell_diversity_map_uniform_and_mixed_cells() {
+    let mut rng = thread_rng();
+    let mut world = World::new();
+    let grid = crate::spatial_grid::SpatialGrid::new(10.0);
+
+    let mut grazer = Genes::new_random(&mut rng);
+    grazer.behavior.movement_style.style = crate::components::MovementType::Grazing;
+    grazer.appearance.hue = 0.1;
+    let mut hunter = Genes::new_random(&mut rng);
+    hunter.behavior.movement_style.style = crate::components::MovementType::Predatory;
+    hunter.appearance.hue = 0.9;
+
+    // Two cells with one uniform genotype each, and a third evenly mixed
+    for i in 0..4 {
+        spawn_at(&mut world, &grazer, 1.0 + i as f32, 1.0);
+        spawn_at(&mut world, &hunter, 21.0 + i as f32, 1.0);
+        let mixed = if i % 2 == 0 { &grazer } else { &hunter };
+        spawn_at(&mut world, mixed, 41.0 + i as f32, 1.0);
+    }
+
+    let diversity: HashMap<_, _> = cell_diversity_map(&world, &grid).into_iter().collect();
+    assert_eq!(diversity.len(), 3);
+    assert_eq!(diversity[&(0, 0)], 0.0);
+    assert_eq!(diversity[&(2, 0)], 0.0);
+    assert!((diversity[&(4, 0)] - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_morans_i_detects_sorted_population() {
+    let mut rng = thread_rng();
+    let template = Genes::new_random(&mut rng);
+
+    // A 20x20 lattice where speed rises smoothly from west to east
+    let sorted_world = |speed_at: &mut dyn FnMut(usize) -> f32| {
+        let mut world = World::new();
+        let grid = crate::spatial_grid::SpatialGrid::new(10.0);
+        for i in 0..20 {
+            for j in 0..20 {
+                let mut genes = template.clone();
+                genes.movement.speed = speed_at(i);
+                let (x, y) = (i as f32 * 5.0, j as f32 * 5.0);
+                let entity = world.spawn((Position { x, y }, genes));
+                grid.insert(entity, x, y);
+            }
+        }
+        (world, grid)
+    };
+
+    let (world, grid) = sorted_world(&mut |column| 0.1 + column as f32 * 0.1);
+    let sorted = morans_i(&world, &grid, "speed", 7.5).unwrap();
+    assert!(sorted > 0.8, "sorted population I = {}", sorted);
+
+    let (world, grid) = sorted_world(&mut |_| rng.gen_range(0.1..2.0));
+    let shuffled = morans_i(&world, &grid, "speed", 7.5).unwrap();
+    assert!(shuffled.abs() < 0.3, "unsorted population I = {}", shuffled);
+
+    assert_eq!(morans_i(&world, &grid, "wingspan", 7.5), None);
+}
+
+#[test]
+fn test_detect_species_separates_genotype_clouds() {
+    let mut rng = thread_rng();
+    let mut world = World::new();
+
+    // Two clouds near opposite corners of gene space, jittered by 5% of each range
+    for (i, corner) in [0.15f32, 0.85].into_iter().enumerate() {
+        for _ in 0..60 {
+            let mut genes = Genes::new_random(&mut rng);
+            for (value, bounds) in genes.traits_mut().into_iter().zip(GENE_BOUNDS.iter()) {
+                let fraction = corner + rng.gen_range(-0.05..0.05);
+                *value = bounds.min + fraction * (bounds.max - bounds.min);
+            }
+            spawn_at(&mut world, &genes, i as f32, 0.0);
+        }
+    }
+
+    let species = detect_species(&world);
+    assert_eq!(species.len(), 2);
+    assert!(species.iter().all(|s| s.size == 60));
+    let speed_bounds = &GENE_BOUNDS[0];
+    let speeds: Vec<f32> = species
+        .iter()
+        .map(|s| (s.centroid[0] - speed_bounds.min) / (speed_bounds.max - speed_bounds.min))
+        .collect();
+    assert!(speeds.iter().any(|f| (f - 0.15).abs() < 0.03));
+    assert!(speeds.iter().any(|f| (f - 0.85).abs() < 0.03));
+}
+
+#[test]
+fn test_trophic_levels_of_two_level_chain() {
+    let mut rng = thread_rng();
+    let mut world = World::new();
+
+    let mut grazer = Genes::new_random(&mut rng);
+    grazer.behavior.movement_style.style = crate::components::MovementType::Grazing;
+    grazer.appearance.hue = 0.1;
+    let mut hunter = Genes::new_random(&mut rng);
+    hunter.behavior.movement_style.style = crate::components::MovementType::Predatory;
+    hunter.appearance.hue = 0.9;
+    for i in 0..5 {
+        spawn_at(&mut world, &grazer, i as f32, 0.0);
+    }
+    for i in 0..3 {
+        spawn_at(&mut world, &hunter, i as f32, 10.0);
+    }
+
+    let mut log = FoodWebLog::new(100);
+    assert_eq!(trophic_levels(&world, log.records()).producers, 8);
+
+    log.record(10, &hunter, &grazer);
+    log.record(20, &hunter, &grazer);
+    let levels = trophic_levels(&world, log.records());
+    assert_eq!(
+        levels,
+        TrophicLevels {
+            producers: 5,
+            herbivores: 3,
+            carnivores: 0,
+            apex: 0,
+        }
+    );
+
+    // Once the meals leave the window, the hunters count as producers again
+    log.prune(200);
+    assert_eq!(trophic_levels(&world, log.records()).producers, 8);
+}
+
+#[test]
+fn test_diff_reports_per_field_deltas() {
+    let stats =
+        |total: usize, red: usize, energy: f32, species: usize, apex: usize| SimulationStats {
+            total_entities: total,
+            entity_counts: HashMap::from([(EntityType::RedDominant, red)]),
+            soft_entity_counts: HashMap::new(),
+            average_metrics: EntityMetrics {
+                average_energy: energy,
+                average_speed: 1.0,
+                average_size: 5.0,
+                average_reproduction_rate: 0.1,
+                average_sense_radius: 50.0,
+                average_energy_efficiency: 1.5,
+            },
+            trait_extremes: EntityExtremes::default(),
+            population_cap_fraction: total as f32 / 1000.0,
+            spatial_density: 0.01,
+            world_center_drift: (0.0, 0.0),
+            average_heading: (0.0, 0.0),
+            species: (0..species)
+                .map(|_| Species {
+                    size: 1,
+                    centroid: Default::default(),
+                })
+                .collect(),
+            season_phase: 0.0,
+            trophic_levels: TrophicLevels {
+                apex,
+                ..TrophicLevels::default()
+            },
+            mating_relatedness: 0.0,
+        };
+    let mut baseline = stats(200, 50, 40.0, 3, 2);
+    let mut variant = stats(150, 20, 45.5, 5, 0);
+    variant.entity_counts.insert(EntityType::GreenDominant, 30);
+    baseline.average_metrics.average_speed = 1.25;
+    variant.average_metrics.average_speed = 1.0;
+
+    let diff = baseline.diff(&variant);
+    assert_eq!(diff.total_entities, -50);
+    assert_eq!(diff.entity_counts[&EntityType::RedDominant], -30);
+    assert_eq!(diff.entity_counts[&EntityType::GreenDominant], 30);
+    assert!(!diff.entity_counts.contains_key(&EntityType::Purple));
+    assert_eq!(diff.average_metrics.average_energy, 5.5);
+    assert_eq!(diff.average_metrics.average_speed, -0.25);
+    assert_eq!(diff.average_metrics.average_size, 0.0);
+    assert!((diff.population_cap_fraction + 0.05).abs() < 1e-6);
+    assert_eq!(diff.species, 2);
+    assert_eq!(diff.apex, -2);
+
+    let text = diff.format();
+    assert!(text.contains("Total=-50"), "{}", text);
+    assert!(text.contains("Red:-30 Green:+30 Blue:+0"), "{}", text);
+    assert!(text.contains("AvgEnergy=+5.5"), "{}", text);
+    assert!(text.contains("Species=+2"), "{}", text);
+
+    // The reverse comparison negates every delta
+    assert_eq!(variant.diff(&baseline).total_entities, 50);
+}
+
+#[test]
+fn test_mating_relatedness_averages_sexually_produced_entities() {
+    let mut world = create_test_world();
+    assert_eq!(
+        SimulationStats::from_world(&world, 1000.0, 1.0, detect_species(&world), 0.0)
+            .mating_relatedness,
+        0.0
+    );
+
+    let founders = Pedigree::offspring((1, None), (2, None));
+    let inbred = Pedigree::offspring((3, Some(&founders)), (4, Some(&founders)));
+    world.spawn((Genes::new_random(&mut thread_rng()), founders));
+    world.spawn((Genes::new_random(&mut thread_rng()), inbred));
+
+    let stats = SimulationStats::from_world(&world, 1000.0, 1.0, detect_species(&world), 0.0);
+    assert_eq!(stats.mating_relatedness, 0.25);
+    assert!(stats.format_detailed(1).contains("Relatedness=0.250"));
+}
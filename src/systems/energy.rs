@@ -1,5 +1,5 @@
 use crate::components::Size;
-use crate::config::SimulationConfig;
+use crate::config::{SimulationConfig, SizeGrowth};
 use crate::genes::Genes;
 
 /// Energy system - handles energy consumption and metabolism
@@ -15,11 +15,25 @@ impl EnergySystem {
     ) {
         // Energy changes based on genes and size (larger entities cost more to maintain)
         let size_energy_cost = size.radius * config.energy.size_energy_cost_factor;
-        *new_energy -= (genes.energy_loss_rate() + size_energy_cost) / genes.energy_efficiency();
+        // Sensing isn't free, so sense radius trades off against upkeep
+        let sense_energy_cost = genes.sense_radius() * config.energy.sense_cost_factor;
+        let upkeep = (genes.energy_loss_rate() + size_energy_cost + sense_energy_cost)
+            / genes.energy_efficiency();
+        // Efficiency has diminishing returns: no genotype lives for free
+        *new_energy -= upkeep.max(config.energy.metabolic_floor);
     }
 
     pub fn calculate_new_size(&self, energy: f32, genes: &Genes, config: &SimulationConfig) -> f32 {
-        (energy / 15.0 * genes.size_factor()).clamp(
+        let energy = energy.min(config.energy.energy_ceiling());
+        let radius = match config.energy.size_growth {
+            SizeGrowth::Linear => energy / 15.0 * genes.size_factor(),
+            // Size factor speeds growth toward the asymptote rather than raising it
+            SizeGrowth::Saturating => {
+                let growth = config.energy.size_growth_rate * genes.size_factor();
+                config.energy.size_asymptote * (1.0 - (-growth * energy.max(0.0)).exp())
+            }
+        };
+        radius.clamp(
             config.physics.min_entity_radius,
             config.physics.max_entity_radius,
         )
@@ -78,4 +92,56 @@ mod tests {
         // Energy can go below 0 due to energy loss, but should be finite
         assert!(new_energy.is_finite());
     }
+
+    #[test]
+    fn test_sense_radius_costs_energy() {
+        let system = EnergySystem;
+        let size = Size { radius: 5.0 };
+        let config = SimulationConfig::default();
+        let mut low_sense = Genes::new_random(&mut thread_rng());
+        low_sense.movement.sense_radius = 10.0;
+        let mut high_sense = low_sense.clone();
+        high_sense.movement.sense_radius = 150.0;
+
+        let mut low_energy = 50.0;
+        let mut high_energy = 50.0;
+        system.update_energy(&mut low_energy, &size, &low_sense, &config);
+        system.update_energy(&mut high_energy, &size, &high_sense, &config);
+
+        assert!(high_energy < low_energy);
+    }
+
+    #[test]
+    fn test_metabolic_floor_bounds_extreme_efficiency() {
+        let system = EnergySystem;
+        let size = Size { radius: 1.0 };
+        let config = SimulationConfig::default();
+        let mut genes = Genes::new_random(&mut thread_rng());
+        genes.energy.efficiency = 1000.0;
+
+        let mut energy = 50.0;
+        for _ in 0..10 {
+            let before = energy;
+            system.update_energy(&mut energy, &size, &genes, &config);
+            assert!(before - energy >= config.energy.metabolic_floor - 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_saturating_growth_is_monotonic_and_bounded() {
+        let system = EnergySystem;
+        let genes = Genes::new_random(&mut thread_rng());
+        let mut config = SimulationConfig::default();
+        config.energy.size_growth = SizeGrowth::Saturating;
+        config.energy.size_asymptote = 12.0;
+        config.energy.max_energy_factor = 10.0;
+
+        let sizes: Vec<f32> = (0..=100)
+            .map(|i| system.calculate_new_size(i as f32 * 10.0, &genes, &config))
+            .collect();
+
+        assert!(sizes.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert!(sizes.iter().all(|&size| size <= 12.0));
+        assert!(sizes[100] > sizes[0]);
+    }
 }
@@ -1,18 +1,20 @@
-use crate::components::{Energy, Position, Size};
+use crate::components::{Position, Size};
 use crate::config::SimulationConfig;
-use crate::genes::Genes;
-use hecs::{Entity, World};
+use crate::genes::{Genes, Phenotype};
+use crate::snapshot::{EntitySnapshot, Neighbor};
+use hecs::Entity;
 
 pub struct InteractionSystem;
 
 pub struct InteractionParams<'a> {
     pub new_energy: &'a mut f32,
-    pub eaten_entity: &'a mut Option<Entity>,
+    pub eaten_entities: &'a mut Vec<(Entity, f32)>, // Prey and the net energy it gave
     pub new_pos: &'a Position,
     pub size: &'a Size,
     pub genes: &'a Genes,
+    pub phenotype: &'a Phenotype, // Cached from `genes`
     pub nearby_entities: &'a [Entity],
-    pub world: &'a World,
+    pub neighbors: &'a EntitySnapshot, // Components of nearby entities
     pub config: &'a SimulationConfig,
 }
 
@@ -20,75 +22,97 @@ impl InteractionSystem {
     pub fn handle_interactions(&self, params: InteractionParams) {
         let InteractionParams {
             new_energy,
-            eaten_entity,
+            eaten_entities,
             new_pos,
             size,
             genes,
+            phenotype,
             nearby_entities,
-            world,
+            neighbors,
             config,
         } = params;
+        let energy_cap = config.energy.energy_ceiling();
         for &entity in nearby_entities {
-            if self.can_interact_with_entity(entity, new_pos, size, genes, world, config) {
-                self.process_interaction(entity, new_energy, eaten_entity, genes, world);
-                break; // Only interact with one entity per frame
+            // Stop once the interaction budget is spent or the entity is full
+            if eaten_entities.len() >= config.physics.max_interactions_per_step
+                || *new_energy >= energy_cap
+            {
+                break;
+            }
+            if self.can_interact_with_entity(
+                entity, new_pos, size, genes, phenotype, neighbors, config,
+            ) {
+                if let Some(energy_gained) =
+                    self.process_interaction(entity, size, genes, phenotype, neighbors, config)
+                {
+                    let before = *new_energy;
+                    let net_gain = energy_gained - config.energy.predation_energy_overhead;
+                    *new_energy = (*new_energy + net_gain).min(energy_cap);
+                    eaten_entities.push((entity, *new_energy - before));
+                }
             }
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn can_interact_with_entity(
         &self,
         entity: Entity,
         new_pos: &Position,
         size: &Size,
         genes: &Genes,
-        world: &World,
+        phenotype: &Phenotype,
+        neighbors: &EntitySnapshot,
         config: &SimulationConfig,
     ) -> bool {
-        if let Ok(nearby_pos) = world.get::<&Position>(entity) {
-            if let Ok(nearby_genes) = world.get::<&Genes>(entity) {
-                if let Ok(nearby_energy) = world.get::<&Energy>(entity) {
-                    if let Ok(nearby_size) = world.get::<&Size>(entity) {
-                        if nearby_energy.current > 0.0 {
-                            let distance = self.calculate_distance(new_pos, &nearby_pos);
-                            if distance < (size.radius + config.physics.interaction_radius_offset) {
-                                return genes.can_eat(&nearby_genes, &nearby_size, size);
-                            }
-                        }
-                    }
-                }
+        // Entities starving within their grace period are still prey
+        if let Some(Neighbor {
+            pos: nearby_pos,
+            genes: nearby_genes,
+            phenotype: nearby_phenotype,
+            size: nearby_size,
+            ..
+        }) = neighbors.get(entity)
+        {
+            let distance = self.calculate_distance(new_pos, nearby_pos);
+            if distance < (size.radius + config.physics.interaction_radius_offset) {
+                return genes.can_eat(nearby_genes, nearby_size, size)
+                    && (config.physics.allow_cannibalism
+                        || !self.is_kin(phenotype, nearby_phenotype, config));
             }
         }
         false
     }
 
+    /// Whether `other` is close enough in gene space to be recognised as kin
+    fn is_kin(&self, phenotype: &Phenotype, other: &Phenotype, config: &SimulationConfig) -> bool {
+        phenotype.similarity(other) < config.physics.kin_recognition_threshold
+    }
+
     fn calculate_distance(&self, pos1: &Position, pos2: &Position) -> f32 {
         ((pos2.x - pos1.x).powi(2) + (pos2.y - pos1.y).powi(2)).sqrt()
     }
 
+    /// Energy a predator of the given size gains from eating `entity`
     fn process_interaction(
         &self,
         entity: Entity,
-        new_energy: &mut f32,
-        eaten_entity: &mut Option<Entity>,
+        size: &Size,
         genes: &Genes,
-        world: &World,
-    ) {
-        if let Ok(nearby_energy) = world.get::<&Energy>(entity) {
-            if let Ok(nearby_size) = world.get::<&Size>(entity) {
-                if let Ok(nearby_genes) = world.get::<&Genes>(entity) {
-                    *eaten_entity = Some(entity);
-                    let energy_gained = genes.get_energy_gain(
-                        nearby_energy.current,
-                        &nearby_size,
-                        &Size { radius: 1.0 },
-                        &nearby_genes,
-                    );
-                    *new_energy =
-                        (*new_energy + energy_gained - 0.5).min(genes.energy_efficiency() * 100.0);
-                }
-            }
-        }
+        phenotype: &Phenotype,
+        neighbors: &EntitySnapshot,
+        config: &SimulationConfig,
+    ) -> Option<f32> {
+        let nearby = neighbors.get(entity)?;
+        // A starving body has no energy left to give
+        Some(genes.get_energy_gain(
+            nearby.energy.current.max(0.0),
+            nearby.size,
+            size,
+            phenotype,
+            nearby.phenotype,
+            &config.energy,
+        ))
     }
 }
 
@@ -1,5 +1,5 @@
 use super::*;
-use crate::components::{Position, Size};
+use crate::components::{Energy, Position, Size};
 use crate::genes::Genes;
 use hecs::World;
 use rand::prelude::*;
@@ -8,7 +8,7 @@ use rand::prelude::*;
 fn test_interaction_system_handle_interactions() {
     let system = InteractionSystem;
     let mut new_energy = 50.0;
-    let mut eaten_entity = None;
+    let mut eaten_entities = Vec::new();
     let new_pos = Position { x: 0.0, y: 0.0 };
     let size = Size { radius: 10.0 };
     let mut rng = thread_rng();
@@ -19,18 +19,68 @@ fn test_interaction_system_handle_interactions() {
 
     system.handle_interactions(InteractionParams {
         new_energy: &mut new_energy,
-        eaten_entity: &mut eaten_entity,
+        eaten_entities: &mut eaten_entities,
         new_pos: &new_pos,
         size: &size,
         genes: &genes,
+        phenotype: &Phenotype::of(&genes),
         nearby_entities: &nearby_entities,
-        world: &world,
+        neighbors: &EntitySnapshot::from_world(&world),
         config: &config,
     });
 
     // Energy should remain unchanged if no interactions
     assert_eq!(new_energy, 50.0);
-    assert!(eaten_entity.is_none());
+    assert!(eaten_entities.is_empty());
+}
+
+fn spawn_prey(world: &mut World, genes: &Genes, x: f32) -> hecs::Entity {
+    world.spawn((
+        Position { x, y: 0.0 },
+        Energy {
+            current: 10.0,
+            max: 20.0,
+        },
+        Size { radius: 2.0 },
+        genes.clone(),
+    ))
+}
+
+#[test]
+fn test_interaction_budget_allows_multiple_prey() {
+    let system = InteractionSystem;
+    let mut rng = thread_rng();
+    let mut predator = Genes::new_random(&mut rng);
+    predator.movement.speed = 2.0;
+    let mut prey = Genes::new_random(&mut rng);
+    prey.movement.speed = 1.0;
+
+    let mut world = World::new();
+    let nearby: Vec<_> = (0..3)
+        .map(|i| spawn_prey(&mut world, &prey, i as f32))
+        .collect();
+
+    let mut config = SimulationConfig::default();
+    config.energy.max_energy_factor = 3.0; // High energy cap so the budget is the limit
+    for budget in 1..=3 {
+        config.physics.max_interactions_per_step = budget;
+        let mut new_energy = 10.0;
+        let mut eaten_entities = Vec::new();
+
+        system.handle_interactions(InteractionParams {
+            new_energy: &mut new_energy,
+            eaten_entities: &mut eaten_entities,
+            new_pos: &Position { x: 0.0, y: 0.0 },
+            size: &Size { radius: 10.0 },
+            genes: &predator,
+            phenotype: &Phenotype::of(&predator),
+            nearby_entities: &nearby,
+            neighbors: &EntitySnapshot::from_world(&world),
+            config: &config,
+        });
+
+        assert_eq!(eaten_entities.len(), budget);
+    }
 }
 
 #[test]
@@ -59,7 +109,7 @@ fn test_interaction_system_drift() {
             let mut total_x = 0.0;
             let mut total_y = 0.0;
 
-            for (_px, _py, cx, cy, _, _, _, _) in &entities {
+            for (_px, _py, cx, cy, _, _, _, _, _) in &entities {
                 total_x += cx;
                 total_y += cy;
             }
@@ -125,7 +175,7 @@ fn test_interaction_processing_order() {
             let mut total_x = 0.0;
             let mut total_y = 0.0;
 
-            for (_px, _py, cx, cy, _, _, _, _) in &entities {
+            for (_px, _py, cx, cy, _, _, _, _, _) in &entities {
                 total_x += cx;
                 total_y += cy;
             }
@@ -142,7 +192,7 @@ fn test_interaction_processing_order() {
             );
 
             // Store positions for analysis
-            for (_px, _py, cx, cy, _, _, _, _) in &entities {
+            for (_px, _py, cx, cy, _, _, _, _, _) in &entities {
                 eaten_positions.push((*cx, *cy));
             }
         }
@@ -216,7 +266,7 @@ fn test_interaction_order_bias() {
 
     // Get initial positions
     let initial_entities = simulation.get_entities();
-    for (_px, _py, cx, cy, _, _, _, _) in &initial_entities {
+    for (_px, _py, cx, cy, _, _, _, _, _) in &initial_entities {
         initial_positions.push((*cx, *cy));
     }
 
@@ -227,7 +277,7 @@ fn test_interaction_order_bias() {
         if step == 19 {
             // After 20 steps
             let final_entities = simulation.get_entities();
-            for (_px, _py, cx, cy, _, _, _, _) in &final_entities {
+            for (_px, _py, cx, cy, _, _, _, _, _) in &final_entities {
                 survivor_positions.push((*cx, *cy));
             }
         }
@@ -310,3 +360,213 @@ fn test_interaction_order_bias() {
         }
     }
 }
+
+#[test]
+fn test_feeding_never_exceeds_energy_ceiling() {
+    let system = InteractionSystem;
+    let mut rng = thread_rng();
+    let mut predator = Genes::new_random(&mut rng);
+    predator.movement.speed = 2.0;
+    predator.energy.efficiency = 4.0; // Efficiency no longer raises the ceiling
+    predator.energy.gain_rate = 5.0;
+    let mut prey = Genes::new_random(&mut rng);
+    prey.movement.speed = 1.0;
+
+    let mut world = World::new();
+    let nearby: Vec<_> = (0..5)
+        .map(|i| spawn_prey(&mut world, &prey, i as f32))
+        .collect();
+
+    let mut config = SimulationConfig::default();
+    config.energy.max_energy_factor = 0.2;
+    config.physics.max_interactions_per_step = 5;
+    let mut new_energy = 15.0;
+
+    system.handle_interactions(InteractionParams {
+        new_energy: &mut new_energy,
+        eaten_entities: &mut Vec::new(),
+        new_pos: &Position { x: 0.0, y: 0.0 },
+        size: &Size { radius: 10.0 },
+        genes: &predator,
+        phenotype: &Phenotype::of(&predator),
+        nearby_entities: &nearby,
+        neighbors: &EntitySnapshot::from_world(&world),
+        config: &config,
+    });
+
+    assert!(new_energy > 15.0);
+    assert!(new_energy <= config.energy.energy_ceiling());
+}
+
+#[test]
+fn test_energy_gain_uses_predator_size() {
+    let system = InteractionSystem;
+    let mut rng = thread_rng();
+    let mut predator = Genes::new_random(&mut rng);
+    predator.movement.speed = 2.0;
+    let mut prey = Genes::new_random(&mut rng);
+    prey.movement.speed = 1.0;
+
+    let mut world = World::new();
+    let meal = spawn_prey(&mut world, &prey, 0.0);
+    let mut config = SimulationConfig::default();
+    config.energy.max_energy_factor = 10.0; // Keep the ceiling out of the way
+
+    let size = Size { radius: 10.0 };
+    let mut new_energy = 10.0;
+    system.handle_interactions(InteractionParams {
+        new_energy: &mut new_energy,
+        eaten_entities: &mut Vec::new(),
+        new_pos: &Position { x: 0.0, y: 0.0 },
+        size: &size,
+        genes: &predator,
+        phenotype: &Phenotype::of(&predator),
+        nearby_entities: &[meal],
+        neighbors: &EntitySnapshot::from_world(&world),
+        config: &config,
+    });
+
+    let prey_size = Size { radius: 2.0 };
+    let (predator_phenotype, prey_phenotype) = (Phenotype::of(&predator), Phenotype::of(&prey));
+    let gain = |size| {
+        predator.get_energy_gain(
+            10.0,
+            &prey_size,
+            size,
+            &predator_phenotype,
+            &prey_phenotype,
+            &config.energy,
+        )
+    };
+    let expected = gain(&size);
+    let placeholder = gain(&Size { radius: 1.0 });
+    let overhead = config.energy.predation_energy_overhead;
+    assert!((new_energy - (10.0 + expected - overhead)).abs() < 1e-4);
+    assert!((expected - placeholder).abs() > 1e-3);
+}
+
+#[test]
+fn test_predation_overhead_reduces_net_gain() {
+    let system = InteractionSystem;
+    let mut rng = thread_rng();
+    let mut predator = Genes::new_random(&mut rng);
+    predator.movement.speed = 2.0;
+    let mut prey = Genes::new_random(&mut rng);
+    prey.movement.speed = 1.0;
+
+    let mut world = World::new();
+    let meal = spawn_prey(&mut world, &prey, 0.0);
+    let neighbors = EntitySnapshot::from_world(&world);
+    let mut config = SimulationConfig::default();
+    config.energy.max_energy_factor = 10.0; // Keep the ceiling out of the way
+
+    let mut eat = |overhead: f32| {
+        config.energy.predation_energy_overhead = overhead;
+        let mut eaten_entities = Vec::new();
+        let mut new_energy = 10.0;
+        system.handle_interactions(InteractionParams {
+            new_energy: &mut new_energy,
+            eaten_entities: &mut eaten_entities,
+            new_pos: &Position { x: 0.0, y: 0.0 },
+            size: &Size { radius: 10.0 },
+            genes: &predator,
+            phenotype: &Phenotype::of(&predator),
+            nearby_entities: &[meal],
+            neighbors: &neighbors,
+            config: &config,
+        });
+        assert_eq!(eaten_entities.len(), 1);
+        eaten_entities[0].1
+    };
+
+    let cheap = eat(0.5);
+    let costly = eat(3.0);
+    assert!(
+        (cheap - costly - 2.5).abs() < 1e-4,
+        "{} vs {}",
+        cheap,
+        costly
+    );
+}
+
+#[test]
+fn test_starving_prey_can_be_eaten_but_gives_no_energy() {
+    let system = InteractionSystem;
+    let mut rng = thread_rng();
+    let mut predator = Genes::new_random(&mut rng);
+    predator.movement.speed = 2.0;
+    let mut prey = Genes::new_random(&mut rng);
+    prey.movement.speed = 1.0;
+
+    let mut world = World::new();
+    let starving = spawn_prey(&mut world, &prey, 1.0);
+    world.get::<&mut Energy>(starving).unwrap().current = -3.0;
+    let config = SimulationConfig::default();
+    let mut new_energy = 10.0;
+    let mut eaten_entities = Vec::new();
+
+    system.handle_interactions(InteractionParams {
+        new_energy: &mut new_energy,
+        eaten_entities: &mut eaten_entities,
+        new_pos: &Position { x: 0.0, y: 0.0 },
+        size: &Size { radius: 10.0 },
+        genes: &predator,
+        phenotype: &Phenotype::of(&predator),
+        nearby_entities: &[starving],
+        neighbors: &EntitySnapshot::from_world(&world),
+        config: &config,
+    });
+
+    let overhead = config.energy.predation_energy_overhead;
+    assert_eq!(eaten_entities, [(starving, -overhead)]);
+    assert_eq!(new_energy, 10.0 - overhead);
+}
+
+#[test]
+fn test_disallowed_cannibalism_spares_kin_but_not_strangers() {
+    let system = InteractionSystem;
+    let mut predator = Genes::new_random(&mut thread_rng());
+    predator.movement.speed = 1.0;
+    predator.behavior.movement_style.style = crate::components::MovementType::Predatory;
+    let mut kin = predator.clone();
+    kin.appearance.hue += if kin.appearance.hue < 0.5 {
+        0.01
+    } else {
+        -0.01
+    };
+    let mut stranger = predator.clone();
+    stranger.appearance.hue = (stranger.appearance.hue + 0.5) % 1.0;
+    stranger.behavior.movement_style.style = crate::components::MovementType::Grazing;
+
+    let mut world = World::new();
+    let nearby = vec![
+        spawn_prey(&mut world, &kin, 1.0),
+        spawn_prey(&mut world, &stranger, 2.0),
+    ];
+    let mut config = SimulationConfig::default();
+    config.energy.max_energy_factor = 3.0;
+    config.physics.max_interactions_per_step = 2;
+
+    let mut eaten_with = |allow_cannibalism: bool| {
+        config.physics.allow_cannibalism = allow_cannibalism;
+        let mut eaten_entities = Vec::new();
+        system.handle_interactions(InteractionParams {
+            new_energy: &mut 10.0,
+            eaten_entities: &mut eaten_entities,
+            new_pos: &Position { x: 0.0, y: 0.0 },
+            size: &Size { radius: 10.0 },
+            genes: &predator,
+            phenotype: &Phenotype::of(&predator),
+            nearby_entities: &nearby,
+            neighbors: &EntitySnapshot::from_world(&world),
+            config: &config,
+        });
+        eaten_entities
+            .iter()
+            .map(|(entity, _)| *entity)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(eaten_with(true), nearby);
+    assert_eq!(eaten_with(false), [nearby[1]]);
+}
@@ -2,8 +2,10 @@ pub mod energy;
 pub mod interaction;
 pub mod movement;
 pub mod reproduction;
+pub mod social;
 
 pub use energy::*;
 pub use interaction::*;
 pub use movement::*;
 pub use reproduction::*;
+pub use social::*;
@@ -1,76 +1,182 @@
-use crate::components::{Energy, MovementType, Position, Size, Velocity};
+use crate::components::{MovementType, Position, Size, Velocity};
 use crate::config::SimulationConfig;
-use crate::genes::Genes;
-use hecs::{Entity, World};
+use crate::genes::{Genes, Phenotype};
+use crate::sampling::{random_direction, random_in_disc};
+use crate::snapshot::{EntitySnapshot, Neighbor};
+use hecs::Entity;
 use rand::prelude::*;
 
+mod state;
+pub use state::{BehaviorContext, BehaviorState};
+
 /// Movement system - handles entity movement and boundary constraints
 pub struct MovementSystem;
 
-pub struct MovementUpdateParams<'a> {
+pub struct MovementUpdateParams<'a, R: Rng> {
     pub genes: &'a Genes,
+    pub phenotype: &'a Phenotype, // Cached from `genes`
+    pub size: &'a Size,
     pub new_pos: &'a mut Position,
     pub new_velocity: &'a mut Velocity,
     pub new_energy: &'a mut f32,
+    pub max_energy: f32,
     pub pos: &'a Position,
     pub nearby_entities: &'a [Entity],
-    pub world: &'a World,
+    pub neighbors: &'a EntitySnapshot, // Components of nearby entities
     pub config: &'a SimulationConfig,
     pub world_size: f32,
+    pub rng: &'a mut R, // Source of randomness for wandering and grazing
 }
 
 impl MovementSystem {
-    pub fn update_movement(&self, params: MovementUpdateParams) {
+    pub fn update_movement<R: Rng>(&self, params: MovementUpdateParams<R>) {
         let MovementUpdateParams {
             genes,
+            phenotype,
+            size,
             new_pos,
             new_velocity,
             new_energy,
+            max_energy,
             pos,
             nearby_entities,
-            world,
+            neighbors,
             config,
             world_size,
+            rng,
         } = params;
-        // Find target for movement based on genes and movement style
-        let target = self.find_movement_target(pos, genes, nearby_entities, world);
+        // The caller seeds the new velocity with last step's
+        let previous_velocity = new_velocity.clone();
+        let state_machine = config.behavior.state_machine;
+        let energy_fraction = if max_energy > 0.0 {
+            *new_energy / max_energy
+        } else {
+            0.0
+        };
+        let flee = if state_machine || genes.behavior.fearfulness > 0.0 {
+            self.flee_direction(pos, genes, size, nearby_entities, neighbors)
+        } else {
+            None
+        };
+        let state = if state_machine {
+            let context = BehaviorContext {
+                energy_fraction,
+                predator_near: flee.is_some(),
+            };
+            BehaviorState::select(&context, &config.behavior)
+        } else {
+            BehaviorState::Forage
+        };
 
-        if let Some((target_x, target_y)) = target {
-            self.move_towards_target(pos, target_x, target_y, genes, new_velocity);
+        if state == BehaviorState::Rest {
+            new_velocity.x *= config.behavior.rest_speed_factor;
+            new_velocity.y *= config.behavior.rest_speed_factor;
         } else {
-            self.move_randomly(genes, new_velocity, config);
+            // Find target for movement based on genes, movement style and state
+            let target = match state {
+                BehaviorState::Forage => self.find_movement_target(
+                    pos,
+                    genes,
+                    phenotype,
+                    nearby_entities,
+                    neighbors,
+                    config,
+                ),
+                BehaviorState::SeekMate => {
+                    self.find_mate_target(pos, genes, phenotype, nearby_entities, neighbors, config)
+                }
+                BehaviorState::Flee | BehaviorState::Rest => None,
+            };
+
+            if let Some((target_x, target_y)) = target {
+                self.move_towards_target(pos, target_x, target_y, genes, new_velocity);
+            } else {
+                self.move_randomly(genes, new_velocity, config, rng);
+            }
+
+            // Apply movement style specific behaviors
+            self.apply_movement_style(
+                pos,
+                genes,
+                phenotype,
+                nearby_entities,
+                neighbors,
+                new_velocity,
+                config,
+                rng,
+            );
         }
 
-        // Apply movement style specific behaviors
-        self.apply_movement_style(pos, genes, nearby_entities, world, new_velocity, config);
+        // Fear overrides foraging and movement style when a predator is close. A fleeing
+        // entity gives it its full attention.
+        if let Some(flee) = flee {
+            let fearfulness = if state == BehaviorState::Flee {
+                1.0
+            } else {
+                genes.behavior.fearfulness
+            };
+            self.apply_fear_response(flee, genes, fearfulness, new_velocity);
+        }
 
-        self.update_position(new_pos, new_velocity);
+        // Torpid and starving entities barely move, so they spend little on movement
+        if energy_fraction < config.behavior.torpor_threshold
+            || !config.energy.is_alive(*new_energy)
+        {
+            new_velocity.x *= config.behavior.torpor_speed_factor;
+            new_velocity.y *= config.behavior.torpor_speed_factor;
+        }
+
+        self.apply_diffusion(new_velocity, config, rng);
+        self.apply_drag(new_velocity, config);
+        self.integrate_position(new_pos, new_velocity, world_size, config);
         self.apply_center_pressure(new_pos, new_velocity, config, world_size);
         self.validate_position(new_pos);
-        self.apply_movement_cost(new_velocity, new_energy, genes, config);
+        self.apply_movement_cost(&previous_velocity, new_velocity, new_energy, genes, config);
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn apply_movement_style(
         &self,
         pos: &Position,
         genes: &Genes,
+        phenotype: &Phenotype,
         nearby_entities: &[Entity],
-        world: &World,
+        neighbors: &EntitySnapshot,
         new_velocity: &mut Velocity,
         config: &SimulationConfig,
+        rng: &mut impl Rng,
     ) {
         match genes.behavior.movement_style.style {
             MovementType::Flocking => {
-                self.apply_flocking_behavior(pos, genes, nearby_entities, world, new_velocity);
+                self.apply_flocking_behavior(
+                    pos,
+                    genes,
+                    phenotype,
+                    nearby_entities,
+                    neighbors,
+                    new_velocity,
+                    config,
+                );
             }
             MovementType::Solitary => {
-                self.apply_solitary_behavior(pos, genes, nearby_entities, world, new_velocity);
+                self.apply_solitary_behavior(pos, genes, nearby_entities, neighbors, new_velocity);
             }
             MovementType::Predatory => {
-                self.apply_predatory_behavior(pos, genes, nearby_entities, world, new_velocity);
+                self.apply_predatory_behavior(
+                    pos,
+                    genes,
+                    phenotype,
+                    nearby_entities,
+                    neighbors,
+                    new_velocity,
+                    config,
+                );
             }
             MovementType::Grazing => {
-                self.apply_grazing_behavior(genes, new_velocity, config);
+                self.apply_grazing_behavior(genes, new_velocity, config, rng);
+            }
+            MovementType::Migratory => {
+                self.apply_migratory_behavior(genes, new_velocity, config);
             }
             MovementType::Random => {
                 // Random behavior is already handled in move_randomly
@@ -78,13 +184,16 @@ impl MovementSystem {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn apply_flocking_behavior(
         &self,
         pos: &Position,
         genes: &Genes,
+        phenotype: &Phenotype,
         nearby_entities: &[Entity],
-        world: &World,
+        neighbors: &EntitySnapshot,
         new_velocity: &mut Velocity,
+        config: &SimulationConfig,
     ) {
         let mut flock_center_x = 0.0;
         let mut flock_center_y = 0.0;
@@ -95,44 +204,44 @@ impl MovementSystem {
         let mut separation_y = 0.0;
 
         for &entity in nearby_entities {
-            if let Ok(nearby_pos) = world.get::<&Position>(entity) {
-                if let Ok(nearby_genes) = world.get::<&Genes>(entity) {
-                    if let Ok(nearby_velocity) = world.get::<&Velocity>(entity) {
-                        let distance = ((nearby_pos.x - pos.x).powi(2)
-                            + (nearby_pos.y - pos.y).powi(2))
-                        .sqrt();
-
-                        // Only flock with similar entities (similar genes)
-                        let gene_similarity = genes.calculate_gene_similarity(&nearby_genes);
-                        if distance < genes.sense_radius() && gene_similarity < 0.7 {
-                            // Cohesion: move toward flock center
-                            flock_center_x += nearby_pos.x;
-                            flock_center_y += nearby_pos.y;
-
-                            // Alignment: align with flock direction
-                            flock_velocity_x += nearby_velocity.x;
-                            flock_velocity_y += nearby_velocity.y;
-
-                            // Separation: avoid crowding
-                            if distance > 0.0
-                                && distance < genes.behavior.movement_style.separation_distance
-                            {
-                                let separation_force =
-                                    (genes.behavior.movement_style.separation_distance - distance)
-                                        / distance;
-                                separation_x -= (nearby_pos.x - pos.x) * separation_force;
-                                separation_y -= (nearby_pos.y - pos.y) * separation_force;
-                            }
+            if let Some(Neighbor {
+                pos: nearby_pos,
+                phenotype: nearby_phenotype,
+                velocity: nearby_velocity,
+                ..
+            }) = neighbors.get(entity)
+            {
+                let distance =
+                    ((nearby_pos.x - pos.x).powi(2) + (nearby_pos.y - pos.y).powi(2)).sqrt();
 
-                            flock_count += 1;
-                        }
+                if self.is_flockmate(genes, phenotype, nearby_phenotype, distance) {
+                    // Cohesion: move toward flock center
+                    flock_center_x += nearby_pos.x;
+                    flock_center_y += nearby_pos.y;
+
+                    // Alignment: align with flock direction
+                    flock_velocity_x += nearby_velocity.x;
+                    flock_velocity_y += nearby_velocity.y;
+
+                    // Separation: avoid crowding
+                    if distance > 0.0
+                        && distance < genes.behavior.movement_style.separation_distance
+                    {
+                        let separation_force = (genes.behavior.movement_style.separation_distance
+                            - distance)
+                            / distance;
+                        separation_x -= (nearby_pos.x - pos.x) * separation_force;
+                        separation_y -= (nearby_pos.y - pos.y) * separation_force;
                     }
+
+                    flock_count += 1;
                 }
             }
         }
 
         if flock_count > 0 {
             // Apply flocking forces
+            let behavior = &config.behavior;
             let flock_strength = genes.behavior.movement_style.flocking_strength;
 
             // Cohesion
@@ -145,8 +254,8 @@ impl MovementSystem {
                 let cohesion_y = (flock_center_y - pos.y)
                     * genes.behavior.movement_style.cohesion_strength
                     * flock_strength;
-                new_velocity.x += cohesion_x * 0.1;
-                new_velocity.y += cohesion_y * 0.1;
+                new_velocity.x += cohesion_x * behavior.flock_cohesion_coefficient;
+                new_velocity.y += cohesion_y * behavior.flock_cohesion_coefficient;
             }
 
             // Alignment
@@ -159,30 +268,46 @@ impl MovementSystem {
                 let alignment_y = flock_velocity_y
                     * genes.behavior.movement_style.alignment_strength
                     * flock_strength;
-                new_velocity.x += alignment_x * 0.1;
-                new_velocity.y += alignment_y * 0.1;
+                new_velocity.x += alignment_x * behavior.flock_alignment_coefficient;
+                new_velocity.y += alignment_y * behavior.flock_alignment_coefficient;
             }
 
             // Separation
-            let separation_strength = flock_strength * 0.2;
+            let separation_strength = flock_strength * behavior.flock_separation_coefficient;
             new_velocity.x += separation_x * separation_strength;
             new_velocity.y += separation_y * separation_strength;
         }
     }
 
+    /// Only flock with similar entities, as close in gene space as the kin threshold allows
+    fn is_flockmate(
+        &self,
+        genes: &Genes,
+        phenotype: &Phenotype,
+        nearby_phenotype: &Phenotype,
+        distance: f32,
+    ) -> bool {
+        distance < genes.sense_radius()
+            && phenotype.similarity(nearby_phenotype)
+                < genes.behavior.movement_style.flock_kin_threshold
+    }
+
     fn apply_solitary_behavior(
         &self,
         pos: &Position,
         genes: &Genes,
         nearby_entities: &[Entity],
-        world: &World,
+        neighbors: &EntitySnapshot,
         new_velocity: &mut Velocity,
     ) {
         let mut avoidance_x = 0.0;
         let mut avoidance_y = 0.0;
 
         for &entity in nearby_entities {
-            if let Ok(nearby_pos) = world.get::<&Position>(entity) {
+            if let Some(Neighbor {
+                pos: nearby_pos, ..
+            }) = neighbors.get(entity)
+            {
                 let distance =
                     ((nearby_pos.x - pos.x).powi(2) + (nearby_pos.y - pos.y).powi(2)).sqrt();
 
@@ -201,44 +326,46 @@ impl MovementSystem {
         new_velocity.y += avoidance_y * avoidance_strength;
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn apply_predatory_behavior(
         &self,
         pos: &Position,
         genes: &Genes,
+        phenotype: &Phenotype,
         nearby_entities: &[Entity],
-        world: &World,
+        neighbors: &EntitySnapshot,
         new_velocity: &mut Velocity,
+        config: &SimulationConfig,
     ) {
         let mut best_prey_x = 0.0;
         let mut best_prey_y = 0.0;
         let mut best_preference = 0.0;
 
         for &entity in nearby_entities {
-            if let Ok(nearby_pos) = world.get::<&Position>(entity) {
-                if let Ok(nearby_genes) = world.get::<&Genes>(entity) {
-                    if let Ok(nearby_energy) = world.get::<&Energy>(entity) {
-                        if let Ok(nearby_size) = world.get::<&Size>(entity) {
-                            if nearby_energy.current > 0.0 {
-                                let distance = ((nearby_pos.x - pos.x).powi(2)
-                                    + (nearby_pos.y - pos.y).powi(2))
-                                .sqrt();
-                                if distance < genes.sense_radius() {
-                                    // Calculate predation preference
-                                    let preference = genes.get_predation_preference(&nearby_genes);
-
-                                    // Also consider if we can actually eat this entity
-                                    if genes.can_eat(
-                                        &nearby_genes,
-                                        &nearby_size,
-                                        &Size { radius: 1.0 },
-                                    ) && preference > best_preference
-                                    {
-                                        best_prey_x = nearby_pos.x;
-                                        best_prey_y = nearby_pos.y;
-                                        best_preference = preference;
-                                    }
-                                }
-                            }
+            if let Some(Neighbor {
+                pos: nearby_pos,
+                genes: nearby_genes,
+                phenotype: nearby_phenotype,
+                energy: nearby_energy,
+                size: nearby_size,
+                ..
+            }) = neighbors.get(entity)
+            {
+                if config.energy.is_alive(nearby_energy.current) {
+                    let distance =
+                        ((nearby_pos.x - pos.x).powi(2) + (nearby_pos.y - pos.y).powi(2)).sqrt();
+                    if distance < genes.sense_radius() {
+                        // Calculate predation preference
+                        let preference =
+                            genes.get_predation_preference(phenotype, nearby_phenotype);
+
+                        // Also consider if we can actually eat this entity
+                        if genes.can_eat(nearby_genes, nearby_size, &Size { radius: 1.0 })
+                            && preference > best_preference
+                        {
+                            best_prey_x = nearby_pos.x;
+                            best_prey_y = nearby_pos.y;
+                            best_preference = preference;
                         }
                     }
                 }
@@ -251,29 +378,127 @@ impl MovementSystem {
             let dy = best_prey_y - pos.y;
             let distance = (dx * dx + dy * dy).sqrt();
             if distance > 0.0 {
-                let predatory_speed = genes.speed() * 1.2; // Predators move faster
+                let predatory_speed = genes.speed() * config.behavior.predator_speed_multiplier;
                 new_velocity.x = (dx / distance) * predatory_speed;
                 new_velocity.y = (dy / distance) * predatory_speed;
             }
         }
     }
 
+    /// Unit vector away from nearby entities that could eat this one, closer ones pushing
+    /// harder, or `None` when none are in sense range
+    fn flee_direction(
+        &self,
+        pos: &Position,
+        genes: &Genes,
+        size: &Size,
+        nearby_entities: &[Entity],
+        neighbors: &EntitySnapshot,
+    ) -> Option<(f32, f32)> {
+        let mut flee_x = 0.0;
+        let mut flee_y = 0.0;
+        for &entity in nearby_entities {
+            if let Some(Neighbor {
+                pos: nearby_pos,
+                genes: nearby_genes,
+                size: nearby_size,
+                ..
+            }) = neighbors.get(entity)
+            {
+                let dx = pos.x - nearby_pos.x;
+                let dy = pos.y - nearby_pos.y;
+                let distance_sq = dx * dx + dy * dy;
+                if distance_sq > 0.0
+                    && distance_sq < genes.sense_radius().powi(2)
+                    && nearby_genes.can_eat(genes, size, nearby_size)
+                {
+                    // Closer predators push harder
+                    flee_x += dx / distance_sq;
+                    flee_y += dy / distance_sq;
+                }
+            }
+        }
+
+        let flee_length = (flee_x * flee_x + flee_y * flee_y).sqrt();
+        (flee_length > 0.0).then(|| (flee_x / flee_length, flee_y / flee_length))
+    }
+
+    /// Steer along `flee`, overriding the current velocity by `fearfulness`
+    fn apply_fear_response(
+        &self,
+        flee: (f32, f32),
+        genes: &Genes,
+        fearfulness: f32,
+        new_velocity: &mut Velocity,
+    ) {
+        if fearfulness <= 0.0 {
+            return;
+        }
+        let flee_speed = genes.speed() * 1.2; // Fleeing prey sprint at the default chase speed
+        new_velocity.x += (flee.0 * flee_speed - new_velocity.x) * fearfulness;
+        new_velocity.y += (flee.1 * flee_speed - new_velocity.y) * fearfulness;
+    }
+
+    /// Nearest living kin in sense range, by the flocking kin threshold
+    fn find_mate_target(
+        &self,
+        pos: &Position,
+        genes: &Genes,
+        phenotype: &Phenotype,
+        nearby_entities: &[Entity],
+        neighbors: &EntitySnapshot,
+        config: &SimulationConfig,
+    ) -> Option<(f32, f32)> {
+        nearby_entities
+            .iter()
+            .filter_map(|&entity| neighbors.get(entity))
+            .filter(|nearby| config.energy.is_alive(nearby.energy.current))
+            .map(|nearby| {
+                let distance_sq = (nearby.pos.x - pos.x).powi(2) + (nearby.pos.y - pos.y).powi(2);
+                (nearby, distance_sq)
+            })
+            .filter(|(nearby, distance_sq)| {
+                *distance_sq > 0.0
+                    && *distance_sq < genes.sense_radius().powi(2)
+                    && phenotype.similarity(nearby.phenotype)
+                        < genes.behavior.movement_style.flock_kin_threshold
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(nearby, _)| (nearby.pos.x, nearby.pos.y))
+    }
+
     fn apply_grazing_behavior(
         &self,
         genes: &Genes,
         new_velocity: &mut Velocity,
         config: &SimulationConfig,
+        rng: &mut impl Rng,
     ) {
         // Grazers move slowly and steadily
         let grazing_speed = genes.speed() * 0.6;
 
         // Add some gentle random movement
-        let mut rng = thread_rng();
-        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let (dx, dy) = random_direction(rng);
         let speed_variation = rng.gen_range(0.8..1.2);
 
-        new_velocity.x = angle.cos() * grazing_speed * speed_variation;
-        new_velocity.y = angle.sin() * grazing_speed * speed_variation;
+        new_velocity.x = dx * grazing_speed * speed_variation;
+        new_velocity.y = dy * grazing_speed * speed_variation;
+
+        self.cap_velocity(new_velocity, config);
+    }
+
+    fn apply_migratory_behavior(
+        &self,
+        genes: &Genes,
+        new_velocity: &mut Velocity,
+        config: &SimulationConfig,
+    ) {
+        // Migrants keep a little of their wandering but lean into their inherited heading
+        let heading = genes.behavior.movement_style.migration_heading;
+        let pull = genes.speed() * 0.7;
+
+        new_velocity.x = new_velocity.x * 0.3 + heading.cos() * pull;
+        new_velocity.y = new_velocity.y * 0.3 + heading.sin() * pull;
 
         self.cap_velocity(new_velocity, config);
     }
@@ -295,44 +520,74 @@ impl MovementSystem {
         }
     }
 
-    fn move_randomly(&self, genes: &Genes, new_velocity: &mut Velocity, config: &SimulationConfig) {
-        let mut rng = thread_rng();
+    fn move_randomly(
+        &self,
+        genes: &Genes,
+        new_velocity: &mut Velocity,
+        config: &SimulationConfig,
+        rng: &mut impl Rng,
+    ) {
         let speed_variation = rng.gen_range(0.8..1.2);
         let speed = genes.speed() * speed_variation;
 
-        // Generate random direction using uniform distribution in a circle
-        let (dx, dy) = self.generate_random_direction(&mut rng);
+        let (dx, dy) = random_direction(rng);
         new_velocity.x = dx * speed;
         new_velocity.y = dy * speed;
 
         self.cap_velocity(new_velocity, config);
     }
 
-    fn generate_random_direction(&self, rng: &mut ThreadRng) -> (f32, f32) {
-        loop {
-            let dx = rng.gen_range(-1.0f32..1.0);
-            let dy = rng.gen_range(-1.0f32..1.0);
-            let length_sq = dx * dx + dy * dy;
-            if length_sq <= 1.0 && length_sq > 0.0 {
-                // Normalize to unit vector
-                let length = length_sq.sqrt();
-                return (dx / length, dy / length);
-            }
+    /// Scale the velocity down to `max_velocity` in magnitude, so no heading is faster
+    fn cap_velocity(&self, velocity: &mut Velocity, config: &SimulationConfig) {
+        let speed = (velocity.x * velocity.x + velocity.y * velocity.y).sqrt();
+        if speed > config.physics.max_velocity {
+            let scale = config.physics.max_velocity / speed;
+            velocity.x *= scale;
+            velocity.y *= scale;
         }
     }
 
-    fn cap_velocity(&self, velocity: &mut Velocity, config: &SimulationConfig) {
-        if velocity.x.abs() > config.physics.max_velocity {
-            velocity.x = velocity.x.signum() * config.physics.max_velocity;
-        }
-        if velocity.y.abs() > config.physics.max_velocity {
-            velocity.y = velocity.y.signum() * config.physics.max_velocity;
+    /// Add a random kick drawn uniformly from the disc of radius `diffusion_strength`. It
+    /// has zero mean, so it spreads a collapsing cluster without pushing it anywhere. No
+    /// draws are made when it is off, so seeded runs are unchanged.
+    fn apply_diffusion(
+        &self,
+        velocity: &mut Velocity,
+        config: &SimulationConfig,
+        rng: &mut impl Rng,
+    ) {
+        let strength = config.physics.diffusion_strength;
+        if strength > 0.0 {
+            let (dx, dy) = random_in_disc(rng, strength);
+            velocity.x += dx;
+            velocity.y += dy;
         }
     }
 
-    fn update_position(&self, new_pos: &mut Position, new_velocity: &Velocity) {
-        new_pos.x += new_velocity.x;
-        new_pos.y += new_velocity.y;
+    /// Drag removes a fixed fraction of velocity each step, so steering forces that keep
+    /// adding up settle instead of pinning entities at the speed cap
+    fn apply_drag(&self, velocity: &mut Velocity, config: &SimulationConfig) {
+        let retained = 1.0 - config.physics.velocity_damping;
+        velocity.x *= retained;
+        velocity.y *= retained;
+        self.cap_velocity(velocity, config);
+    }
+
+    /// Advance the position in `substeps` equal increments, resolving boundaries after each
+    /// one so fast entities can't tunnel through walls within a single frame
+    pub fn integrate_position(
+        &self,
+        new_pos: &mut Position,
+        new_velocity: &mut Velocity,
+        world_size: f32,
+        config: &SimulationConfig,
+    ) {
+        let substeps = config.physics.substeps.max(1);
+        for _ in 0..substeps {
+            new_pos.x += new_velocity.x / substeps as f32;
+            new_pos.y += new_velocity.y / substeps as f32;
+            self.handle_boundaries(new_pos, new_velocity, world_size, config);
+        }
     }
 
     fn validate_position(&self, new_pos: &mut Position) {
@@ -361,23 +616,33 @@ impl MovementSystem {
         let distance_from_edge_y = half_world - pos.y.abs();
         let distance_from_edge = distance_from_edge_x.min(distance_from_edge_y);
 
+        let physics = &config.physics;
+
         // Only apply pressure if entity is away from center
-        if distance_from_center > 10.0 {
+        if distance_from_center > physics.center_pressure_inner_radius {
             // Calculate direction towards center
             let center_dx = -pos.x / distance_from_center;
             let center_dy = -pos.y / distance_from_center;
 
             // Base pressure strength
-            let base_pressure = config.physics.center_pressure_strength;
+            let base_pressure = physics.center_pressure_strength;
 
             // Increase pressure strength when closer to edges
             // Pressure is strongest at edges (distance_from_edge = 0) and weakest in center
-            let edge_multiplier = if distance_from_edge < 50.0 {
-                // Exponential increase as we get closer to edges
-                let edge_factor = (50.0 - distance_from_edge) / 50.0;
-                1.0 + edge_factor * edge_factor * 8.0 // Up to 9x stronger at edges
+            let ramp = physics.edge_ramp_distance;
+            let edge_boost = if ramp > 0.0 && distance_from_edge < ramp {
+                // Quadratic increase as we get closer to edges
+                let edge_factor = (ramp - distance_from_edge) / ramp;
+                edge_factor * edge_factor * physics.edge_multiplier_max
             } else {
-                1.0
+                0.0
+            };
+
+            // Soft walls push back only near the edges, with no constant pull to the center
+            let edge_multiplier = if physics.soft_walls_only {
+                edge_boost
+            } else {
+                1.0 + edge_boost
             };
 
             let pressure_strength = base_pressure * edge_multiplier;
@@ -388,6 +653,7 @@ impl MovementSystem {
 
     fn apply_movement_cost(
         &self,
+        previous_velocity: &Velocity,
         new_velocity: &Velocity,
         new_energy: &mut f32,
         genes: &Genes,
@@ -395,48 +661,58 @@ impl MovementSystem {
     ) {
         let movement_distance =
             (new_velocity.x * new_velocity.x + new_velocity.y * new_velocity.y).sqrt();
-        *new_energy -=
-            movement_distance * config.energy.movement_energy_cost / genes.energy_efficiency();
+
+        // Manoeuvring costs extra: radians turned, scaled by the speed being redirected
+        let cross = previous_velocity.x * new_velocity.y - previous_velocity.y * new_velocity.x;
+        let dot = previous_velocity.x * new_velocity.x + previous_velocity.y * new_velocity.y;
+        let turn_angle = if cross == 0.0 && dot == 0.0 {
+            0.0 // Starting from or coming to a standstill isn't a turn
+        } else {
+            cross.atan2(dot).abs()
+        };
+        let turning_cost = turn_angle * movement_distance * config.energy.turning_energy_cost;
+
+        *new_energy -= (movement_distance * config.energy.movement_energy_cost + turning_cost)
+            / genes.energy_efficiency();
     }
 
     fn find_movement_target(
         &self,
         pos: &Position,
         genes: &Genes,
+        phenotype: &Phenotype,
         nearby_entities: &[Entity],
-        world: &World,
+        neighbors: &EntitySnapshot,
+        config: &SimulationConfig,
     ) -> Option<(f32, f32)> {
         let mut best_target_x = 0.0;
         let mut best_target_y = 0.0;
         let mut best_preference = 0.0;
 
         for &entity in nearby_entities {
-            if let Ok(nearby_pos) = world.get::<&Position>(entity) {
-                if let Ok(nearby_genes) = world.get::<&Genes>(entity) {
-                    if let Ok(nearby_energy) = world.get::<&Energy>(entity) {
-                        if let Ok(nearby_size) = world.get::<&Size>(entity) {
-                            if nearby_energy.current > 0.0 {
-                                let distance = ((nearby_pos.x - pos.x).powi(2)
-                                    + (nearby_pos.y - pos.y).powi(2))
-                                .sqrt();
-                                if distance < genes.sense_radius() {
-                                    // Check if this is a potential food source
-                                    if genes.can_eat(
-                                        &nearby_genes,
-                                        &nearby_size,
-                                        &Size { radius: 1.0 },
-                                    ) {
-                                        // Calculate preference based on gene similarity
-                                        let preference =
-                                            genes.get_predation_preference(&nearby_genes);
-
-                                        if preference > best_preference {
-                                            best_target_x = nearby_pos.x;
-                                            best_target_y = nearby_pos.y;
-                                            best_preference = preference;
-                                        }
-                                    }
-                                }
+            if let Some(Neighbor {
+                pos: nearby_pos,
+                genes: nearby_genes,
+                phenotype: nearby_phenotype,
+                energy: nearby_energy,
+                size: nearby_size,
+                ..
+            }) = neighbors.get(entity)
+            {
+                if config.energy.is_alive(nearby_energy.current) {
+                    let distance =
+                        ((nearby_pos.x - pos.x).powi(2) + (nearby_pos.y - pos.y).powi(2)).sqrt();
+                    if distance < genes.sense_radius() {
+                        // Check if this is a potential food source
+                        if genes.can_eat(nearby_genes, nearby_size, &Size { radius: 1.0 }) {
+                            // Calculate preference based on gene similarity
+                            let preference =
+                                genes.get_predation_preference(phenotype, nearby_phenotype);
+
+                            if preference > best_preference {
+                                best_target_x = nearby_pos.x;
+                                best_target_y = nearby_pos.y;
+                                best_preference = preference;
                             }
                         }
                     }
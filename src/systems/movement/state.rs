@@ -0,0 +1,33 @@
+use crate::config::BehaviorConfig;
+
+/// What an entity is doing this step, chosen from its situation when
+/// `behavior.state_machine` is on. Its genetic movement style still shapes how it moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BehaviorState {
+    Forage,   // Head for food, or wander
+    Flee,     // Run from a nearby predator
+    SeekMate, // Head for the nearest kin
+    Rest,     // Coast to save energy
+}
+
+/// The situation a `BehaviorState` is chosen from
+#[derive(Debug, Clone, Copy)]
+pub struct BehaviorContext {
+    pub energy_fraction: f32, // Current energy over max
+    pub predator_near: bool,  // Something that could eat the entity is within sense range
+}
+
+impl BehaviorState {
+    /// Danger comes first, then exhaustion, then the urge to reproduce
+    pub fn select(context: &BehaviorContext, config: &BehaviorConfig) -> Self {
+        if context.predator_near {
+            BehaviorState::Flee
+        } else if context.energy_fraction < config.rest_energy_fraction {
+            BehaviorState::Rest
+        } else if context.energy_fraction >= config.mate_energy_fraction {
+            BehaviorState::SeekMate
+        } else {
+            BehaviorState::Forage
+        }
+    }
+}
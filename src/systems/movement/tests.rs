@@ -19,14 +19,18 @@ fn test_movement_system_update_movement() {
 
     system.update_movement(MovementUpdateParams {
         genes: &genes,
+        phenotype: &Phenotype::of(&genes),
+        size: &Size { radius: 5.0 },
         new_pos: &mut new_pos,
         new_velocity: &mut new_velocity,
         new_energy: &mut new_energy,
+        max_energy: 100.0,
         pos: &pos,
         nearby_entities: &nearby_entities,
-        world: &world,
+        neighbors: &EntitySnapshot::from_world(&world),
         config: &config,
         world_size: 100.0,
+        rng: &mut thread_rng(),
     });
 
     // Position should have changed
@@ -59,6 +63,35 @@ fn test_movement_system_handle_boundaries() {
     assert!(velocity.x < 0.0 || velocity.y < 0.0);
 }
 
+#[test]
+fn test_substeps_keep_fast_entity_inside_walls() {
+    let system = MovementSystem;
+    let mut config = SimulationConfig::default();
+    let world_size = 100.0;
+    let wall = world_size / 2.0 - config.physics.boundary_margin;
+
+    for substeps in [1, 2, 4, 8] {
+        config.physics.substeps = substeps;
+        let mut pos = Position { x: 40.0, y: 0.0 };
+        let mut velocity = Velocity { x: 30.0, y: 0.0 };
+
+        // Check the position after every frame of a sustained high-speed run
+        for _ in 0..10 {
+            system.integrate_position(&mut pos, &mut velocity, world_size, &config);
+            assert!(pos.x.abs() <= wall, "substeps={} x={}", substeps, pos.x);
+            velocity.x = 30.0 * velocity.x.signum();
+        }
+    }
+
+    // With substeps the bounce happens mid-frame, so the entity rebounds away from the wall
+    config.physics.substeps = 4;
+    let mut pos = Position { x: 40.0, y: 0.0 };
+    let mut velocity = Velocity { x: 30.0, y: 0.0 };
+    system.integrate_position(&mut pos, &mut velocity, world_size, &config);
+    assert!(pos.x < wall);
+    assert!(velocity.x < 0.0);
+}
+
 #[test]
 fn test_movement_system_boundary_center() {
     let system = MovementSystem;
@@ -105,14 +138,18 @@ fn test_movement_system_with_target() {
 
     system.update_movement(MovementUpdateParams {
         genes: &genes,
+        phenotype: &Phenotype::of(&genes),
+        size: &Size { radius: 5.0 },
         new_pos: &mut new_pos,
         new_velocity: &mut new_velocity,
         new_energy: &mut new_energy,
+        max_energy: 100.0,
         pos: &pos,
         nearby_entities: &nearby_entities,
-        world: &world,
+        neighbors: &EntitySnapshot::from_world(&world),
         config: &config,
         world_size: 100.0,
+        rng: &mut thread_rng(),
     });
 
     // Should have moved (position changed) and used energy
@@ -148,16 +185,21 @@ fn test_movement_drift_analysis() {
     let mut energy = 100.0;
 
     // Run movement update with no nearby entities
+    let mover = Genes::new_random(&mut thread_rng());
     movement_system.update_movement(MovementUpdateParams {
-        genes: &Genes::new_random(&mut thread_rng()),
+        genes: &mover,
+        phenotype: &Phenotype::of(&mover),
+        size: &Size { radius: 5.0 },
         new_pos: &mut pos,
         new_velocity: &mut velocity,
         new_energy: &mut energy,
+        max_energy: 100.0,
         pos: &Position { x: 0.0, y: 0.0 },
         nearby_entities: &[],
-        world: &world,
+        neighbors: &EntitySnapshot::from_world(&world),
         config: &config,
         world_size: 100.0,
+        rng: &mut thread_rng(),
     });
 
     // Check if there's any systematic bias in velocity generation
@@ -251,16 +293,21 @@ fn test_velocity_distribution_analysis() {
         let mut velocity = Velocity { x: 0.0, y: 0.0 };
         let mut energy = 100.0;
 
+        let mover = Genes::new_random(&mut thread_rng());
         movement_system.update_movement(MovementUpdateParams {
-            genes: &Genes::new_random(&mut thread_rng()),
+            genes: &mover,
+            phenotype: &Phenotype::of(&mover),
+            size: &Size { radius: 5.0 },
             new_pos: &mut pos,
             new_velocity: &mut velocity,
             new_energy: &mut energy,
+            max_energy: 100.0,
             pos: &Position { x: 0.0, y: 0.0 },
             nearby_entities: &[],
-            world: &world,
+            neighbors: &EntitySnapshot::from_world(&world),
             config: &config,
             world_size: 100.0,
+            rng: &mut thread_rng(),
         });
 
         x_velocities.push(velocity.x);
@@ -351,16 +398,21 @@ fn test_movement_target_bias() {
     let mut velocity = Velocity { x: 0.0, y: 0.0 };
     let mut energy = 100.0;
 
+    let mover = Genes::new_random(&mut thread_rng());
     movement_system.update_movement(MovementUpdateParams {
-        genes: &Genes::new_random(&mut thread_rng()),
+        genes: &mover,
+        phenotype: &Phenotype::of(&mover),
+        size: &Size { radius: 5.0 },
         new_pos: &mut pos,
         new_velocity: &mut velocity,
         new_energy: &mut energy,
+        max_energy: 100.0,
         pos: &Position { x: 0.0, y: 0.0 },
         nearby_entities: &target_entities,
-        world: &world,
+        neighbors: &EntitySnapshot::from_world(&world),
         config: &config,
         world_size: 100.0,
+        rng: &mut thread_rng(),
     });
 
     println!(
@@ -419,16 +471,21 @@ fn test_long_term_drift_simulation() {
     for step in 0..100 {
         let old_pos = pos.clone();
 
+        let mover = Genes::new_random(&mut thread_rng());
         movement_system.update_movement(MovementUpdateParams {
-            genes: &Genes::new_random(&mut thread_rng()),
+            genes: &mover,
+            phenotype: &Phenotype::of(&mover),
+            size: &Size { radius: 5.0 },
             new_pos: &mut pos,
             new_velocity: &mut velocity,
             new_energy: &mut energy,
+            max_energy: 100.0,
             pos: &old_pos.clone(),
             nearby_entities: &[],
-            world: &world,
+            neighbors: &EntitySnapshot::from_world(&world),
             config: &config,
             world_size: 100.0,
+            rng: &mut thread_rng(),
         });
 
         // Handle boundaries
@@ -623,3 +680,510 @@ fn test_random_number_bias() {
         y_std
     );
 }
+
+#[test]
+fn test_prey_flees_from_larger_predator() {
+    let system = MovementSystem;
+    let mut rng = thread_rng();
+    let config = SimulationConfig::default();
+
+    let mut prey_genes = Genes::new_random(&mut rng);
+    prey_genes.movement.speed = 1.0;
+    prey_genes.movement.sense_radius = 50.0;
+    prey_genes.behavior.movement_style.style = MovementType::Random;
+    prey_genes.behavior.fearfulness = 1.0;
+
+    let mut predator_genes = Genes::new_random(&mut rng);
+    predator_genes.movement.speed = 2.0;
+
+    // Predator sits just to the right of the prey
+    let mut world = World::new();
+    let predator = world.spawn((
+        Position { x: 10.0, y: 0.0 },
+        Energy {
+            current: 50.0,
+            max: 100.0,
+        },
+        Size { radius: 10.0 },
+        predator_genes,
+    ));
+
+    let pos = Position { x: 0.0, y: 0.0 };
+    let mut new_pos = pos.clone();
+    let mut new_velocity = Velocity { x: 0.0, y: 0.0 };
+    let mut new_energy = 100.0;
+
+    system.update_movement(MovementUpdateParams {
+        genes: &prey_genes,
+        phenotype: &Phenotype::of(&prey_genes),
+        size: &Size { radius: 2.0 },
+        new_pos: &mut new_pos,
+        new_velocity: &mut new_velocity,
+        new_energy: &mut new_energy,
+        max_energy: 100.0,
+        pos: &pos,
+        nearby_entities: &[predator],
+        neighbors: &EntitySnapshot::from_world(&world),
+        config: &config,
+        world_size: 200.0,
+        rng: &mut thread_rng(),
+    });
+
+    assert!(new_velocity.x < -1.0, "velocity: {:?}", new_velocity);
+    assert!(new_pos.x < 0.0);
+}
+
+#[test]
+fn test_soft_walls_only_removes_inward_pull() {
+    let system = MovementSystem;
+    let mut config = SimulationConfig::default();
+    config.physics.soft_walls_only = true;
+    let off_center = Position {
+        x: 200.0,
+        y: -150.0,
+    };
+
+    let mut velocity = Velocity { x: 0.0, y: 0.0 };
+    system.apply_center_pressure(&off_center, &mut velocity, &config, 1000.0);
+    assert_eq!((velocity.x, velocity.y), (0.0, 0.0));
+
+    // Near a wall the ramp still pushes back toward the center
+    let near_wall = Position { x: 490.0, y: 0.0 };
+    system.apply_center_pressure(&near_wall, &mut velocity, &config, 1000.0);
+    assert!(velocity.x < 0.0);
+
+    // With the default mode the same off-center entity is pulled inward
+    config.physics.soft_walls_only = false;
+    let mut velocity = Velocity { x: 0.0, y: 0.0 };
+    system.apply_center_pressure(&off_center, &mut velocity, &config, 1000.0);
+    assert!(velocity.x < 0.0 && velocity.y > 0.0);
+}
+
+#[test]
+fn test_higher_kin_threshold_flocks_with_more_neighbors() {
+    let system = MovementSystem;
+    let mut rng = thread_rng();
+    let mut genes = Genes::new_random(&mut rng);
+    genes.movement.sense_radius = 100.0;
+    let phenotype = Phenotype::of(&genes);
+    let neighbors: Vec<Phenotype> = (0..200)
+        .map(|_| Phenotype::of(&Genes::new_random(&mut rng)))
+        .collect();
+
+    let mut flockmates = |threshold: f32| {
+        genes.behavior.movement_style.flock_kin_threshold = threshold;
+        neighbors
+            .iter()
+            .filter(|other| system.is_flockmate(&genes, &phenotype, other, 10.0))
+            .count()
+    };
+
+    let selective = flockmates(0.3);
+    let tolerant = flockmates(0.9);
+    assert!(tolerant > selective, "{} vs {}", tolerant, selective);
+}
+
+#[test]
+fn test_sharp_reversal_costs_more_than_going_straight() {
+    let system = MovementSystem;
+    let mut genes = Genes::new_random(&mut thread_rng());
+    genes.energy.efficiency = 1.0;
+    let mut config = SimulationConfig::default();
+    config.energy.turning_energy_cost = 0.1;
+
+    let heading_east = Velocity { x: 1.5, y: 0.0 };
+    let cost = |new_velocity: Velocity| {
+        let mut energy = 100.0;
+        system.apply_movement_cost(&heading_east, &new_velocity, &mut energy, &genes, &config);
+        100.0 - energy
+    };
+
+    let straight = cost(Velocity { x: 1.5, y: 0.0 });
+    let right_angle = cost(Velocity { x: 0.0, y: 1.5 });
+    let reversal = cost(Velocity { x: -1.5, y: 0.0 });
+
+    assert!((straight - 1.5 * config.energy.movement_energy_cost).abs() < 1e-5);
+    assert!(right_angle > straight);
+    assert!(reversal > right_angle);
+    let expected_turn = std::f32::consts::PI * 1.5 * 0.1;
+    assert!((reversal - straight - expected_turn).abs() < 1e-4);
+}
+
+#[test]
+fn test_behavior_state_transitions() {
+    let config = SimulationConfig::default().behavior;
+    let select = |energy_fraction, predator_near| {
+        BehaviorState::select(
+            &BehaviorContext {
+                energy_fraction,
+                predator_near,
+            },
+            &config,
+        )
+    };
+
+    assert_eq!(select(0.5, false), BehaviorState::Forage);
+    assert_eq!(select(0.9, false), BehaviorState::SeekMate);
+    assert_eq!(select(0.05, false), BehaviorState::Rest);
+    // Danger overrides every other need
+    assert_eq!(select(0.5, true), BehaviorState::Flee);
+    assert_eq!(select(0.9, true), BehaviorState::Flee);
+    assert_eq!(select(0.05, true), BehaviorState::Flee);
+}
+
+/// One movement step for a Random-style entity at the origin with the state machine on
+fn step_with_state_machine(
+    genes: &Genes,
+    energy: f32,
+    velocity: Velocity,
+    world: &World,
+    nearby_entities: &[hecs::Entity],
+) -> Velocity {
+    let mut config = SimulationConfig::default();
+    config.behavior.state_machine = true;
+    let pos = Position { x: 0.0, y: 0.0 };
+    let mut new_velocity = velocity;
+    MovementSystem.update_movement(MovementUpdateParams {
+        genes,
+        phenotype: &Phenotype::of(genes),
+        size: &Size { radius: 2.0 },
+        new_pos: &mut pos.clone(),
+        new_velocity: &mut new_velocity,
+        new_energy: &mut energy.clone(),
+        max_energy: 100.0,
+        pos: &pos,
+        nearby_entities,
+        neighbors: &EntitySnapshot::from_world(world),
+        config: &config,
+        world_size: 200.0,
+        rng: &mut thread_rng(),
+    });
+    new_velocity
+}
+
+#[test]
+fn test_behavior_states_steer_movement() {
+    let mut rng = thread_rng();
+    let mut genes = Genes::new_random(&mut rng);
+    genes.movement.speed = 1.0;
+    genes.movement.sense_radius = 50.0;
+    genes.behavior.movement_style.style = MovementType::Random;
+    genes.behavior.fearfulness = 0.0;
+    let world = World::new();
+
+    // Resting coasts on a fraction of last step's velocity
+    let velocity = step_with_state_machine(&genes, 5.0, Velocity { x: 2.0, y: 0.0 }, &world, &[]);
+    assert!((velocity.x - 0.4).abs() < 1e-4, "velocity: {:?}", velocity);
+    assert_eq!(velocity.y, 0.0);
+
+    // Seeking a mate heads for the nearest kin
+    let mut world = World::new();
+    let kin = world.spawn((
+        Position { x: 20.0, y: 0.0 },
+        Energy {
+            current: 90.0,
+            max: 100.0,
+        },
+        Size { radius: 2.0 },
+        genes.clone(),
+    ));
+    let velocity =
+        step_with_state_machine(&genes, 90.0, Velocity { x: 0.0, y: 0.0 }, &world, &[kin]);
+    assert!((velocity.x - 1.0).abs() < 1e-4, "velocity: {:?}", velocity);
+    assert!(velocity.y.abs() < 1e-4);
+
+    // Fleeing runs flat out even for a fearless genotype
+    let mut predator_genes = Genes::new_random(&mut rng);
+    predator_genes.movement.speed = 2.0;
+    let mut world = World::new();
+    let predator = world.spawn((
+        Position { x: 10.0, y: 0.0 },
+        Energy {
+            current: 50.0,
+            max: 100.0,
+        },
+        Size { radius: 10.0 },
+        predator_genes,
+    ));
+    let velocity = step_with_state_machine(
+        &genes,
+        50.0,
+        Velocity { x: 0.0, y: 0.0 },
+        &world,
+        &[predator],
+    );
+    assert!((velocity.x + 1.2).abs() < 1e-4, "velocity: {:?}", velocity);
+}
+
+#[test]
+fn test_cohesion_coefficient_scales_pull_toward_flock() {
+    let system = MovementSystem;
+    let mut genes = Genes::new_random(&mut thread_rng());
+    genes.movement.sense_radius = 100.0;
+    genes.behavior.movement_style.flock_kin_threshold = 1.0;
+    genes.behavior.movement_style.flocking_strength = 1.0;
+    genes.behavior.movement_style.cohesion_strength = 1.0;
+    genes.behavior.movement_style.separation_distance = 5.0;
+
+    // A single identical flockmate, still and well beyond separation distance
+    let mut world = World::new();
+    let mate = world.spawn((
+        Position { x: 20.0, y: 0.0 },
+        Velocity { x: 0.0, y: 0.0 },
+        Energy {
+            current: 50.0,
+            max: 100.0,
+        },
+        Size { radius: 2.0 },
+        genes.clone(),
+    ));
+    let neighbors = EntitySnapshot::from_world(&world);
+
+    let cohesion_pull = |coefficient: f32| {
+        let mut config = SimulationConfig::default();
+        config.behavior.flock_cohesion_coefficient = coefficient;
+        let mut velocity = Velocity { x: 0.0, y: 0.0 };
+        system.apply_flocking_behavior(
+            &Position { x: 0.0, y: 0.0 },
+            &genes,
+            &Phenotype::of(&genes),
+            &[mate],
+            &neighbors,
+            &mut velocity,
+            &config,
+        );
+        velocity.x
+    };
+
+    let weak = cohesion_pull(0.1);
+    let strong = cohesion_pull(0.5);
+    assert!((weak - 2.0).abs() < 1e-4, "weak pull: {}", weak);
+    assert!((strong - 10.0).abs() < 1e-4, "strong pull: {}", strong);
+}
+
+#[test]
+fn test_torpid_entity_moves_less() {
+    let system = MovementSystem;
+    let mut genes = Genes::new_random(&mut thread_rng());
+    genes.movement.speed = 2.0;
+    genes.behavior.movement_style.style = MovementType::Random;
+    let mut config = SimulationConfig::default();
+    config.behavior.torpor_threshold = 0.1;
+    let world = World::new();
+    let neighbors = EntitySnapshot::from_world(&world);
+
+    // Distance moved and energy spent in one step from rest at the origin
+    let step = |energy: f32| {
+        let pos = Position { x: 0.0, y: 0.0 };
+        let mut new_pos = pos.clone();
+        let mut new_energy = energy;
+        system.update_movement(MovementUpdateParams {
+            genes: &genes,
+            phenotype: &Phenotype::of(&genes),
+            size: &Size { radius: 2.0 },
+            new_pos: &mut new_pos,
+            new_velocity: &mut Velocity { x: 0.0, y: 0.0 },
+            new_energy: &mut new_energy,
+            max_energy: 100.0,
+            pos: &pos,
+            nearby_entities: &[],
+            neighbors: &neighbors,
+            config: &config,
+            world_size: 200.0,
+            rng: &mut thread_rng(),
+        });
+        (new_pos.x.hypot(new_pos.y), energy - new_energy)
+    };
+
+    let (starving_distance, starving_cost) = step(5.0);
+    let (healthy_distance, healthy_cost) = step(80.0);
+    assert!(
+        starving_distance * 4.0 < healthy_distance,
+        "{} vs {}",
+        starving_distance,
+        healthy_distance
+    );
+    assert!(starving_cost < healthy_cost);
+}
+
+#[test]
+fn test_damping_slows_a_coasting_entity() {
+    let system = MovementSystem;
+    let genes = Genes::new_random(&mut thread_rng());
+    let mut config = SimulationConfig::default();
+    // A resting entity keeps its velocity, so only drag acts on it
+    config.behavior.state_machine = true;
+    config.behavior.rest_speed_factor = 1.0;
+    config.physics.velocity_damping = 0.1;
+    let world = World::new();
+    let neighbors = EntitySnapshot::from_world(&world);
+
+    let pos = Position { x: 0.0, y: 0.0 };
+    let mut velocity = Velocity { x: 1.4, y: 1.4 };
+    let mut speeds = vec![velocity.x.hypot(velocity.y)];
+    for _ in 0..5 {
+        system.update_movement(MovementUpdateParams {
+            genes: &genes,
+            phenotype: &Phenotype::of(&genes),
+            size: &Size { radius: 2.0 },
+            new_pos: &mut pos.clone(),
+            new_velocity: &mut velocity,
+            new_energy: &mut 5.0,
+            max_energy: 100.0,
+            pos: &pos,
+            nearby_entities: &[],
+            neighbors: &neighbors,
+            config: &config,
+            world_size: 200.0,
+            rng: &mut thread_rng(),
+        });
+        speeds.push(velocity.x.hypot(velocity.y));
+    }
+    for pair in speeds.windows(2) {
+        assert!(pair[1] < pair[0], "speeds: {:?}", speeds);
+    }
+    assert!((speeds[5] - speeds[0] * 0.9f32.powi(5)).abs() < 1e-4);
+}
+
+#[test]
+fn test_diffusion_kicks_have_zero_mean_and_bounded_magnitude() {
+    let system = MovementSystem;
+    let mut config = SimulationConfig::default();
+    config.physics.diffusion_strength = 0.5;
+    let mut rng = StdRng::seed_from_u64(12);
+
+    let samples = 20_000;
+    let (mut sum_x, mut sum_y) = (0.0f64, 0.0f64);
+    for _ in 0..samples {
+        let mut velocity = Velocity { x: 0.0, y: 0.0 };
+        system.apply_diffusion(&mut velocity, &config, &mut rng);
+        assert!(velocity.x.hypot(velocity.y) <= 0.5 + 1e-6);
+        sum_x += velocity.x as f64;
+        sum_y += velocity.y as f64;
+    }
+    // The standard error of each mean is about 0.5 / 2 / sqrt(samples) = 0.0018
+    let (mean_x, mean_y) = (sum_x / samples as f64, sum_y / samples as f64);
+    assert!(
+        mean_x.abs() < 0.01 && mean_y.abs() < 0.01,
+        "{} {}",
+        mean_x,
+        mean_y
+    );
+
+    config.physics.diffusion_strength = 0.0;
+    let mut velocity = Velocity { x: 0.3, y: -0.2 };
+    system.apply_diffusion(&mut velocity, &config, &mut rng);
+    assert_eq!((velocity.x, velocity.y), (0.3, -0.2));
+}
+
+#[test]
+fn test_velocity_cap_is_isotropic() {
+    let system = MovementSystem;
+    let config = SimulationConfig::default();
+    let max = config.physics.max_velocity;
+
+    let mut axial = Velocity { x: 10.0, y: 0.0 };
+    let mut diagonal = Velocity { x: 10.0, y: 10.0 };
+    system.cap_velocity(&mut axial, &config);
+    system.cap_velocity(&mut diagonal, &config);
+    assert!((axial.x.hypot(axial.y) - max).abs() < 1e-5);
+    assert!((diagonal.x.hypot(diagonal.y) - max).abs() < 1e-5);
+    assert!((diagonal.x - diagonal.y).abs() < 1e-6);
+}
+
+#[test]
+fn test_same_rng_state_gives_identical_random_movement() {
+    let system = MovementSystem;
+    let mut genes = Genes::new_random(&mut thread_rng());
+    let config = SimulationConfig::default();
+    let world = World::new();
+    let neighbors = EntitySnapshot::from_world(&world);
+
+    for style in [MovementType::Random, MovementType::Grazing] {
+        genes.behavior.movement_style.style = style.clone();
+        // Position and velocity after ten steps driven by one seeded stream
+        let walk = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut pos = Position { x: 0.0, y: 0.0 };
+            let mut velocity = Velocity { x: 0.0, y: 0.0 };
+            for _ in 0..10 {
+                let mut new_pos = pos.clone();
+                let mut new_velocity = velocity.clone();
+                system.update_movement(MovementUpdateParams {
+                    genes: &genes,
+                    phenotype: &Phenotype::of(&genes),
+                    size: &Size { radius: 2.0 },
+                    new_pos: &mut new_pos,
+                    new_velocity: &mut new_velocity,
+                    new_energy: &mut 80.0,
+                    max_energy: 100.0,
+                    pos: &pos,
+                    nearby_entities: &[],
+                    neighbors: &neighbors,
+                    config: &config,
+                    world_size: 200.0,
+                    rng: &mut rng,
+                });
+                pos = new_pos;
+                velocity = new_velocity;
+            }
+            (pos.x, pos.y, velocity.x, velocity.y)
+        };
+
+        assert_eq!(walk(11), walk(11), "{:?} is not reproducible", style);
+        assert_ne!(walk(11), walk(12), "{:?} ignores its rng", style);
+    }
+}
+
+#[test]
+fn test_predator_speed_multiplier_sets_chase_speed() {
+    let system = MovementSystem;
+    let mut rng = thread_rng();
+    let mut predator_genes = Genes::new_random(&mut rng);
+    predator_genes.movement.speed = 1.0;
+    predator_genes.movement.sense_radius = 50.0;
+    predator_genes.behavior.movement_style.style = MovementType::Predatory;
+    predator_genes.behavior.fearfulness = 0.0;
+    predator_genes.behavior.gene_preference_strength = 0.0;
+
+    let mut prey_genes = Genes::new_random(&mut rng);
+    prey_genes.movement.speed = 1.0;
+    let mut world = World::new();
+    let prey = world.spawn((
+        Position { x: 10.0, y: 0.0 },
+        Energy {
+            current: 50.0,
+            max: 100.0,
+        },
+        Size { radius: 0.5 },
+        prey_genes,
+    ));
+    let neighbors = EntitySnapshot::from_world(&world);
+
+    let chase_speed = |multiplier: f32| {
+        let mut config = SimulationConfig::default();
+        config.behavior.predator_speed_multiplier = multiplier;
+        let pos = Position { x: 0.0, y: 0.0 };
+        let mut new_velocity = Velocity { x: 0.0, y: 0.0 };
+        system.update_movement(MovementUpdateParams {
+            genes: &predator_genes,
+            phenotype: &Phenotype::of(&predator_genes),
+            size: &Size { radius: 5.0 },
+            new_pos: &mut pos.clone(),
+            new_velocity: &mut new_velocity,
+            new_energy: &mut 80.0,
+            max_energy: 100.0,
+            pos: &pos,
+            nearby_entities: &[prey],
+            neighbors: &neighbors,
+            config: &config,
+            world_size: 200.0,
+            rng: &mut thread_rng(),
+        });
+        assert!(new_velocity.x > 0.0, "not chasing: {:?}", new_velocity);
+        new_velocity.x.hypot(new_velocity.y)
+    };
+
+    assert!((chase_speed(1.2) - 1.2).abs() < 1e-4);
+    assert!((chase_speed(1.8) - 1.8).abs() < 1e-4);
+}
@@ -1,26 +1,42 @@
 use crate::components::{Energy, Position, Size, Velocity};
-use crate::config::SimulationConfig;
-use crate::genes::Genes;
+use crate::config::{ChildEnergyMode, SimulationConfig};
+use crate::genes::{DiploidGenes, Genes};
+use crate::sampling::random_in_disc;
+use hecs::{Entity, World};
 use rand::prelude::*;
 
+/// Candidate spots tried before an offspring is placed regardless of overlap
+const PLACEMENT_TRIES: usize = 8;
+
 /// Reproduction system - handles entity reproduction and population control
 pub struct ReproductionSystem;
 
 impl ReproductionSystem {
+    /// `cap_fraction` is the population over its cap, not a spatial density, so births
+    /// slow as the cap nears however large the world is. Entities younger than
+    /// `maturity_age` steps never reproduce.
+    #[allow(clippy::too_many_arguments)]
     pub fn check_reproduction(
         &self,
         energy: f32,
         max_energy: f32,
         genes: &Genes,
-        population_density: f32,
+        age: u32,
+        cap_fraction: f32,
         config: &SimulationConfig,
+        rng: &mut impl Rng,
     ) -> bool {
+        if age < config.reproduction.maturity_age {
+            return false;
+        }
+        let (rate_factor, _) = Self::strategy_factors(genes.reproduction.strategy);
         let reproduction_chance = genes.reproduction_rate()
-            * (1.0 - population_density * config.reproduction.population_density_factor)
+            * rate_factor
+            * (1.0 - cap_fraction * config.reproduction.population_density_factor)
                 .max(config.reproduction.min_reproduction_chance);
 
         energy > max_energy * config.reproduction.reproduction_energy_threshold
-            && thread_rng().gen::<f32>() < reproduction_chance
+            && rng.gen::<f32>() < reproduction_chance
     }
 
     pub fn create_offspring(
@@ -28,7 +44,9 @@ impl ReproductionSystem {
         parent_genes: &Genes,
         parent_energy_max: f32,
         parent_pos: &Position,
+        occupied: &[(Position, f32)],
         config: &SimulationConfig,
+        rng: &mut impl Rng,
     ) -> (
         Position,
         Energy,
@@ -38,34 +56,79 @@ impl ReproductionSystem {
         Velocity,
         crate::components::MovementStyle,
     ) {
-        let mut rng = thread_rng();
-        let child_genes = parent_genes.mutate(&mut rng);
-        let child_energy = parent_energy_max * config.reproduction.child_energy_factor;
+        let child_genes = parent_genes.mutate_with(rng, &config.genetics);
+        self.create_offspring_with_genes(
+            child_genes,
+            parent_energy_max,
+            None,
+            parent_pos,
+            occupied,
+            config,
+            rng,
+        )
+    }
+
+    /// Parent's energy after giving birth, plus the energy it hands the child in
+    /// `ChildEnergyMode::Inherit`. Inheriting children take `child_energy_factor` of the
+    /// parent's current energy (scaled by its provisioning) straight out of the parent, so
+    /// the pair hold exactly what the parent did. Otherwise the parent pays
+    /// `reproduction_energy_cost` and the child's energy is set from the parent's max.
+    pub fn debit_parent(
+        &self,
+        energy: f32,
+        max_energy: f32,
+        genes: &Genes,
+        config: &SimulationConfig,
+    ) -> (f32, Option<f32>) {
+        match config.reproduction.child_energy_mode {
+            ChildEnergyMode::Reset => (energy * config.reproduction.reproduction_energy_cost, None),
+            ChildEnergyMode::Inherit => {
+                let (_, provisioning) = Self::strategy_factors(genes.reproduction.strategy);
+                let child_energy =
+                    (energy * config.reproduction.child_energy_factor * provisioning)
+                        .min(energy)
+                        .min(max_energy / config.energy.max_energy_ratio.max(1.0));
+                (energy - child_energy, Some(child_energy))
+            }
+        }
+    }
+
+    /// Build an offspring from already-inherited genes (e.g. a diploid cross).
+    /// `inherited_energy` is the child's energy from `debit_parent`, if it inherits any.
+    /// `occupied` lists the positions and radii of nearby entities the child should not overlap.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_offspring_with_genes(
+        &self,
+        child_genes: Genes,
+        parent_energy_max: f32,
+        inherited_energy: Option<f32>,
+        parent_pos: &Position,
+        occupied: &[(Position, f32)],
+        config: &SimulationConfig,
+        rng: &mut impl Rng,
+    ) -> (
+        Position,
+        Energy,
+        Size,
+        Genes,
+        crate::components::Color,
+        Velocity,
+        crate::components::MovementStyle,
+    ) {
+        // Provisioning follows the inherited strategy, which matches the parent's up to mutation.
+        // Newborns keep the same headroom above their starting energy as spawned entities.
+        let (_, provisioning) = Self::strategy_factors(child_genes.reproduction.strategy);
+        let child_energy = inherited_energy.unwrap_or_else(|| {
+            (parent_energy_max * config.reproduction.child_energy_factor * provisioning)
+                .min(parent_energy_max / config.energy.max_energy_ratio.max(1.0))
+        });
         let child_radius = (child_energy / 15.0 * child_genes.size_factor())
             .clamp(config.physics.min_entity_radius, 15.0);
         let child_color = child_genes.get_color();
-
-        // Use uniform distribution in a circle for child positioning
-        let (dx, dy) = loop {
-            let dx = rng.gen_range(
-                -config.reproduction.child_spawn_radius..config.reproduction.child_spawn_radius,
-            );
-            let dy = rng.gen_range(
-                -config.reproduction.child_spawn_radius..config.reproduction.child_spawn_radius,
-            );
-            let distance_sq = dx * dx + dy * dy;
-            if distance_sq
-                <= config.reproduction.child_spawn_radius * config.reproduction.child_spawn_radius
-            {
-                break (dx, dy);
-            }
-        };
+        let child_pos = self.place_offspring(parent_pos, child_radius, occupied, config, rng);
 
         (
-            Position {
-                x: parent_pos.x + dx,
-                y: parent_pos.y + dy,
-            },
+            child_pos,
             Energy {
                 current: child_energy,
                 max: parent_energy_max,
@@ -80,9 +143,73 @@ impl ReproductionSystem {
         )
     }
 
-    pub fn check_death(&self, population_density: f32, config: &SimulationConfig) -> bool {
-        let death_chance = population_density * config.reproduction.death_chance_factor;
-        thread_rng().gen::<f32>() < death_chance
+    /// r/K trade-off as (reproduction rate, offspring provisioning) multipliers. Strategy 0.5
+    /// is neutral; each step towards 1.0 doubles the rate as it halves the provisioning.
+    fn strategy_factors(strategy: f32) -> (f32, f32) {
+        let skew = strategy.clamp(0.0, 1.0) * 2.0 - 1.0;
+        (2f32.powf(skew), 2f32.powf(-skew))
+    }
+
+    /// Pick a spot within `child_spawn_radius` of the parent that doesn't overlap any occupied
+    /// circle, falling back to the last candidate once `PLACEMENT_TRIES` are used up
+    fn place_offspring(
+        &self,
+        parent_pos: &Position,
+        child_radius: f32,
+        occupied: &[(Position, f32)],
+        config: &SimulationConfig,
+        rng: &mut impl Rng,
+    ) -> Position {
+        let mut candidate = parent_pos.clone();
+        for _ in 0..PLACEMENT_TRIES {
+            let (dx, dy) = random_in_disc(rng, config.reproduction.child_spawn_radius);
+            candidate = Position {
+                x: parent_pos.x + dx,
+                y: parent_pos.y + dy,
+            };
+            let clear = occupied.iter().all(|(pos, radius)| {
+                (pos.x - candidate.x).powi(2) + (pos.y - candidate.y).powi(2)
+                    >= (radius + child_radius).powi(2)
+            });
+            if clear {
+                break;
+            }
+        }
+        candidate
+    }
+
+    /// Sexual reproduction for diploid genotypes: one gamete from the parent and one from the
+    /// first nearby diploid mate, falling back to selfing when no mate is in range. Returns
+    /// the child's genotype and the mate, which is `entity` itself when selfing.
+    pub fn cross_with_nearby_mate(
+        &self,
+        parent: &DiploidGenes,
+        entity: Entity,
+        nearby_entities: &[Entity],
+        world: &World,
+        config: &SimulationConfig,
+        rng: &mut impl Rng,
+    ) -> (DiploidGenes, Entity) {
+        let mate = nearby_entities
+            .iter()
+            .filter(|&&other| other != entity)
+            .find_map(|&other| Some((other, world.get::<&DiploidGenes>(other).ok()?)));
+
+        match mate {
+            Some((other, mate)) => (parent.cross(&mate, rng, &config.genetics), other),
+            None => (parent.cross(parent, rng, &config.genetics), entity),
+        }
+    }
+
+    /// Death from crowding also scales with `cap_fraction`, keeping the cap a soft limit
+    pub fn check_death(
+        &self,
+        cap_fraction: f32,
+        config: &SimulationConfig,
+        rng: &mut impl Rng,
+    ) -> bool {
+        let death_chance = cap_fraction * config.reproduction.death_chance_factor;
+        rng.gen::<f32>() < death_chance
     }
 }
 
@@ -104,8 +231,15 @@ mod tests {
         let population_density = 0.1; // Low density for higher reproduction chance
         let config = SimulationConfig::default();
 
-        let _should_reproduce =
-            system.check_reproduction(energy, max_energy, &genes, population_density, &config);
+        let _should_reproduce = system.check_reproduction(
+            energy,
+            max_energy,
+            &genes,
+            u32::MAX,
+            population_density,
+            &config,
+            &mut rng,
+        );
     }
 
     #[test]
@@ -117,8 +251,15 @@ mod tests {
         let parent_pos = Position { x: 0.0, y: 0.0 };
         let config = SimulationConfig::default();
 
-        let (pos, energy, size, _genes, color, velocity, _movement_style) =
-            system.create_offspring(&parent_genes, parent_energy_max, &parent_pos, &config);
+        let (pos, energy, size, _genes, color, velocity, _movement_style) = system
+            .create_offspring(
+                &parent_genes,
+                parent_energy_max,
+                &parent_pos,
+                &[],
+                &config,
+                &mut rng,
+            );
 
         // Position should be near parent
         let distance = ((pos.x - parent_pos.x).powi(2) + (pos.y - parent_pos.y).powi(2)).sqrt();
@@ -142,13 +283,169 @@ mod tests {
         assert!(velocity.y.abs() <= config.physics.max_velocity);
     }
 
+    #[test]
+    fn test_offspring_keep_max_energy_ratio() {
+        let system = ReproductionSystem;
+        let mut genes = Genes::new_random(&mut thread_rng());
+        genes.reproduction.strategy = 0.5; // Neutral provisioning
+        let mut config = SimulationConfig::default();
+        config.reproduction.child_energy_factor = 0.9;
+        config.energy.max_energy_ratio = 2.0;
+
+        let (_, energy, ..) = system.create_offspring(
+            &genes,
+            100.0,
+            &Position { x: 0.0, y: 0.0 },
+            &[],
+            &config,
+            &mut thread_rng(),
+        );
+        assert_eq!(energy.current, 50.0);
+        assert_eq!(energy.max, 100.0);
+    }
+
+    #[test]
+    fn test_child_energy_modes() {
+        let system = ReproductionSystem;
+        let mut genes = Genes::new_random(&mut thread_rng());
+        genes.reproduction.strategy = 0.5; // Neutral provisioning
+        let mut config = SimulationConfig::default();
+        config.reproduction.child_energy_factor = 0.4;
+        config.reproduction.reproduction_energy_cost = 0.7;
+        let parent = Position { x: 0.0, y: 0.0 };
+        let birth = |config: &SimulationConfig| {
+            let (parent_energy, inherited) = system.debit_parent(60.0, 100.0, &genes, config);
+            let (_, child, ..) = system.create_offspring_with_genes(
+                genes.clone(),
+                100.0,
+                inherited,
+                &parent,
+                &[],
+                config,
+                &mut thread_rng(),
+            );
+            (parent_energy, child.current)
+        };
+
+        // Reset: the child gets a share of the parent's max, the parent pays the flat cost
+        let (parent_energy, child_energy) = birth(&config);
+        assert!((parent_energy - 42.0).abs() < 1e-4);
+        assert!((child_energy - 40.0).abs() < 1e-4);
+
+        // Inherit: the child's share of the parent's current energy comes out of the parent
+        config.reproduction.child_energy_mode = ChildEnergyMode::Inherit;
+        let (parent_energy, child_energy) = birth(&config);
+        assert!((child_energy - 24.0).abs() < 1e-4);
+        assert!((parent_energy - 36.0).abs() < 1e-4);
+        assert!((parent_energy + child_energy - 60.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_r_strategists_have_cheaper_offspring() {
+        let system = ReproductionSystem;
+        let config = SimulationConfig::default();
+        let mut r_genes = Genes::new_random(&mut thread_rng());
+        r_genes.reproduction.mutation_rate = 0.0;
+        let mut k_genes = r_genes.clone();
+        r_genes.reproduction.strategy = 0.9;
+        k_genes.reproduction.strategy = 0.1;
+
+        let parent = Position { x: 0.0, y: 0.0 };
+        let mut rng = thread_rng();
+        let (_, r_child, ..) =
+            system.create_offspring(&r_genes, 100.0, &parent, &[], &config, &mut rng);
+        let (_, k_child, ..) =
+            system.create_offspring(&k_genes, 100.0, &parent, &[], &config, &mut rng);
+        assert!(r_child.current < k_child.current);
+
+        // ...and reproduce more often, all else equal
+        let (r_rate, _) = ReproductionSystem::strategy_factors(0.9);
+        let (k_rate, _) = ReproductionSystem::strategy_factors(0.1);
+        assert!(r_rate > k_rate);
+    }
+
+    #[test]
+    fn test_offspring_avoid_occupied_space() {
+        let system = ReproductionSystem;
+        let genes = Genes::new_random(&mut thread_rng());
+        let config = SimulationConfig::default();
+        let parent = Position { x: 0.0, y: 0.0 };
+        let occupied = [(parent.clone(), 3.0), (Position { x: 14.0, y: 0.0 }, 1.0)];
+
+        for _ in 0..100 {
+            let (pos, _, size, ..) = system.create_offspring(
+                &genes,
+                30.0,
+                &parent,
+                &occupied,
+                &config,
+                &mut thread_rng(),
+            );
+            for (other, radius) in &occupied {
+                let distance = ((pos.x - other.x).powi(2) + (pos.y - other.y).powi(2)).sqrt();
+                assert!(
+                    distance >= radius + size.radius,
+                    "child at ({}, {}) overlaps entity at ({}, {})",
+                    pos.x,
+                    pos.y,
+                    other.x,
+                    other.y
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_reproduction_system_check_death() {
         let system = ReproductionSystem;
         let population_density = 0.9; // High density
         let config = SimulationConfig::default();
 
-        let _should_die = system.check_death(population_density, &config);
+        let _should_die = system.check_death(population_density, &config, &mut thread_rng());
+    }
+
+    #[test]
+    fn test_cross_with_nearby_mate() {
+        let system = ReproductionSystem;
+        let mut rng = thread_rng();
+        let mut world = hecs::World::new();
+
+        let mut parent_allele = Genes::new_random(&mut rng);
+        parent_allele.movement.speed = 1.0;
+        parent_allele.reproduction.mutation_rate = 0.0;
+        let mut mate_allele = parent_allele.clone();
+        mate_allele.movement.speed = 2.0;
+
+        let parent = DiploidGenes {
+            a: parent_allele.clone(),
+            b: parent_allele,
+        };
+        let mate = DiploidGenes {
+            a: mate_allele.clone(),
+            b: mate_allele,
+        };
+        let parent_entity = world.spawn((parent.clone(),));
+        let mate_entity = world.spawn((mate,));
+
+        let config = SimulationConfig::default();
+        let nearby = [parent_entity, mate_entity];
+        let (child, mate) = system.cross_with_nearby_mate(
+            &parent,
+            parent_entity,
+            &nearby,
+            &world,
+            &config,
+            &mut rng,
+        );
+        assert_eq!(child.a.speed(), 1.0);
+        assert_eq!(child.b.speed(), 2.0);
+        assert_eq!(mate, mate_entity);
+
+        // Without a mate in range the parent self-fertilises
+        let (selfed, mate) =
+            system.cross_with_nearby_mate(&parent, parent_entity, &[], &world, &config, &mut rng);
+        assert_eq!(selfed.b.speed(), 1.0);
+        assert_eq!(mate, parent_entity);
     }
 
     #[test]
@@ -161,8 +458,15 @@ mod tests {
         let population_density = 0.1; // Low density
         let config = SimulationConfig::default();
 
-        let should_reproduce =
-            system.check_reproduction(energy, max_energy, &genes, population_density, &config);
+        let should_reproduce = system.check_reproduction(
+            energy,
+            max_energy,
+            &genes,
+            u32::MAX,
+            population_density,
+            &config,
+            &mut rng,
+        );
 
         // Should not reproduce with low energy
         assert!(!should_reproduce);
@@ -193,7 +497,7 @@ mod tests {
                 let mut total_x = 0.0;
                 let mut total_y = 0.0;
 
-                for (_px, _py, cx, cy, _, _, _, _) in &entities {
+                for (_px, _py, cx, cy, _, _, _, _, _) in &entities {
                     total_x += cx;
                     total_y += cy;
                 }
@@ -0,0 +1,142 @@
+use crate::components::Position;
+use crate::config::SimulationConfig;
+use crate::genes::{Genes, Phenotype};
+use crate::snapshot::EntitySnapshot;
+use hecs::Entity;
+
+/// Only entities at least this social share energy
+const ALTRUIST_SOCIAL_TENDENCY: f32 = 0.7;
+
+/// A donor must hold more than this fraction of its max energy to give any away
+const HEALTHY_ENERGY_FRACTION: f32 = 0.5;
+
+/// Kin below this fraction of their max energy are helped
+const NEEDY_ENERGY_FRACTION: f32 = 0.25;
+
+/// Energy sharing between kin: highly social entities hand part of their energy to a
+/// starving relative nearby, so altruism can be selected for through kin
+pub struct SocialSystem;
+
+pub struct ShareParams<'a> {
+    pub entity: Entity,
+    pub new_energy: &'a mut f32,
+    pub max_energy: f32,
+    pub pos: &'a Position,
+    pub genes: &'a Genes,
+    pub phenotype: &'a Phenotype, // Cached from `genes`
+    pub nearby_entities: &'a [Entity],
+    pub neighbors: &'a EntitySnapshot, // Components of nearby entities
+    pub config: &'a SimulationConfig,
+}
+
+impl SocialSystem {
+    /// Give `behavior.energy_share_amount` to the neediest starving kin within sense range,
+    /// if the entity is an altruist with energy to spare. Kin within their starvation grace
+    /// count, being the neediest of all. The gift is taken from the donor
+    /// here; the recipient and amount are returned for the caller to deliver.
+    pub fn share_energy(&self, params: ShareParams) -> Option<(Entity, f32)> {
+        let ShareParams {
+            entity,
+            new_energy,
+            max_energy,
+            pos,
+            genes,
+            phenotype,
+            nearby_entities,
+            neighbors,
+            config,
+        } = params;
+        let behavior = &config.behavior;
+        if behavior.energy_share_amount <= 0.0
+            || genes.behavior.social_tendency < ALTRUIST_SOCIAL_TENDENCY
+            || *new_energy <= max_energy * HEALTHY_ENERGY_FRACTION
+        {
+            return None;
+        }
+
+        let recipient = nearby_entities
+            .iter()
+            .filter(|&&other| other != entity)
+            .filter_map(|&other| Some((other, neighbors.get(other)?)))
+            .filter(|(_, nearby)| {
+                let distance_sq = (nearby.pos.x - pos.x).powi(2) + (nearby.pos.y - pos.y).powi(2);
+                let fraction = nearby.energy.current / nearby.energy.max.max(f32::EPSILON);
+                distance_sq < genes.sense_radius().powi(2)
+                    && fraction < NEEDY_ENERGY_FRACTION
+                    && phenotype.similarity(nearby.phenotype) < behavior.energy_share_kin_threshold
+            })
+            .min_by(|a, b| a.1.energy.current.total_cmp(&b.1.energy.current))
+            .map(|(other, _)| other)?;
+
+        let amount = behavior.energy_share_amount;
+        *new_energy -= amount;
+        Some((recipient, amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Energy, Size};
+    use hecs::World;
+    use rand::thread_rng;
+
+    fn spawn(world: &mut World, genes: &Genes, x: f32, energy: f32) -> Entity {
+        world.spawn((
+            Position { x, y: 0.0 },
+            Size { radius: 2.0 },
+            Energy {
+                current: energy,
+                max: 100.0,
+            },
+            genes.clone(),
+        ))
+    }
+
+    #[test]
+    fn test_healthy_altruist_feeds_starving_kin() {
+        let mut altruist = Genes::new_random(&mut thread_rng());
+        altruist.behavior.social_tendency = 0.9;
+        altruist.movement.sense_radius = 50.0;
+        let mut stranger = altruist.clone();
+        stranger.appearance.hue += if stranger.appearance.hue < 0.5 {
+            0.5
+        } else {
+            -0.5
+        };
+        stranger.behavior.social_tendency = 0.0;
+
+        let mut world = World::new();
+        let donor = spawn(&mut world, &altruist, 0.0, 80.0);
+        let kin = spawn(&mut world, &altruist, 5.0, -2.0); // Within its starvation grace
+        let starving_stranger = spawn(&mut world, &stranger, 3.0, 5.0);
+        let snapshot = EntitySnapshot::from_world(&world);
+        let mut config = SimulationConfig::default();
+        config.behavior.energy_share_amount = 4.0;
+
+        let share = |energy: &mut f32, config: &SimulationConfig| {
+            SocialSystem.share_energy(ShareParams {
+                entity: donor,
+                new_energy: energy,
+                max_energy: 100.0,
+                pos: &Position { x: 0.0, y: 0.0 },
+                genes: &altruist,
+                phenotype: &Phenotype::of(&altruist),
+                nearby_entities: &[donor, starving_stranger, kin],
+                neighbors: &snapshot,
+                config,
+            })
+        };
+
+        let mut energy = 80.0;
+        assert_eq!(share(&mut energy, &config), Some((kin, 4.0)));
+        assert_eq!(energy, 76.0);
+
+        let mut hungry = 40.0;
+        assert_eq!(share(&mut hungry, &config), None);
+        assert_eq!(hungry, 40.0);
+
+        config.behavior.energy_share_amount = 0.0;
+        assert_eq!(share(&mut energy, &config), None);
+    }
+}
@@ -1,27 +1,5 @@
-use bytemuck::{Pod, Zeroable};
+use crate::render::{instances_from_buffer, EntityPipeline, SimulationUniforms};
 use wasm_bindgen::prelude::*;
-use wgpu::util::DeviceExt;
-
-/// Instance data for each entity (32 bytes each)
-#[repr(C)]
-#[derive(Copy, Clone, Debug, Pod, Zeroable)]
-struct Instance {
-    prev_curr_pos: [f32; 4], // xy = prev_pos, zw = curr_pos
-    radius_color: [f32; 4],  // x = radius, yzw = color (rgb)
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, Pod, Zeroable)]
-struct SimulationUniforms {
-    world_size: f32,
-    interpolation_factor: f32,
-    camera_zoom: f32,
-    camera_x: f32,
-    camera_y: f32,
-    padding1: f32,
-    padding2: f32,
-    padding3: f32,
-}
 
 #[wasm_bindgen]
 pub struct WebGpuRenderer {
@@ -29,11 +7,9 @@ pub struct WebGpuRenderer {
     queue: wgpu::Queue,
     surface: wgpu::Surface<'static>,
     config: wgpu::SurfaceConfiguration,
-    render_pipeline: wgpu::RenderPipeline,
-    instance_buffer: wgpu::Buffer,
-    uniform_buffer: wgpu::Buffer,
-    bind_group: wgpu::BindGroup,
-    num_instances: u32,
+    pipeline: EntityPipeline,
+    highlight: [f32; 3],         // x, y, active
+    low_energy_marker: [f32; 2], // style, threshold
     width: u32,
     height: u32,
 }
@@ -105,140 +81,16 @@ impl WebGpuRenderer {
         };
         surface.configure(&device, &config);
 
-        // Create uniforms
-        let uniforms = SimulationUniforms {
-            world_size: 1000.0, // Default, will be updated
-            interpolation_factor: 0.0,
-            camera_zoom: 1.0,
-            camera_x: 0.0,
-            camera_y: 0.0,
-            padding1: 0.0,
-            padding2: 0.0,
-            padding3: 0.0,
-        };
-
-        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Simulation Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[uniforms]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Simulation Bind Group Layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-        });
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Simulation Bind Group"),
-            layout: &bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
-            }],
-        });
-
-        // Create shader
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../shader.wgsl").into()),
-        });
-
-        // Create pipeline layout
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[&bind_group_layout],
-                push_constant_ranges: &[],
-            });
-
-        // Create render pipeline with instanced rendering
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
-                    step_mode: wgpu::VertexStepMode::Instance,
-                    attributes: &[
-                        wgpu::VertexAttribute {
-                            offset: 0,
-                            shader_location: 0,
-                            format: wgpu::VertexFormat::Float32x4, // prev_curr_pos
-                        },
-                        wgpu::VertexAttribute {
-                            offset: 16,
-                            shader_location: 1,
-                            format: wgpu::VertexFormat::Float32x4, // radius_color
-                        },
-                    ],
-                }],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
-        });
-
-        // Create instance buffer (pre-allocate for 20000 entities)
-        let initial_instances = vec![
-            Instance {
-                prev_curr_pos: [0.0, 0.0, 0.0, 0.0],
-                radius_color: [0.0, 0.0, 0.0, 0.0],
-            };
-            20000
-        ];
-
-        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Instance Buffer"),
-            contents: bytemuck::cast_slice(&initial_instances),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-        });
+        let pipeline = EntityPipeline::new(&device, config.format);
 
         Ok(WebGpuRenderer {
             device,
             queue,
             surface,
             config,
-            render_pipeline,
-            instance_buffer,
-            uniform_buffer,
-            bind_group,
-            num_instances: 0,
+            pipeline,
+            highlight: [0.0; 3],
+            low_energy_marker: [0.0; 2],
             width,
             height,
         })
@@ -254,10 +106,22 @@ impl WebGpuRenderer {
         }
     }
 
+    /// Draw a ring around the entity at `(x, y)` until cleared with `active = false`
+    pub fn set_highlight(&mut self, x: f32, y: f32, active: bool) {
+        self.highlight = [x, y, if active { 1.0 } else { 0.0 }];
+    }
+
+    /// Mark entities below `threshold` of their max energy: style 0 = none, 1 = outline,
+    /// 2 = energy bar
+    pub fn set_low_energy_marker(&mut self, style: f32, threshold: f32) {
+        self.low_energy_marker = [style, threshold];
+    }
+
     pub fn render(
         &mut self,
         entities_ptr: *const f32,
         entity_count: u32,
+        layout: u32,
         world_size: f32,
         interpolation_factor: f32,
         camera_zoom: f32,
@@ -268,44 +132,29 @@ impl WebGpuRenderer {
             return;
         }
 
-        // Update uniforms
         let uniforms = SimulationUniforms {
             world_size,
             interpolation_factor,
             camera_zoom,
             camera_x,
             camera_y,
-            padding1: 0.0,
-            padding2: 0.0,
-            padding3: 0.0,
+            highlight_x: self.highlight[0],
+            highlight_y: self.highlight[1],
+            highlight_active: self.highlight[2],
+            low_energy_style: self.low_energy_marker[0],
+            low_energy_threshold: self.low_energy_marker[1],
+            _padding: [0.0; 2],
         };
-        self.queue
-            .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
-        // Read entity data (8 floats per entity: prev_x, prev_y, cur_x, cur_y, radius, r, g, b)
+        // Read entity data laid out per `layout` (see `crate::layout_stride`)
+        let stride = crate::layout_stride(layout);
         let entity_data =
-            unsafe { std::slice::from_raw_parts(entities_ptr, (entity_count * 8) as usize) };
-
-        // Convert to instances (Parallel conversion would be nice but requires a buffer)
-        let mut instances = Vec::with_capacity(entity_count as usize);
-
-        for chunk in entity_data.chunks(8) {
-            if chunk.len() < 8 {
-                break;
-            }
-            instances.push(Instance {
-                prev_curr_pos: [chunk[0], chunk[1], chunk[2], chunk[3]],
-                radius_color: [chunk[4], chunk[5], chunk[6], chunk[7]],
-            });
-        }
-
-        self.num_instances = instances.len() as u32;
-
-        // Update instance buffer
-        if !instances.is_empty() {
-            self.queue
-                .write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
-        }
+            unsafe { std::slice::from_raw_parts(entities_ptr, entity_count as usize * stride) };
+        self.pipeline.upload(
+            &self.queue,
+            &uniforms,
+            &instances_from_buffer(entity_data, layout),
+        );
 
         // Render
         let output = match self.surface.get_current_texture() {
@@ -323,33 +172,7 @@ impl WebGpuRenderer {
                 label: Some("Render Encoder"),
             });
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
-
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_bind_group(0, &self.bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
-            render_pass.draw(0..6, 0..self.num_instances);
-        }
-
+        self.pipeline.draw(&mut encoder, &view);
         self.queue.submit(std::iter::once(encoder.finish()));
         output.present();
     }
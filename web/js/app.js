@@ -3,6 +3,7 @@ import init, {
   WebSimulation,
   WebGpuRenderer,
   init_panic_hook,
+  preset_config,
 } from "../pkg/evo.js?v=b250293";
 
 // Shared configuration object - matches the new Rust SimulationConfig structure
@@ -12,6 +13,12 @@ const DEFAULT_CONFIG = {
     max_population: 10000,
     initial_entities: 2500,
     spawn_radius_factor: 0.2,
+    spawn_pattern: "Disc",
+    initial_movement_type_weights: [1.0, 1.0, 1.0, 1.0, 1.0, 1.0],
+    target_population: 0,
+    homeostasis_gain: 2.0,
+    min_viable_population: 0,
+    reseed_count: 5,
   },
   physics: {
     max_velocity: 2.0,
@@ -22,10 +29,41 @@ const DEFAULT_CONFIG = {
     interaction_radius_offset: 15.0,
     velocity_bounce_factor: 0.8,
     center_pressure_strength: 0.3,
+    max_interactions_per_step: 1,
+    substeps: 1,
+    center_pressure_inner_radius: 10.0,
+    edge_ramp_distance: 50.0,
+    edge_multiplier_max: 8.0,
+    soft_walls_only: false,
+    incremental_grid: true,
+    grid_cell_capacity: 0,
+    max_neighbors: 20,
+    velocity_damping: 0.0,
+    symmetric_interactions: false,
+    diffusion_strength: 0.0,
+    query_radius_margin: 0.0,
+    allow_cannibalism: true,
+    kin_recognition_threshold: 0.1,
   },
   energy: {
     size_energy_cost_factor: 0.15,
     movement_energy_cost: 0.1,
+    max_energy_factor: 1.5,
+    sense_cost_factor: 0.002,
+    turning_energy_cost: 0.0,
+    initial_energy_min: 15.0,
+    initial_energy_max: 75.0,
+    max_energy_ratio: 1.3,
+    size_growth: "Linear",
+    size_growth_rate: 0.005,
+    size_asymptote: 15.0,
+    predation_energy_overhead: 0.5,
+    death_energy_threshold: 0.0,
+    metabolic_floor: 0.05,
+    meal_gain_factor: 0.3,
+    prey_size_bonus: 0.3,
+    max_size_bonus: 1.5,
+    starvation_grace: 0,
   },
   reproduction: {
     reproduction_energy_threshold: 0.8,
@@ -35,6 +73,58 @@ const DEFAULT_CONFIG = {
     population_density_factor: 0.8,
     min_reproduction_chance: 0.05,
     death_chance_factor: 0.1,
+    reproduction_cooldown: 0,
+    maturity_age: 0,
+    child_energy_mode: "Reset",
+  },
+  genetics: {
+    diploid: false,
+    dominance: 0.5,
+    mutation_rate_scale: 1.0,
+    generational: false,
+    generation_steps: 500,
+    locked_genes: {},
+    trait_mutation_rates: {},
+  },
+  fitness: {
+    energy_weight: 1.0,
+    lifespan_weight: 0.0,
+    offspring_weight: 0.0,
+  },
+  timing: {
+    steps_per_second: 60.0,
+    max_steps_per_frame: 4,
+    step_budget_ms: 0.0,
+    skip_stats_over_budget: false,
+  },
+  seasons: {
+    enabled: false,
+    period: 3000,
+    movement_cost_amplitude: 0.5,
+    center_pressure_amplitude: 0.0,
+  },
+  behavior: {
+    state_machine: false,
+    rest_energy_fraction: 0.15,
+    mate_energy_fraction: 0.8,
+    rest_speed_factor: 0.2,
+    flock_cohesion_coefficient: 0.1,
+    flock_alignment_coefficient: 0.1,
+    flock_separation_coefficient: 0.2,
+    torpor_threshold: 0.0,
+    torpor_speed_factor: 0.1,
+    predator_speed_multiplier: 1.2,
+    energy_share_amount: 0.0,
+    energy_share_kin_threshold: 0.1,
+  },
+  rendering: {
+    low_energy_style: "None",
+    low_energy_threshold: 0.2,
+    color_mode: "Appearance",
+    population_series_length: 600,
+    population_sample_interval: 10,
+    trails: false,
+    trail_length: 12,
   },
 };
 
@@ -47,7 +137,11 @@ class EvolutionApp {
     this.lastTime = 0;
     this.frameCount = 0;
     this.fps = 0;
-    this.targetFPS = 60;
+
+    // Spatial grid overlay, available only with ?debug in the URL
+    this.debug = new URLSearchParams(window.location.search).has("debug");
+    this.gridOverlay = null;
+    this.trailOverlay = null;
 
     // Camera state
     this.camera = {
@@ -83,7 +177,7 @@ class EvolutionApp {
       this.canvas.width = window.innerWidth;
       this.canvas.height = window.innerHeight;
 
-      const configJson = JSON.stringify(DEFAULT_CONFIG);
+      const configJson = this.configJson();
       console.log("Config being passed to WebSimulation:", configJson);
       this.simulation = new WebSimulation(
         Math.max(this.canvas.width, this.canvas.height),
@@ -99,10 +193,14 @@ class EvolutionApp {
       console.log("WebGPU renderer initialized successfully!");
 
       this.setupEventListeners();
+      this.injectGenesFromUrl();
       this.startRenderLoop();
     } catch (error) {
       console.error("Failed to initialize:", error);
-      this.showError("Failed to initialize simulation: " + error.message);
+      const field = error.field ? ` (${error.field})` : "";
+      this.showError(
+        "Failed to initialize simulation: " + error.message + field
+      );
     }
   }
 
@@ -161,12 +259,39 @@ class EvolutionApp {
       this.simulation.update_param("bounce_factor", value);
     });
 
+    const mutationSlider = document.getElementById("mutation-rate");
+    mutationSlider.addEventListener("input", (e) => {
+      const value = parseFloat(e.target.value);
+      document.getElementById("mutation-value").textContent = value.toFixed(1);
+      this.simulation.update_param("mutation_rate", value);
+    });
+
+    const timeScaleSlider = document.getElementById("time-scale");
+    timeScaleSlider.addEventListener("input", (e) => {
+      const value = parseFloat(e.target.value);
+      document.getElementById("time-scale-value").textContent = value.toFixed(2);
+      this.simulation.set_time_scale(value);
+    });
+
+    // Creature sharing
+    document.getElementById("inject-genes").addEventListener("click", () => {
+      this.injectGenes(document.getElementById("gene-code").value, 10);
+    });
+
     // Keyboard shortcuts
     document.addEventListener("keydown", (e) => {
       if (e.key === "h" || e.key === "H") {
         this.toggleUI();
       } else if (e.key === "r" || e.key === "R") {
         this.reset();
+      } else if (e.key === "f" || e.key === "F") {
+        document.getElementById("hud").classList.toggle("hidden");
+      } else if (e.key === "t" || e.key === "T") {
+        this.toggleTrails();
+      } else if (e.key === "Escape") {
+        this.simulation.unfollow();
+      } else if (this.debug && (e.key === "g" || e.key === "G")) {
+        this.toggleGridOverlay();
       }
     });
 
@@ -180,7 +305,10 @@ class EvolutionApp {
     });
 
     this.canvas.addEventListener("mousedown", (e) => {
-      if (e.button === 0) {
+      if (e.button === 0 && e.shiftKey) {
+        // Shift-click to export the creature under the cursor
+        this.exportGenesAt(e.clientX, e.clientY);
+      } else if (e.button === 0) {
         // Left click to pan
         this.camera.isPanning = true;
         this.camera.lastMouseX = e.clientX;
@@ -188,8 +316,17 @@ class EvolutionApp {
       }
     });
 
+    // Double-click to follow the creature under the cursor
+    this.canvas.addEventListener("dblclick", (e) => {
+      const { x, y } = this.screenToWorld(e.clientX, e.clientY);
+      this.simulation.follow_at(x, y);
+    });
+
     window.addEventListener("mousemove", (e) => {
       if (this.camera.isPanning) {
+        // Dragging the view takes the camera back from the followed creature
+        this.simulation.unfollow();
+
         const dx = (e.clientX - this.camera.lastMouseX) / (this.canvas.width / 2);
         const dy = (e.clientY - this.camera.lastMouseY) / (this.canvas.height / 2);
 
@@ -208,6 +345,122 @@ class EvolutionApp {
     });
   }
 
+  // Inverse of the camera transform in shader.wgsl
+  screenToWorld(clientX, clientY) {
+    const halfWorld = this.simulation.get_world_size() / 2;
+    const ndcX = (clientX / this.canvas.width) * 2 - 1;
+    const ndcY = -((clientY / this.canvas.height) * 2 - 1);
+    return {
+      x: (ndcX / this.camera.zoom - this.camera.x) * halfWorld,
+      y: -(ndcY / this.camera.zoom - this.camera.y) * halfWorld,
+    };
+  }
+
+  // The camera transform in shader.wgsl
+  worldToScreen(x, y) {
+    const halfWorld = this.simulation.get_world_size() / 2;
+    const ndcX = (x / halfWorld + this.camera.x) * this.camera.zoom;
+    const ndcY = (-y / halfWorld + this.camera.y) * this.camera.zoom;
+    return {
+      x: ((ndcX + 1) / 2) * this.canvas.width,
+      y: ((1 - ndcY) / 2) * this.canvas.height,
+    };
+  }
+
+  toggleGridOverlay() {
+    const overlay = document.getElementById("grid-overlay");
+    overlay.classList.toggle("hidden");
+    this.gridOverlay = overlay.classList.contains("hidden")
+      ? null
+      : overlay.getContext("2d");
+  }
+
+  toggleTrails() {
+    const overlay = document.getElementById("trail-overlay");
+    overlay.classList.toggle("hidden");
+    const enabled = !overlay.classList.contains("hidden");
+    this.trailOverlay = enabled ? overlay.getContext("2d") : null;
+    this.simulation.set_trails(enabled);
+  }
+
+  // Match an overlay to the simulation canvas and clear it
+  clearOverlay(ctx) {
+    const { width, height } = this.canvas;
+    if (ctx.canvas.width !== width || ctx.canvas.height !== height) {
+      ctx.canvas.width = width;
+      ctx.canvas.height = height;
+    }
+    ctx.clearRect(0, 0, width, height);
+  }
+
+  // Outline each non-empty grid cell and label it with its entity count
+  drawGridOverlay() {
+    const ctx = this.gridOverlay;
+    this.clearOverlay(ctx);
+    ctx.strokeStyle = "rgba(0, 255, 160, 0.35)";
+    ctx.fillStyle = "rgba(0, 255, 160, 0.8)";
+    ctx.font = "10px monospace";
+
+    for (const cell of this.simulation.get_grid_cells()) {
+      // y grows downward on screen, so the cell's top edge is at y + size
+      const a = this.worldToScreen(cell.x, cell.y + cell.size);
+      const b = this.worldToScreen(cell.x + cell.size, cell.y);
+      ctx.strokeRect(a.x, a.y, b.x - a.x, b.y - a.y);
+      if (b.x - a.x > 16) {
+        ctx.fillText(cell.count, a.x + 2, a.y + 10);
+      }
+    }
+  }
+
+  // Draw each trail from get_trails() as segments fading in towards the entity
+  drawTrails() {
+    const ctx = this.trailOverlay;
+    this.clearOverlay(ctx);
+    ctx.lineWidth = 1.5;
+
+    const trails = this.simulation.get_trails();
+    let i = 0;
+    while (i < trails.length) {
+      const count = trails[i];
+      const [r, g, b] = [trails[i + 1], trails[i + 2], trails[i + 3]].map(
+        (c) => Math.round(c * 255)
+      );
+      const points = i + 4;
+      let prev = this.worldToScreen(trails[points], trails[points + 1]);
+      for (let p = 1; p < count; p++) {
+        const next = this.worldToScreen(
+          trails[points + 2 * p],
+          trails[points + 2 * p + 1]
+        );
+        ctx.strokeStyle = `rgba(${r}, ${g}, ${b}, ${(0.6 * p) / count})`;
+        ctx.beginPath();
+        ctx.moveTo(prev.x, prev.y);
+        ctx.lineTo(next.x, next.y);
+        ctx.stroke();
+        prev = next;
+      }
+      i = points + 2 * count;
+    }
+  }
+
+  exportGenesAt(clientX, clientY) {
+    const { x, y } = this.screenToWorld(clientX, clientY);
+    const code = this.simulation.export_genes_at(x, y);
+    if (code) {
+      document.getElementById("gene-code").value = code;
+      navigator.clipboard?.writeText(code);
+    }
+  }
+
+  injectGenes(code, count) {
+    if (!code) return;
+    try {
+      this.simulation.inject_genes(code, count);
+    } catch (error) {
+      this.showError(error.message);
+    }
+  }
+
   toggleUI() {
     const container = document.querySelector(".container");
     const toggleBtn = document.getElementById("toggle-ui");
@@ -229,11 +482,34 @@ class EvolutionApp {
 
     this.simulation = new WebSimulation(
       Math.max(canvas.width, canvas.height),
-      JSON.stringify(DEFAULT_CONFIG)
+      this.configJson()
     );
+    if (this.trailOverlay) {
+      this.simulation.set_trails(true);
+    }
+    this.injectGenesFromUrl();
     this.updateStats();
   }
 
+  // ?preset=<name> starts from a curated preset instead of the defaults
+  configJson() {
+    const name = new URLSearchParams(window.location.search).get("preset");
+    const preset = name ? preset_config(name) : undefined;
+    if (name && !preset) {
+      console.warn(`Unknown preset "${name}", using the defaults`);
+    }
+    return preset ?? JSON.stringify(DEFAULT_CONFIG);
+  }
+
+  // ?inject=<code>&count=<n> spawns copies of a shared creature at start
+  injectGenesFromUrl() {
+    const params = new URLSearchParams(window.location.search);
+    const code = params.get("inject");
+    if (code) {
+      this.injectGenes(code, parseInt(params.get("count") || "10", 10));
+    }
+  }
+
   startRenderLoop() {
     const animate = (currentTime) => {
       // Calculate FPS
@@ -245,12 +521,10 @@ class EvolutionApp {
         this.updateStats();
       }
 
-      // Update simulation at target FPS
-      const targetInterval = 1000 / this.targetFPS;
-      if (currentTime - this.lastUpdateTime >= targetInterval) {
-        this.simulation.update();
-        this.lastUpdateTime = currentTime;
-      }
+      // Run the fixed-timestep steps due for the real time elapsed
+      this.simulation.tick(currentTime - this.lastFrameTime);
+      this.lastFrameTime = currentTime;
+      this.updateHud();
 
       // Render
       this.render();
@@ -258,7 +532,7 @@ class EvolutionApp {
       this.animationId = requestAnimationFrame(animate);
     };
 
-    this.lastUpdateTime = performance.now();
+    this.lastFrameTime = performance.now();
     this.animationId = requestAnimationFrame(animate);
   }
 
@@ -267,37 +541,59 @@ class EvolutionApp {
       const entityPtr = this.simulation.update_entity_buffer();
       const entityCount = this.simulation.entity_count();
       const worldSize = this.simulation.get_world_size();
-      
-      // Calculate interpolation factor
-      const targetInterval = 1000 / this.targetFPS;
-      const currentTime = performance.now();
-      const interpolationFactor = Math.min(1.0, (currentTime - this.lastUpdateTime) / targetInterval);
-      
+      const interpolationFactor = this.simulation.interpolation_factor();
+      this.followCamera(worldSize / 2);
+      const [markerStyle, markerThreshold] = this.simulation.low_energy_marker();
+      this.renderer.set_low_energy_marker(markerStyle, markerThreshold);
+
       this.renderer.render(
         entityPtr,
         entityCount,
+        this.simulation.buffer_layout(),
         worldSize,
         interpolationFactor,
         this.camera.zoom,
         this.camera.x,
         this.camera.y
       );
+      if (this.trailOverlay) {
+        this.drawTrails();
+      }
+      if (this.gridOverlay) {
+        this.drawGridOverlay();
+      }
+    }
+  }
+
+  // Keep the followed creature centred and ringed; stops once it dies
+  followCamera(halfWorld) {
+    const followed = this.simulation.followed_position();
+    if (followed) {
+      this.camera.x = -followed[0] / halfWorld;
+      this.camera.y = followed[1] / halfWorld;
+      this.renderer.set_highlight(followed[0], followed[1], true);
+    } else {
+      this.renderer.set_highlight(0, 0, false);
     }
   }
 
   updateStats() {
     if (this.simulation) {
-      const stats = this.simulation.get_stats();
-      if (stats) {
-        document.getElementById("population").textContent =
-          stats.total_entities || 0;
-        document.getElementById("step-count").textContent =
-          this.simulation.get_step() || 0;
-        document.getElementById("fps").textContent = this.fps;
-      }
+      document.getElementById("population").textContent =
+        this.simulation.get_population() || 0;
+      document.getElementById("step-count").textContent =
+        this.simulation.get_step() || 0;
+      document.getElementById("fps").textContent = this.fps;
     }
   }
 
+  updateHud() {
+    document.getElementById("hud-fps").textContent = this.fps;
+    document.getElementById("hud-population").textContent =
+      this.simulation.get_population();
+    document.getElementById("hud-step").textContent = this.simulation.get_step();
+  }
+
   showError(message) {
     const errorDiv = document.createElement("div");
     errorDiv.style.cssText = `